@@ -7,6 +7,7 @@ use crate::event::exit_code::ExitCode;
 use crate::event::loop_control::LoopControl;
 use crate::event::loop_state::LoopStateRef;
 use crate::event::main_loop::MainLoop;
+use crate::monitor::Monitor;
 
 #[derive(Clone)]
 pub enum LoopTarget<'main, 'child> {
@@ -17,6 +18,24 @@ impl<'main, 'child> LoopTarget<'main, 'child>
 where
     'child: 'main,
 {
+    fn main(&self) -> &'main MainLoop {
+        match self {
+            LoopTarget::Main(m) => *m,
+            LoopTarget::Child(child_loop) => child_loop.main,
+        }
+    }
+
+    /// Returns all monitors that are connected to the system. On Windows and macOS
+    /// the primary monitor comes first.
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.main().monitors.read().unwrap().clone()
+    }
+
+    /// Returns the monitor on which new surfaces are placed by default
+    pub fn primary_monitor(&self) -> Option<Monitor> {
+        self.main().monitors.read().unwrap().first().cloned()
+    }
+
     /// Because of the special loop structure (theres always a main loop that outlives every child loop)
     /// the callback doesn't need to be static lifetime but only has to fulfill 'main lifetime
     pub fn spawn<F>(&self, callback: F)
@@ -24,10 +43,7 @@ where
         F: for<'new_child> FnOnce(&'new_child ChildLoop<'main>) -> ExitCode + 'main + Send,
     {
         // The main loop lives for 'main
-        let main: &'main MainLoop = match self {
-            LoopTarget::Main(m) => *m,
-            LoopTarget::Child(child_loop) => child_loop.main,
-        };
+        let main: &'main MainLoop = self.main();
 
         let callback_box = unsafe {
             mem::transmute::<
@@ -2,35 +2,87 @@ use std::cell::RefCell;
 use std::process::exit;
 use std::sync::RwLock;
 
-use crate::event::inner::InnerLoop;
+use crate::event::inner::{InnerFlow, InnerLoop};
 use crate::event::loop_control::LoopControl;
 use crate::event::loop_state::LoopStateRef;
 use crate::event::loop_target::LoopTarget;
+use crate::event::record::Recorder;
+use crate::event::replay::Replayer;
 use crate::event::{Event, Flow};
+use crate::monitor::{Monitor, MonitorEvent};
 use crate::platform;
 
 pub struct MainLoop {
     _state: LoopStateRef,
     pub(super) child_loop_controls: RwLock<Vec<LoopControl>>,
     pub(crate) inner: RefCell<platform::event::MainLoop>,
+    /// The monitors are kept up to date by the monitor events so that they can be
+    /// read while the platform loop is processing events
+    pub(super) monitors: RwLock<Vec<Monitor>>,
+    recorder: Option<Recorder>,
+    replayer: Option<Replayer>,
 }
 impl MainLoop {
     pub fn new() -> Self {
+        let inner = platform::event::MainLoop::new();
         MainLoop {
             _state: LoopStateRef::new(),
             child_loop_controls: RwLock::new(vec![]),
-            inner: RefCell::new(platform::event::MainLoop::new()),
+            monitors: RwLock::new(inner.monitors()),
+            inner: RefCell::new(inner),
+            recorder: None,
+            replayer: None,
         }
     }
 
+    /// Records every event that is passed to the callback of [MainLoop::run]
+    pub fn record(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Injects the events of a recording into the callback of [MainLoop::run]
+    /// next to the events of the operating system
+    pub fn replay(&mut self, replayer: Replayer) {
+        self.replayer = Some(replayer);
+    }
+
     pub fn run<'main>(
         &'main mut self,
         mut callback: impl FnMut(&LoopTarget<'main, 'main>, Option<&Event>, &mut Flow),
     ) -> ! {
+        let mut recorder = self.recorder.take();
+        let mut replayer = self.replayer.take();
         let mut flow = Flow::Poll;
         let target = LoopTarget::Main(self);
         let mut emitted = false;
         self.inner.borrow_mut().init(|event| {
+            if let Some(rec) = &mut recorder {
+                // Stop recording after the first failure instead of reporting every event
+                if let Err(err) = rec.record(event) {
+                    eprintln!("Could not record event, recording stopped: {}", err);
+                    recorder = None;
+                }
+            }
+            if let Some(replayer) = &mut replayer {
+                replayer.observe(event);
+            }
+            if let Event::MonitorEvent { id, event } = event {
+                let mut monitors = self.monitors.write().unwrap();
+                let index = monitors.iter().position(|monitor| monitor.id == *id);
+                match (event, index) {
+                    (MonitorEvent::Connected(monitor) | MonitorEvent::Changed(monitor), None) => {
+                        monitors.push(monitor.clone())
+                    }
+                    (
+                        MonitorEvent::Connected(monitor) | MonitorEvent::Changed(monitor),
+                        Some(i),
+                    ) => monitors[i] = monitor.clone(),
+                    (MonitorEvent::Disconnected, Some(i)) => {
+                        monitors.remove(i);
+                    }
+                    (MonitorEvent::Disconnected, None) => {}
+                }
+            }
             callback(&target, Some(event), &mut flow);
             emitted = true;
         });
@@ -39,8 +91,21 @@ impl MainLoop {
                 break exit_code;
             }
             {
+                let mut inner_flow = flow.clone().try_into().unwrap();
+                // Don't block on the operating system while recorded events are still pending
+                if let Some(replayer) = &mut replayer {
+                    if !replayer.is_finished() {
+                        inner_flow = InnerFlow::Poll;
+                    }
+                }
                 let mut mut_guard = self.inner.borrow_mut();
-                mut_guard.process(&flow.clone().try_into().unwrap());
+                mut_guard.process(&inner_flow);
+            }
+            if let Some(replayer) = &mut replayer {
+                while let Some(event) = replayer.poll() {
+                    callback(&target, Some(&event), &mut flow);
+                    emitted = true;
+                }
             }
             if !emitted {
                 callback(&target, None, &mut flow);
@@ -48,6 +113,11 @@ impl MainLoop {
             // Reset emitted boolean to check if an event was emitted
             emitted = false;
         };
+        if let Some(recorder) = &mut recorder {
+            if let Err(err) = recorder.flush() {
+                eprintln!("Could not flush event recording: {}", err);
+            }
+        }
         {
             let mut child_controls = self.child_loop_controls.write().unwrap();
             for ctx in child_controls.drain(..) {
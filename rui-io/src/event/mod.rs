@@ -7,13 +7,18 @@ mod loop_state;
 mod loop_target;
 mod main_loop;
 pub(crate) mod queue;
+mod record;
+mod replay;
 
+use crate::monitor::{MonitorEvent, MonitorId};
 use crate::surface::{SurfaceEvent, SurfaceId};
 
 pub use child_loop::ChildLoop as ChildEventLoop;
 pub use flow::Flow;
 pub use loop_target::LoopTarget as EventLoopTarget;
 pub use main_loop::MainLoop as MainEventLoop;
+pub use record::{ParseError as RecordingParseError, RecordedEvent, Recorder, Recording};
+pub use replay::Replayer;
 
 pub use loop_target::LoopTarget;
 
@@ -21,6 +26,7 @@ pub use loop_target::LoopTarget;
 pub enum Event {
     Init,
     SurfaceEvent { id: SurfaceId, event: SurfaceEvent },
+    MonitorEvent { id: MonitorId, event: MonitorEvent },
     EventsCleared,
     Default,
 }
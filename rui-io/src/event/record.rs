@@ -0,0 +1,358 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+use std::time::{Duration, Instant};
+
+use rui_util::Extent;
+
+use crate::event::Event;
+use crate::monitor::MonitorEvent;
+use crate::surface::{SurfaceEvent, SurfaceId, TiledEdges};
+
+/// A single event together with the time at which it was seen by the loop,
+/// relative to the start of the recording.
+#[derive(Clone, Debug)]
+pub struct RecordedEvent {
+    pub timestamp: Duration,
+    pub event: Event,
+}
+
+/// Recorder writes every event it is given into a line based text format.
+///
+/// Each line holds the timestamp in microseconds followed by the event, e.g.
+/// `1520 surface 3 resized 900 900`. The format is intentionally simple so that
+/// recordings attached to bug reports can be read and edited by hand.
+///
+/// Monitor events are written as comments because they describe the machine the
+/// recording was made on rather than the interaction with the application, e.g.
+/// `# 20 monitor 1 connected`. They help to read a recording but aren't replayed.
+pub struct Recorder {
+    writer: Box<dyn Write>,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Creates a recorder that writes into the given writer
+    pub fn new(writer: impl Write + 'static) -> Self {
+        Recorder {
+            writer: Box::new(writer),
+            start: Instant::now(),
+        }
+    }
+
+    /// Creates (or truncates) the file at `path` and records into it
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Recorder::new(BufWriter::new(file)))
+    }
+
+    /// Appends the event to the recording with the elapsed time since the recorder
+    /// was created
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        let recorded = RecordedEvent {
+            timestamp: self.start.elapsed(),
+            event: event.clone(),
+        };
+        writeln!(self.writer, "{}", recorded)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Nothing sensible can be done with an error at this point
+        let _ = self.writer.flush();
+    }
+}
+
+/// A recording is the parsed content of a file written by a [Recorder]
+#[derive(Clone, Debug, Default)]
+pub struct Recording {
+    events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        Recording { events }
+    }
+
+    /// Loads the recording stored at `path`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Recording::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Parses a recording line by line. Empty lines and lines starting with `#` are skipped.
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut events = vec![];
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.parse::<RecordedEvent>() {
+                Ok(event) => events.push(event),
+                Err(err) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: {}", number + 1, err),
+                    ))
+                }
+            }
+        }
+        Ok(Recording { events })
+    }
+
+    pub fn events(&self) -> &Vec<RecordedEvent> {
+        &self.events
+    }
+}
+
+impl IntoIterator for Recording {
+    type Item = RecordedEvent;
+    type IntoIter = std::vec::IntoIter<RecordedEvent>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+impl fmt::Display for RecordedEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Event::MonitorEvent { .. } = self.event {
+            write!(f, "# ")?;
+        }
+        write!(f, "{} ", self.timestamp.as_micros())?;
+        match &self.event {
+            Event::Init => write!(f, "init"),
+            Event::SurfaceEvent { id, event } => {
+                write!(f, "surface {} ", u64::from(*id))?;
+                match event {
+                    SurfaceEvent::Resized(extent) => {
+                        write!(f, "resized {} {}", extent.width, extent.height)
+                    }
+                    SurfaceEvent::Redraw => write!(f, "redraw"),
+                    SurfaceEvent::Frame { timestamp } => {
+                        write!(f, "frame {}", timestamp.as_micros())
+                    }
+                    SurfaceEvent::ShouldClose => write!(f, "close"),
+                    SurfaceEvent::Focused(focused) => write!(f, "focused {}", focused),
+                    SurfaceEvent::Maximized(maximized) => write!(f, "maximized {}", maximized),
+                    SurfaceEvent::Fullscreen(fullscreen) => {
+                        write!(f, "fullscreen {}", fullscreen)
+                    }
+                    SurfaceEvent::Tiled(edges) => write!(
+                        f,
+                        "tiled {} {} {} {}",
+                        edges.top, edges.right, edges.bottom, edges.left
+                    ),
+                    SurfaceEvent::Suspended(suspended) => write!(f, "suspended {}", suspended),
+                }
+            }
+            Event::MonitorEvent { id, event } => {
+                write!(f, "monitor {} ", u64::from(*id))?;
+                match event {
+                    MonitorEvent::Connected(_) => write!(f, "connected"),
+                    MonitorEvent::Changed(_) => write!(f, "changed"),
+                    MonitorEvent::Disconnected => write!(f, "disconnected"),
+                }
+            }
+            Event::EventsCleared => write!(f, "cleared"),
+            Event::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Error that occurs when a line of a recording can't be parsed
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn next<'a>(parts: &mut SplitWhitespace<'a>, what: &str) -> Result<&'a str, ParseError> {
+    parts
+        .next()
+        .ok_or_else(|| ParseError(format!("missing {}", what)))
+}
+
+/// Parses the next part, which is used for numbers as well as booleans
+fn number<T: FromStr>(parts: &mut SplitWhitespace, what: &str) -> Result<T, ParseError> {
+    let part = next(parts, what)?;
+    part.parse()
+        .map_err(|_| ParseError(format!("invalid {} `{}`", what, part)))
+}
+
+impl FromStr for RecordedEvent {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let timestamp = Duration::from_micros(number(&mut parts, "timestamp")?);
+        let event = match next(&mut parts, "event")? {
+            "init" => Event::Init,
+            "cleared" => Event::EventsCleared,
+            "default" => Event::Default,
+            "surface" => {
+                let id = SurfaceId::from(number::<u64>(&mut parts, "surface id")?);
+                let event = match next(&mut parts, "surface event")? {
+                    "resized" => SurfaceEvent::Resized(Extent {
+                        width: number(&mut parts, "width")?,
+                        height: number(&mut parts, "height")?,
+                    }),
+                    "redraw" => SurfaceEvent::Redraw,
+                    "frame" => SurfaceEvent::Frame {
+                        timestamp: Duration::from_micros(number(&mut parts, "frame timestamp")?),
+                    },
+                    "close" => SurfaceEvent::ShouldClose,
+                    "focused" => SurfaceEvent::Focused(number(&mut parts, "focus")?),
+                    "maximized" => SurfaceEvent::Maximized(number(&mut parts, "maximized")?),
+                    "fullscreen" => SurfaceEvent::Fullscreen(number(&mut parts, "fullscreen")?),
+                    "tiled" => SurfaceEvent::Tiled(TiledEdges {
+                        top: number(&mut parts, "top edge")?,
+                        right: number(&mut parts, "right edge")?,
+                        bottom: number(&mut parts, "bottom edge")?,
+                        left: number(&mut parts, "left edge")?,
+                    }),
+                    "suspended" => SurfaceEvent::Suspended(number(&mut parts, "suspended")?),
+                    other => return Err(ParseError(format!("unknown surface event `{}`", other))),
+                };
+                Event::SurfaceEvent { id, event }
+            }
+            "monitor" => return Err(ParseError("monitor events can't be replayed".to_string())),
+            other => return Err(ParseError(format!("unknown event `{}`", other))),
+        };
+        if let Some(rest) = parts.next() {
+            return Err(ParseError(format!("unexpected trailing `{}`", rest)));
+        }
+        Ok(RecordedEvent { timestamp, event })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordedEvent, Recording};
+    use crate::event::Event;
+    use crate::monitor::{Monitor, MonitorEvent, MonitorId};
+    use crate::surface::{SurfaceEvent, SurfaceId, TiledEdges};
+    use rui_util::{Extent, Offset};
+    use std::time::Duration;
+
+    fn recorded(event: Event) -> RecordedEvent {
+        RecordedEvent {
+            timestamp: Duration::from_micros(1520),
+            event,
+        }
+    }
+
+    fn surface(event: SurfaceEvent) -> Event {
+        Event::SurfaceEvent {
+            id: SurfaceId::from(3),
+            event,
+        }
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let events = [
+            Event::Init,
+            Event::EventsCleared,
+            Event::Default,
+            surface(SurfaceEvent::Resized(Extent {
+                width: 900,
+                height: 600,
+            })),
+            surface(SurfaceEvent::Redraw),
+            surface(SurfaceEvent::Frame {
+                timestamp: Duration::from_micros(16_667),
+            }),
+            surface(SurfaceEvent::ShouldClose),
+            surface(SurfaceEvent::Focused(true)),
+            surface(SurfaceEvent::Maximized(false)),
+            surface(SurfaceEvent::Fullscreen(true)),
+            surface(SurfaceEvent::Tiled(TiledEdges {
+                top: true,
+                right: false,
+                bottom: true,
+                left: false,
+            })),
+            surface(SurfaceEvent::Suspended(true)),
+        ];
+        for event in events {
+            let line = recorded(event).to_string();
+            let parsed = line.parse::<RecordedEvent>().unwrap();
+            assert_eq!(parsed.timestamp, Duration::from_micros(1520));
+            assert_eq!(parsed.to_string(), line);
+        }
+        assert_eq!(
+            recorded(surface(SurfaceEvent::Resized(Extent {
+                width: 900,
+                height: 600
+            })))
+            .to_string(),
+            "1520 surface 3 resized 900 600"
+        );
+    }
+
+    #[test]
+    fn monitor_events_are_comments() {
+        let monitor = Monitor {
+            id: MonitorId::from(1),
+            name: "HDMI-A-1".to_string(),
+            position: Offset { x: 0, y: 0 },
+            size: Extent {
+                width: 1920,
+                height: 1080,
+            },
+            scale_factor: 1.0,
+            refresh_rate: Some(60_000),
+            modes: vec![],
+        };
+        let events = [
+            MonitorEvent::Connected(monitor.clone()),
+            MonitorEvent::Changed(monitor),
+            MonitorEvent::Disconnected,
+        ];
+        let mut text = String::new();
+        for event in events {
+            let id = MonitorId::from(1);
+            text += &recorded(Event::MonitorEvent { id, event }).to_string();
+            text += "\n";
+        }
+        text += &recorded(Event::Init).to_string();
+        assert!(text.starts_with("# 1520 monitor 1 connected\n"));
+        let recording = Recording::read(text.as_bytes()).unwrap();
+        assert_eq!(recording.events().len(), 1);
+        assert!(matches!(recording.events()[0].event, Event::Init));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        for line in [
+            "",
+            "surface 3 redraw",
+            "1520 unknown",
+            "1520 surface 3",
+            "1520 surface 3 resized 900",
+            "1520 surface 3 focused maybe",
+            "1520 surface 3 redraw now",
+            "1520 monitor 1 connected",
+        ] {
+            assert!(line.parse::<RecordedEvent>().is_err(), "{}", line);
+        }
+        let err = Recording::read("1 init\n\n2 nope\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 3:"), "{}", err);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::event::record::{RecordedEvent, Recording};
+use crate::event::Event;
+use crate::surface::{SurfaceEvent, SurfaceId};
+
+/// Replayer feeds the events of a [Recording] back into a loop while honoring
+/// the recorded timestamps.
+///
+/// Surface ids are assigned by the operating system and usually differ between two
+/// runs. Therefore recorded ids are mapped onto the ids of live surfaces in the order
+/// in which both appear for the first time. Events of a recorded surface that has no
+/// live counterpart yet are queued until one appears.
+pub struct Replayer {
+    events: std::iter::Peekable<std::vec::IntoIter<RecordedEvent>>,
+    start: Option<Instant>,
+    speed: f64,
+    recorded_ids: Vec<SurfaceId>,
+    live_ids: Vec<SurfaceId>,
+    id_map: HashMap<SurfaceId, SurfaceId>,
+    /// Due events of recorded surfaces that aren't mapped yet, in recorded order
+    queued: VecDeque<(SurfaceId, SurfaceEvent)>,
+}
+
+impl Replayer {
+    pub fn new(recording: Recording) -> Self {
+        Replayer {
+            events: recording.into_iter().peekable(),
+            start: None,
+            speed: 1.0,
+            recorded_ids: vec![],
+            live_ids: vec![],
+            id_map: HashMap::new(),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Changes the playback speed. A speed of `2.0` replays the events twice as fast,
+    /// `f64::INFINITY` replays them as fast as possible.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(speed > 0.0, "Replay speed must be positive!");
+        self.speed = speed;
+        self
+    }
+
+    /// Determines whether there are events left to replay. Queued events don't count,
+    /// they are released by the live event that reveals their surface.
+    pub fn is_finished(&mut self) -> bool {
+        self.events.peek().is_none()
+    }
+
+    /// Informs the replayer about an event that was emitted by the live loop so that
+    /// newly created surfaces can be mapped onto recorded ones
+    pub(crate) fn observe(&mut self, event: &Event) {
+        if let Event::SurfaceEvent { id, .. } = event {
+            if !self.live_ids.contains(id) {
+                self.live_ids.push(*id);
+                self.update_id_map();
+            }
+        }
+    }
+
+    fn update_id_map(&mut self) {
+        for (recorded, live) in self.recorded_ids.iter().zip(self.live_ids.iter()) {
+            self.id_map.insert(*recorded, *live);
+        }
+    }
+
+    /// Returns the next event if it is due. The time starts with the first call.
+    ///
+    /// [Event::Init] is never replayed because the live loop already emitted it.
+    pub fn poll(&mut self) -> Option<Event> {
+        let id_map = &self.id_map;
+        if let Some(index) = self
+            .queued
+            .iter()
+            .position(|(id, _)| id_map.contains_key(id))
+        {
+            let (id, event) = self.queued.remove(index).unwrap();
+            let id = self.id_map[&id];
+            return Some(Event::SurfaceEvent { id, event });
+        }
+        let start = *self.start.get_or_insert_with(Instant::now);
+        loop {
+            let due = match self.events.peek() {
+                None => return None,
+                Some(recorded) => {
+                    Duration::from_secs_f64(recorded.timestamp.as_secs_f64() / self.speed)
+                }
+            };
+            if start.elapsed() < due {
+                return None;
+            }
+            let RecordedEvent { event, .. } = self.events.next().unwrap();
+            match event {
+                Event::Init => continue,
+                Event::SurfaceEvent { id, event } => {
+                    if !self.recorded_ids.contains(&id) {
+                        self.recorded_ids.push(id);
+                        self.update_id_map();
+                    }
+                    match self.id_map.get(&id) {
+                        Some(id) => return Some(Event::SurfaceEvent { id: *id, event }),
+                        None => self.queued.push_back((id, event)),
+                    }
+                }
+                event => return Some(event),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Replayer;
+    use crate::event::record::{RecordedEvent, Recording};
+    use crate::event::Event;
+    use crate::surface::{SurfaceEvent, SurfaceId};
+    use std::time::Duration;
+
+    fn surface(id: u64, event: SurfaceEvent) -> Event {
+        Event::SurfaceEvent {
+            id: SurfaceId::from(id),
+            event,
+        }
+    }
+
+    fn replayer(events: Vec<Event>) -> Replayer {
+        let events = events
+            .into_iter()
+            .map(|event| RecordedEvent {
+                timestamp: Duration::ZERO,
+                event,
+            })
+            .collect();
+        Replayer::new(Recording::new(events)).speed(f64::INFINITY)
+    }
+
+    fn id(event: Option<Event>) -> Option<u64> {
+        match event {
+            Some(Event::SurfaceEvent { id, .. }) => Some(id.into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn recorded_ids_map_onto_live_ids_in_order() {
+        let mut replayer = replayer(vec![
+            Event::Init,
+            surface(7, SurfaceEvent::Redraw),
+            surface(9, SurfaceEvent::Redraw),
+            surface(7, SurfaceEvent::ShouldClose),
+        ]);
+        replayer.observe(&surface(30, SurfaceEvent::Redraw));
+        replayer.observe(&surface(40, SurfaceEvent::Redraw));
+        // Seeing a surface again doesn't change the mapping
+        replayer.observe(&surface(30, SurfaceEvent::Redraw));
+        assert_eq!(id(replayer.poll()), Some(30));
+        assert_eq!(id(replayer.poll()), Some(40));
+        assert_eq!(id(replayer.poll()), Some(30));
+        assert!(replayer.poll().is_none());
+        assert!(replayer.is_finished());
+    }
+
+    #[test]
+    fn events_of_unmapped_surfaces_wait_for_a_live_surface() {
+        let mut replayer = replayer(vec![
+            surface(7, SurfaceEvent::Redraw),
+            Event::EventsCleared,
+            surface(7, SurfaceEvent::ShouldClose),
+        ]);
+        assert!(matches!(replayer.poll(), Some(Event::EventsCleared)));
+        assert!(replayer.poll().is_none());
+        assert!(replayer.is_finished());
+
+        replayer.observe(&surface(30, SurfaceEvent::Redraw));
+        assert!(matches!(
+            replayer.poll(),
+            Some(Event::SurfaceEvent {
+                event: SurfaceEvent::Redraw,
+                ..
+            })
+        ));
+        assert!(matches!(
+            replayer.poll(),
+            Some(Event::SurfaceEvent {
+                event: SurfaceEvent::ShouldClose,
+                ..
+            })
+        ));
+        assert!(replayer.poll().is_none());
+    }
+}
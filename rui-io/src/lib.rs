@@ -21,6 +21,8 @@ extern crate lazy_static;
 /// This module contains the low level implementation of an event loop aswell as a definition
 /// of events.
 pub mod event;
+/// This module allows to enumerate the monitors that are connected to the system.
+pub mod monitor;
 mod os_error;
 mod platform;
 /// This module offers the low level implementation of a surface with a drawable area.
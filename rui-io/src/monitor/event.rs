@@ -0,0 +1,11 @@
+use crate::monitor::Monitor;
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// A monitor got plugged in
+    Connected(Monitor),
+    /// The geometry, scale or mode of a monitor changed
+    Changed(Monitor),
+    /// A monitor got unplugged
+    Disconnected,
+}
@@ -0,0 +1,15 @@
+use std::hash::Hash;
+
+#[derive(Hash, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Id(u64);
+
+impl From<u64> for Id {
+    fn from(id: u64) -> Self {
+        Id(id)
+    }
+}
+impl From<Id> for u64 {
+    fn from(id: Id) -> Self {
+        id.0
+    }
+}
@@ -0,0 +1,72 @@
+use rui_util::{Extent, Offset};
+
+pub use event::Event as MonitorEvent;
+pub use id::Id as MonitorId;
+
+mod event;
+pub mod id;
+
+/// A display that is connected to the system.
+///
+/// The position and size are given in physical pixels in the coordinate space of the
+/// desktop, which allows to place surfaces relative to each other.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    pub id: MonitorId,
+    /// Human readable name of the monitor, e.g. `HDMI-A-1` or the model name
+    pub name: String,
+    pub position: Offset,
+    pub size: Extent,
+    pub scale_factor: f64,
+    /// Refresh rate of the current mode in millihertz
+    pub refresh_rate: Option<u32>,
+    /// All video modes the monitor supports
+    pub modes: Vec<VideoMode>,
+}
+
+impl Monitor {
+    /// Determines whether the monitor contains the given point of the desktop
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.position.x
+            && y >= self.position.y
+            && ((x - self.position.x) as u32) < self.size.width
+            && ((y - self.position.y) as u32) < self.size.height
+    }
+}
+
+/// A resolution and refresh rate combination a monitor can be driven with
+#[derive(Debug, Clone, Copy)]
+pub struct VideoMode {
+    pub size: Extent,
+    /// Refresh rate in millihertz
+    pub refresh_rate: u32,
+}
+
+/// Determines the events that lead from the `old` to the `new` set of monitors.
+/// Used on platforms which only report that the monitor configuration changed.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn changes(old: &[Monitor], new: &[Monitor]) -> Vec<(MonitorId, MonitorEvent)> {
+    let mut changes = vec![];
+    for monitor in old {
+        if !new.iter().any(|m| m.id == monitor.id) {
+            changes.push((monitor.id, MonitorEvent::Disconnected));
+        }
+    }
+    for monitor in new {
+        match old.iter().find(|m| m.id == monitor.id) {
+            None => changes.push((monitor.id, MonitorEvent::Connected(monitor.clone()))),
+            Some(old) => {
+                if old.position.x != monitor.position.x
+                    || old.position.y != monitor.position.y
+                    || old.size.width != monitor.size.width
+                    || old.size.height != monitor.size.height
+                    || old.scale_factor != monitor.scale_factor
+                    || old.refresh_rate != monitor.refresh_rate
+                {
+                    changes.push((monitor.id, MonitorEvent::Changed(monitor.clone())));
+                }
+            }
+        }
+    }
+    changes
+}
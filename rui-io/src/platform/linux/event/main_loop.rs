@@ -1,17 +1,22 @@
 use crate::event::inner::{InnerFlow, InnerLoop};
 use crate::event::Event;
-use crate::surface::SurfaceId;
+use crate::monitor::{Monitor, MonitorEvent, MonitorId};
+use crate::platform::platform::monitor;
+use crate::surface::{SurfaceEvent, SurfaceId, TiledEdges};
 use rui_util::Extent;
 use smithay_client_toolkit::environment::Environment;
+use smithay_client_toolkit::output::OutputStatusListener;
 use smithay_client_toolkit::reexports::client::Display;
 use smithay_client_toolkit::shell::Shell;
-use smithay_client_toolkit::window::{FallbackFrame, Window};
+use smithay_client_toolkit::window::{FallbackFrame, State, Window};
 use smithay_client_toolkit::{default_environment, new_default_environment};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
+use wayland_client::protocol::wl_callback;
 use wayland_client::EventQueue;
 
 default_environment!(MyApp, desktop);
@@ -19,15 +24,61 @@ default_environment!(MyApp, desktop);
 pub enum NextAction {
     None,
     Refresh,
-    Redraw,
     Resize,
     Close,
 }
 
+/// Changes of the window that were requested through the surface and are applied
+/// the next time the main loop processes the window
+pub enum WindowRequest {
+    Title(String),
+    MinSize(Option<(u32, u32)>),
+    MaxSize(Option<(u32, u32)>),
+    Fullscreen(bool),
+    Maximized(bool),
+    Minimize,
+}
+
+/// The xdg toplevel states of a window which are reported to the application
+#[derive(Clone, Copy, Default, PartialEq)]
+struct ToplevelStates {
+    activated: bool,
+    maximized: bool,
+    fullscreen: bool,
+    tiled: TiledEdges,
+}
+
+impl ToplevelStates {
+    fn from_states(states: &[State]) -> Self {
+        let mut result = ToplevelStates::default();
+        for state in states {
+            match state {
+                State::Activated => result.activated = true,
+                State::Maximized => result.maximized = true,
+                State::Fullscreen => result.fullscreen = true,
+                State::TiledTop => result.tiled.top = true,
+                State::TiledRight => result.tiled.right = true,
+                State::TiledBottom => result.tiled.bottom = true,
+                State::TiledLeft => result.tiled.left = true,
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
 pub struct WindowStateShared {
     next_action: NextAction,
     drawen_once: bool,
     size: Extent,
+    requests: Vec<WindowRequest>,
+    states: ToplevelStates,
+    events: Vec<SurfaceEvent>,
+    /// Redraw requests are coalesced until the compositor is ready for a new frame
+    redraw_requested: bool,
+    /// A frame callback was requested and the compositor didn't answer it yet
+    frame_pending: bool,
+    frame_done: Option<u32>,
 }
 impl WindowStateShared {
     pub fn new(size: Extent) -> Self {
@@ -35,6 +86,12 @@ impl WindowStateShared {
             next_action: NextAction::None,
             drawen_once: false,
             size,
+            requests: vec![],
+            states: ToplevelStates::default(),
+            events: vec![],
+            redraw_requested: false,
+            frame_pending: false,
+            frame_done: None,
         }
     }
 
@@ -59,7 +116,29 @@ impl WindowStateShared {
     }
 
     pub fn signal_should_redraw(&mut self) {
-        self.next_action = NextAction::Redraw
+        self.redraw_requested = true;
+    }
+
+    /// Called when the compositor answered the frame callback with its timestamp
+    pub fn signal_frame_done(&mut self, time: u32) {
+        self.frame_pending = false;
+        self.frame_done = Some(time);
+    }
+
+    pub fn take_frame_done(&mut self) -> Option<u32> {
+        self.frame_done.take()
+    }
+
+    /// Determines whether the window should be redrawn now. This is the case if a
+    /// redraw was requested and the compositor isn't still busy with the last frame.
+    pub fn take_redraw(&mut self) -> bool {
+        if self.redraw_requested && !self.frame_pending {
+            self.redraw_requested = false;
+            self.frame_pending = true;
+            true
+        } else {
+            false
+        }
     }
 
     pub fn signal_should_refresh(&mut self) {
@@ -70,6 +149,37 @@ impl WindowStateShared {
         self.next_action = NextAction::Resize
     }
 
+    pub fn push_request(&mut self, request: WindowRequest) {
+        self.requests.push(request);
+    }
+
+    pub fn take_requests(&mut self) -> Vec<WindowRequest> {
+        mem::take(&mut self.requests)
+    }
+
+    /// Compares the states of a configure event with the previous ones and queues
+    /// an event for every state that changed
+    pub fn set_states(&mut self, states: &[State]) {
+        let new = ToplevelStates::from_states(states);
+        let old = mem::replace(&mut self.states, new);
+        if old.activated != new.activated {
+            self.events.push(SurfaceEvent::Focused(new.activated));
+        }
+        if old.maximized != new.maximized {
+            self.events.push(SurfaceEvent::Maximized(new.maximized));
+        }
+        if old.fullscreen != new.fullscreen {
+            self.events.push(SurfaceEvent::Fullscreen(new.fullscreen));
+        }
+        if old.tiled != new.tiled {
+            self.events.push(SurfaceEvent::Tiled(new.tiled));
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<SurfaceEvent> {
+        mem::take(&mut self.events)
+    }
+
     pub fn take_next_action(&mut self) -> NextAction {
         let mut next = NextAction::None;
         mem::swap(&mut next, &mut self.next_action);
@@ -88,12 +198,33 @@ impl WindowState {
     }
 }
 
+/// Applies the requested changes to the xdg toplevel of the window.
+/// Returns whether any request was applied.
+fn apply_requests(window: &mut Window<FallbackFrame>, requests: Vec<WindowRequest>) -> bool {
+    let applied = !requests.is_empty();
+    for request in requests {
+        match request {
+            WindowRequest::Title(title) => window.set_title(title),
+            WindowRequest::MinSize(size) => window.set_min_size(size),
+            WindowRequest::MaxSize(size) => window.set_max_size(size),
+            WindowRequest::Fullscreen(true) => window.set_fullscreen(None),
+            WindowRequest::Fullscreen(false) => window.unset_fullscreen(),
+            WindowRequest::Maximized(true) => window.set_maximized(),
+            WindowRequest::Maximized(false) => window.unset_maximized(),
+            WindowRequest::Minimize => window.set_minimized(),
+        }
+    }
+    applied
+}
+
 pub struct MainLoop {
     pub(crate) wl_display: Display,
     main_event_queue: EventQueue,
     pub(crate) windows: HashMap<SurfaceId, WindowState>,
     environment: Environment<MyApp>,
     callback: Option<Rc<RefCell<dyn FnMut(&Event)>>>,
+    _output_listener: OutputStatusListener,
+    monitor_events: Rc<RefCell<Vec<(MonitorId, MonitorEvent)>>>,
 }
 
 #[cfg(debug_assertions)]
@@ -164,15 +295,41 @@ impl MainLoop {
         #[cfg(debug_assertions)]
         debug_printout(&environment);
 
+        // The listener is only called for changes that happen after this point
+        let monitor_events = Rc::new(RefCell::new(vec![]));
+        let mut known: HashSet<MonitorId> = monitor::monitors(&environment.get_all_outputs())
+            .iter()
+            .map(|monitor| monitor.id)
+            .collect();
+        let events = monitor_events.clone();
+        let output_listener = environment.listen_for_outputs(move |_, info, _| {
+            let id = monitor::monitor_id(info);
+            let event = if info.obsolete {
+                known.remove(&id);
+                MonitorEvent::Disconnected
+            } else if known.insert(id) {
+                MonitorEvent::Connected(monitor::monitor(info))
+            } else {
+                MonitorEvent::Changed(monitor::monitor(info))
+            };
+            events.borrow_mut().push((id, event));
+        });
+
         MainLoop {
             wl_display: display,
             main_event_queue: queue,
             windows: HashMap::new(),
             environment,
             callback: None,
+            _output_listener: output_listener,
+            monitor_events,
         }
     }
 
+    pub fn monitors(&self) -> Vec<Monitor> {
+        monitor::monitors(&self.environment.get_all_outputs())
+    }
+
     pub fn get_environment(&self) -> &Environment<MyApp> {
         &self.environment
     }
@@ -215,44 +372,70 @@ impl InnerLoop for MainLoop {
         let mut followup_map = HashMap::new();
 
         for (id, mut window) in self.windows.drain() {
-            {
+            // The events are emitted after the shared state was released because
+            // the callback may call into the surface
+            let mut events = vec![];
+            let redraw = {
                 let mut shared = window.shared.as_ref().borrow_mut();
+                if apply_requests(&mut window.window, shared.take_requests()) {
+                    window.window.refresh();
+                    window.window.surface().commit();
+                }
+                events.append(&mut shared.take_events());
+                if let Some(time) = shared.take_frame_done() {
+                    events.push(SurfaceEvent::Frame {
+                        timestamp: Duration::from_millis(time as u64),
+                    });
+                }
                 match shared.take_next_action() {
                     NextAction::None => {}
                     NextAction::Refresh => {
                         window.window.refresh();
                         window.window.surface().commit();
                     }
-                    NextAction::Redraw => {
-                        shared.signal_drawen_once();
-                        // Surface::redraw(&mut self.pool, window.window.surface(), shared.size.width, shared.size.height)
-                        (self.callback.as_ref().unwrap().as_ref().borrow_mut())(
-                            &Event::SurfaceEvent {
-                                id,
-                                event: crate::surface::event::Event::Redraw,
-                            },
-                        );
-                        window.window.refresh();
-                        window.window.surface().commit();
-                    }
                     NextAction::Close => {
                         continue;
                     }
                     NextAction::Resize => {
                         window.window.resize(shared.size.width, shared.size.height);
                         window.window.refresh();
-                        (self.callback.as_ref().unwrap().as_ref().borrow_mut())(
-                            &Event::SurfaceEvent {
-                                id,
-                                event: crate::surface::event::Event::Resized(Extent {
-                                    width: shared.size.width,
-                                    height: shared.size.height,
-                                }),
-                            },
-                        );
-                        window.window.surface().commit();
+                        events.push(SurfaceEvent::Resized(Extent {
+                            width: shared.size.width,
+                            height: shared.size.height,
+                        }));
+                        // The new size has to be presented
+                        shared.signal_should_redraw();
                     }
                 }
+                let redraw = shared.take_redraw();
+                if redraw {
+                    shared.signal_drawen_once();
+                }
+                redraw
+            };
+            if redraw {
+                // The frame callback is attached to the commit that presents the frame
+                let frame_shared = window.shared.clone();
+                window
+                    .window
+                    .surface()
+                    .frame()
+                    .quick_assign(move |_, event, _| {
+                        if let wl_callback::Event::Done { callback_data } = event {
+                            frame_shared.borrow_mut().signal_frame_done(callback_data);
+                        }
+                    });
+                events.push(SurfaceEvent::Redraw);
+            }
+            for event in events {
+                (self.callback.as_ref().unwrap().as_ref().borrow_mut())(&Event::SurfaceEvent {
+                    id,
+                    event,
+                });
+            }
+            if redraw {
+                window.window.refresh();
+                window.window.surface().commit();
             }
             followup_map.insert(id, window);
         }
@@ -306,5 +489,13 @@ impl InnerLoop for MainLoop {
                 }
             }
         }
+
+        let monitor_events = mem::take(&mut *self.monitor_events.borrow_mut());
+        for (id, event) in monitor_events {
+            (self.callback.as_ref().unwrap().as_ref().borrow_mut())(&Event::MonitorEvent {
+                id,
+                event,
+            });
+        }
     }
 }
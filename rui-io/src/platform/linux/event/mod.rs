@@ -3,5 +3,6 @@ mod main_loop;
 
 pub use child_loop::ChildLoop;
 pub use main_loop::MainLoop;
+pub(crate) use main_loop::WindowRequest;
 pub(crate) use main_loop::WindowState;
 pub(crate) use main_loop::WindowStateShared;
@@ -0,0 +1,70 @@
+use crate::monitor::{Monitor, MonitorId, VideoMode};
+use rui_util::{Extent, Offset};
+use smithay_client_toolkit::output::{with_output_info, OutputInfo};
+use wayland_client::protocol::wl_output::WlOutput;
+
+pub fn monitor_id(info: &OutputInfo) -> MonitorId {
+    MonitorId::from(info.id as u64)
+}
+
+fn video_mode(dimensions: (i32, i32), refresh_rate: i32) -> VideoMode {
+    VideoMode {
+        size: Extent {
+            width: dimensions.0.max(0) as u32,
+            height: dimensions.1.max(0) as u32,
+        },
+        refresh_rate: refresh_rate.max(0) as u32,
+    }
+}
+
+pub fn monitor(info: &OutputInfo) -> Monitor {
+    let current = info.modes.iter().find(|mode| mode.is_current);
+    // The name is only advertised by newer compositors
+    let name = if info.name.is_empty() {
+        format!("{} {}", info.make, info.model)
+    } else {
+        info.name.clone()
+    };
+    Monitor {
+        id: monitor_id(info),
+        name,
+        // The location is in the logical space of the compositor, the modes are in
+        // physical pixels
+        position: Offset {
+            x: info.location.0 * info.scale_factor,
+            y: info.location.1 * info.scale_factor,
+        },
+        size: current
+            .map(|mode| video_mode(mode.dimensions, mode.refresh_rate).size)
+            .unwrap_or(Extent {
+                width: 0,
+                height: 0,
+            }),
+        scale_factor: info.scale_factor as f64,
+        refresh_rate: current.map(|mode| mode.refresh_rate.max(0) as u32),
+        modes: info
+            .modes
+            .iter()
+            .map(|mode| video_mode(mode.dimensions, mode.refresh_rate))
+            .collect(),
+    }
+}
+
+/// Returns the monitors of the outputs that are still advertised by the compositor.
+/// Wayland has no notion of a primary output, therefore they are in the order
+/// the compositor advertised them.
+pub fn monitors(outputs: &[WlOutput]) -> Vec<Monitor> {
+    outputs
+        .iter()
+        .filter_map(|output| {
+            with_output_info(output, |info| {
+                if info.obsolete {
+                    None
+                } else {
+                    Some(monitor(info))
+                }
+            })
+            .flatten()
+        })
+        .collect()
+}
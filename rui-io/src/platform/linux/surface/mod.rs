@@ -1,10 +1,16 @@
 use crate::event::LoopTarget;
-use crate::platform::event::{WindowState, WindowStateShared};
-use crate::surface::{SurfaceAttributes, SurfaceId};
+use crate::monitor::Monitor;
+use crate::platform::event::{WindowRequest, WindowState, WindowStateShared};
+use crate::platform::platform::monitor;
+use crate::surface::{
+    Modality, SurfaceAttributes, SurfaceId, WindowState as SurfaceWindowState, MAXIMUM_WINDOW_SIZE,
+};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, WaylandHandle};
 use rui_util::alloc::oneshot;
 use rui_util::Extent;
-use smithay_client_toolkit::window::{Event, FallbackFrame};
+use smithay_client_toolkit::get_surface_outputs;
+use smithay_client_toolkit::output::with_output_info;
+use smithay_client_toolkit::window::{Decorations, Event, FallbackFrame, Window};
 use std::cell::RefCell;
 use std::sync::Arc;
 use wayland_client::protocol::wl_surface::WlSurface;
@@ -58,7 +64,7 @@ impl<'main, 'child> Surface<'main, 'child> {
             let sender_arc = Arc::new(RefCell::new(Some(sender)));
             let window_state_shared_cloned = window_state_shared.clone();
 
-            let window = environment
+            let mut window = environment
                 .create_window::<FallbackFrame, _>(
                     surface.clone(),
                     None,
@@ -69,8 +75,8 @@ impl<'main, 'child> Surface<'main, 'child> {
                         let mut window_state_shared_mut = window_state_shared.as_ref().borrow_mut();
 
                         match event {
-                            #[allow(unused_variables)] // todo: react to states;
                             Event::Configure { new_size, states } => {
+                                window_state_shared_mut.set_states(&states);
                                 if let Some(new_size) = new_size {
                                     window_state_shared_mut.set_size(Extent {
                                         width: new_size.0,
@@ -99,11 +105,7 @@ impl<'main, 'child> Surface<'main, 'child> {
                 )
                 .expect("Unable to create new window");
 
-            if !attr.title.is_empty() {
-                window.set_title(attr.title.clone());
-            }
-            window.set_app_id(attr.title.clone());
-            window.set_resizable(attr.is_resizable);
+            Self::apply_attributes(&mut window, attr);
             inner_ml.windows.insert(
                 surface_id,
                 WindowState::new(window, window_state_shared.clone()),
@@ -122,6 +124,58 @@ impl<'main, 'child> Surface<'main, 'child> {
         win
     }
 
+    /// Applies the attributes to a newly created xdg toplevel.
+    ///
+    /// xdg-shell gives the compositor full control over the placement of toplevels,
+    /// therefore `position` can't be honored. The same applies to application modality
+    /// which has no Wayland equivalent.
+    fn apply_attributes(window: &mut Window<FallbackFrame>, attr: &SurfaceAttributes) {
+        if !attr.title.is_empty() {
+            window.set_title(attr.title.clone());
+        }
+        window.set_app_id(attr.title.clone());
+        window.set_resizable(attr.is_resizable);
+        window.set_min_size(Self::min_size(attr.minimum_size));
+        window.set_max_size(Self::max_size(attr.maximum_size));
+        if attr.is_borderless {
+            window.set_decorate(Decorations::None);
+        }
+        match attr.window_state {
+            // A toplevel only gets mapped once a buffer is attached, which happens
+            // after the first configure. Hidden windows are therefore treated as windowed.
+            SurfaceWindowState::Hidden
+            | SurfaceWindowState::Automatic
+            | SurfaceWindowState::Windowed => {}
+            SurfaceWindowState::Minimized => window.set_minimized(),
+            SurfaceWindowState::Maximized => window.set_maximized(),
+            SurfaceWindowState::Fullscreen => window.set_fullscreen(None),
+        }
+        match attr.modality {
+            Modality::None => {}
+            Modality::ParentWindow | Modality::Application => {
+                eprintln!("Modal surfaces are not supported on Wayland!");
+            }
+        }
+    }
+
+    fn min_size(size: Extent) -> Option<(u32, u32)> {
+        if size.width == 0 && size.height == 0 {
+            return None;
+        }
+        Some((size.width, size.height))
+    }
+
+    fn max_size(size: Extent) -> Option<(u32, u32)> {
+        if size.width == MAXIMUM_WINDOW_SIZE.width && size.height == MAXIMUM_WINDOW_SIZE.height {
+            return None;
+        }
+        Some((size.width, size.height))
+    }
+
+    fn request(&self, request: WindowRequest) {
+        self.window_state.borrow_mut().push_request(request);
+    }
+
     fn surface_id(surface: &WlSurface) -> SurfaceId {
         SurfaceId::from(surface.as_ref().id() as u64)
     }
@@ -134,9 +188,41 @@ impl<'main, 'child> Surface<'main, 'child> {
         self.surface_id
     }
 
+    /// Returns the output the surface is shown on. A surface that spans multiple
+    /// outputs is reported on the first one.
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        get_surface_outputs(&self.wl_surface)
+            .first()
+            .and_then(|output| with_output_info(output, monitor::monitor))
+    }
+
     pub fn request_redraw(&mut self) {
         self.window_state.borrow_mut().signal_should_redraw();
     }
+
+    pub fn set_title(&mut self, title: String) {
+        self.request(WindowRequest::Title(title));
+    }
+
+    pub fn set_min_size(&mut self, size: Extent) {
+        self.request(WindowRequest::MinSize(Self::min_size(size)));
+    }
+
+    pub fn set_max_size(&mut self, size: Extent) {
+        self.request(WindowRequest::MaxSize(Self::max_size(size)));
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.request(WindowRequest::Fullscreen(fullscreen));
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.request(WindowRequest::Maximized(maximized));
+    }
+
+    pub fn minimize(&mut self) {
+        self.request(WindowRequest::Minimize);
+    }
 }
 unsafe impl<'main, 'child> HasRawWindowHandle for Surface<'main, 'child> {
     fn raw_window_handle(&self) -> RawWindowHandle {
@@ -33,6 +33,11 @@ impl DelegateClass {
                 Self::did_finish_launching as extern "C" fn(&mut Object, Sel, id),
             );
 
+            decl.add_method(
+                sel!(applicationDidChangeScreenParameters:),
+                Self::did_change_screen_parameters as extern "C" fn(&mut Object, Sel, id),
+            );
+
             decl.add_ivar::<*mut c_void>(Self::STATE_IVAR_NAME);
 
             decl.register()
@@ -64,4 +69,8 @@ impl DelegateClass {
     extern "C" fn did_finish_launching(this: &mut Object, _: Sel, _: id) {
         unsafe { Self::get_state_mut(this) }.did_finish_launching();
     }
+
+    extern "C" fn did_change_screen_parameters(this: &mut Object, _: Sel, _: id) {
+        unsafe { Self::get_state_mut(this) }.did_change_screen_parameters();
+    }
 }
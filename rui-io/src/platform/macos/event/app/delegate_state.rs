@@ -1,18 +1,33 @@
 use crate::event::queue::Enqueue;
 use crate::event::Event;
+use crate::monitor::Monitor;
 use crate::platform::event::Queue;
+use crate::platform::platform::monitor;
 use std::cell::RefCell;
 use std::rc::Rc;
 
 pub struct DelegateState {
     callback: Rc<RefCell<dyn FnMut(&Event)>>,
+    /// Monitors before the screen parameters changed
+    monitors: Vec<Monitor>,
 }
 impl DelegateState {
     pub fn new(callback: Rc<RefCell<dyn FnMut(&Event)>>) -> Self {
-        DelegateState { callback }
+        DelegateState {
+            callback,
+            monitors: monitor::monitors(),
+        }
     }
 
     pub fn did_finish_launching(&mut self) {
         (self.callback.borrow_mut())(&Event::Init);
     }
+
+    pub fn did_change_screen_parameters(&mut self) {
+        let monitors = monitor::monitors();
+        for (id, event) in crate::monitor::changes(&self.monitors, &monitors) {
+            (self.callback.borrow_mut())(&Event::MonitorEvent { id, event });
+        }
+        self.monitors = monitors;
+    }
 }
@@ -7,8 +7,10 @@ use objc::runtime::{NO, YES};
 
 use crate::event::inner::{InnerFlow, InnerLoop};
 use crate::event::Event;
+use crate::monitor::Monitor;
 use crate::platform::event::main_loop_state::MainLoopState;
 use crate::platform::event::Queue;
+use crate::platform::platform::monitor;
 use crate::surface::SurfaceEvent;
 
 pub struct MainLoop {
@@ -20,6 +22,10 @@ impl MainLoop {
         MainLoop { state: None }
     }
 
+    pub fn monitors(&self) -> Vec<Monitor> {
+        monitor::monitors()
+    }
+
     pub fn state(&self) -> &MainLoopState {
         self.state.as_ref().unwrap()
     }
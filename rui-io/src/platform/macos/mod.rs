@@ -1,6 +1,7 @@
 mod surface;
 mod ffi;
 pub mod event;
+mod monitor;
 mod util;
 
 pub use surface::Surface;
\ No newline at end of file
@@ -0,0 +1,90 @@
+use crate::monitor::{Monitor, MonitorId, VideoMode};
+use cocoa::appkit::NSScreen;
+use cocoa::base::{id, nil};
+use cocoa::foundation::{NSArray, NSString};
+use core_graphics::display::{CGDirectDisplayID, CGDisplay, CGDisplayMode};
+use objc::runtime::{BOOL, NO};
+use rui_util::{Extent, Offset};
+use std::ffi::CStr;
+use std::ptr;
+
+unsafe fn display_id(ns_screen: id) -> CGDirectDisplayID {
+    let description = ns_screen.deviceDescription();
+    let key = NSString::alloc(nil).init_str("NSScreenNumber");
+    let number: id = msg_send![description, objectForKey: key];
+    msg_send![number, unsignedIntValue]
+}
+
+unsafe fn name(ns_screen: id) -> String {
+    // localizedName is available since macOS 10.15
+    let responds: BOOL = msg_send![ns_screen, respondsToSelector: sel!(localizedName)];
+    if responds == NO {
+        return String::new();
+    }
+    let name: id = msg_send![ns_screen, localizedName];
+    if name == nil {
+        return String::new();
+    }
+    CStr::from_ptr(name.UTF8String())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn video_mode(mode: &CGDisplayMode) -> VideoMode {
+    VideoMode {
+        size: Extent {
+            width: mode.pixel_width() as u32,
+            height: mode.pixel_height() as u32,
+        },
+        refresh_rate: (mode.refresh_rate() * 1000.0).round() as u32,
+    }
+}
+
+/// Creates the monitor of a NSScreen. Cocoa places the origin in the bottom left
+/// corner of the primary screen, which is flipped to the top left corner.
+pub unsafe fn monitor(ns_screen: id) -> Monitor {
+    let display_id = display_id(ns_screen);
+    let scale_factor = ns_screen.backingScaleFactor() as f64;
+    let frame = NSScreen::frame(ns_screen);
+    let primary = NSScreen::frame(NSArray::objectAtIndex(NSScreen::screens(nil), 0));
+
+    let display = CGDisplay::new(display_id);
+    let current = display.display_mode();
+    // Displays without a fixed refresh rate (e.g. built-in displays) report 0
+    let refresh_rate = current
+        .as_ref()
+        .map(|mode| video_mode(mode).refresh_rate)
+        .filter(|refresh_rate| *refresh_rate != 0);
+    let modes = CGDisplayMode::all_display_modes(display_id, ptr::null())
+        .unwrap_or_default()
+        .iter()
+        .map(video_mode)
+        .collect();
+
+    Monitor {
+        id: MonitorId::from(display_id as u64),
+        name: name(ns_screen),
+        position: Offset {
+            x: (frame.origin.x * scale_factor).round() as i32,
+            y: ((primary.size.height - frame.origin.y - frame.size.height) * scale_factor).round()
+                as i32,
+        },
+        size: Extent {
+            width: (frame.size.width * scale_factor).round() as u32,
+            height: (frame.size.height * scale_factor).round() as u32,
+        },
+        scale_factor,
+        refresh_rate,
+        modes,
+    }
+}
+
+/// Returns all monitors, the first one is the primary monitor with the menu bar
+pub fn monitors() -> Vec<Monitor> {
+    unsafe {
+        let screens = NSScreen::screens(nil);
+        (0..screens.count())
+            .map(|index| monitor(screens.objectAtIndex(index)))
+            .collect()
+    }
+}
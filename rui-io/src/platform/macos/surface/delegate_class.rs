@@ -60,6 +60,14 @@ impl DelegateClass {
                 sel!(windowDidResignKey:),
                 Self::window_did_resign_key as extern "C" fn(&mut Object, Sel, id),
             );
+            decl.add_method(
+                sel!(windowDidMiniaturize:),
+                Self::window_did_miniaturize as extern "C" fn(&mut Object, Sel, id),
+            );
+            decl.add_method(
+                sel!(windowDidDeminiaturize:),
+                Self::window_did_deminiaturize as extern "C" fn(&mut Object, Sel, id),
+            );
             decl.add_method(
                 sel!(draggingEntered:),
                 Self::dragging_entered as extern "C" fn(&mut Object, Sel, id) -> BOOL,
@@ -176,6 +184,14 @@ impl DelegateClass {
         unsafe { Self::get_state_mut(this) }.window_did_resign_key();
     }
 
+    extern "C" fn window_did_miniaturize(this: &mut Object, _: Sel, _: id) {
+        unsafe { Self::get_state_mut(this) }.window_did_miniaturize();
+    }
+
+    extern "C" fn window_did_deminiaturize(this: &mut Object, _: Sel, _: id) {
+        unsafe { Self::get_state_mut(this) }.window_did_deminiaturize();
+    }
+
     extern "C" fn dragging_entered(this: &mut Object, _: Sel, sender: id) -> BOOL {
         unsafe { Self::get_state_mut(this) }
             .dragging_entered()
@@ -30,6 +30,13 @@ impl DelegateState {
         }
     }
 
+    fn emit(&mut self, event: SurfaceEvent) {
+        (self.callback.as_ref().borrow_mut())(&Event::SurfaceEvent {
+            id: util::get_window_id(self.ns_window),
+            event,
+        });
+    }
+
     fn get_scale_factor(&self) -> f64 {
         (unsafe { NSWindow::backingScaleFactor(self.ns_window) }) as f64
     }
@@ -83,9 +90,21 @@ impl DelegateState {
 
     pub fn window_did_change_backing_properties(&mut self) {}
 
-    pub fn window_did_become_key(&mut self) {}
+    pub fn window_did_become_key(&mut self) {
+        self.emit(SurfaceEvent::Focused(true));
+    }
 
-    pub fn window_did_resign_key(&mut self) {}
+    pub fn window_did_resign_key(&mut self) {
+        self.emit(SurfaceEvent::Focused(false));
+    }
+
+    pub fn window_did_miniaturize(&mut self) {
+        self.emit(SurfaceEvent::Suspended(true));
+    }
+
+    pub fn window_did_deminiaturize(&mut self) {
+        self.emit(SurfaceEvent::Suspended(false));
+    }
 
     pub fn dragging_entered(&mut self) -> BOOL {
         YES
@@ -116,9 +135,13 @@ impl DelegateState {
         options
     }
 
-    pub fn window_did_enter_fullscreen(&mut self) {}
+    pub fn window_did_enter_fullscreen(&mut self) {
+        self.emit(SurfaceEvent::Fullscreen(true));
+    }
 
-    pub fn window_did_exit_fullscreen(&mut self) {}
+    pub fn window_did_exit_fullscreen(&mut self) {
+        self.emit(SurfaceEvent::Fullscreen(false));
+    }
 
     pub fn window_did_fail_to_enter_fullscreen(&mut self) {}
 }
@@ -3,7 +3,7 @@ use std::pin::Pin;
 
 use cocoa::appkit::{CGFloat, NSApp, NSBackingStoreBuffered, NSView, NSWindow, NSWindowStyleMask};
 use cocoa::base::{id, nil};
-use cocoa::foundation::{NSPoint, NSRect, NSSize};
+use cocoa::foundation::{NSPoint, NSRect, NSSize, NSString};
 use core_foundation::runloop::{CFRunLoopGetMain, CFRunLoopWakeUp};
 use lazy_static::lazy_static;
 use objc::rc::autoreleasepool;
@@ -14,10 +14,12 @@ use delegate_state::DelegateState as WindowDelegateState;
 use rui_util::Extent;
 
 use crate::event::LoopTarget;
+use crate::monitor::Monitor;
+use crate::platform::platform::monitor;
 use crate::platform::platform::surface::delegate_state::DelegateState;
 use crate::platform::platform::surface::view_state::ViewState;
 use crate::platform::platform::{ffi, util};
-use crate::surface::{SurfaceAttributes, SurfaceId};
+use crate::surface::{SurfaceAttributes, SurfaceId, MAXIMUM_WINDOW_SIZE};
 use class::Class as WindowClass;
 use delegate_class::DelegateClass as WindowDelegateClass;
 use view_class::ViewClass as WindowViewClass;
@@ -174,13 +176,27 @@ impl<'main, 'child> Surface<'main, 'child> {
         }
     }
 
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        unsafe {
+            let ns_screen: id = msg_send![self.ns_window, screen];
+            if ns_screen == nil {
+                None
+            } else {
+                Some(monitor::monitor(ns_screen))
+            }
+        }
+    }
+
     pub fn request_redraw(&self) {
         match self.loop_target {
             LoopTarget::Main(main) => {
                 let id = self.id();
-                let mut mut_guard = main.inner.borrow_mut();
-                if !mut_guard.state_mut().redraw_pending.contains(&id) {
-                    mut_guard.state_mut().redraw_pending.push(id);
+                // The loop is borrowed while it emits events. In that case the redraw
+                // is only reported through `drawRect:` of the view.
+                if let Ok(mut mut_guard) = main.inner.try_borrow_mut() {
+                    if !mut_guard.state_mut().redraw_pending.contains(&id) {
+                        mut_guard.state_mut().redraw_pending.push(id);
+                    }
                 }
                 unsafe {
                     let _: () = msg_send![self.ns_view, setNeedsDisplay: YES];
@@ -190,6 +206,57 @@ impl<'main, 'child> Surface<'main, 'child> {
             LoopTarget::Child(_) => unreachable!(),
         }
     }
+
+    pub fn set_title(&mut self, title: String) {
+        autoreleasepool(|| unsafe {
+            let title = NSString::alloc(nil).init_str(&title);
+            self.ns_window.setTitle_(title);
+        });
+    }
+
+    /// Converts a size in pixels into a size in points
+    fn content_size(&self, size: Extent) -> NSSize {
+        let scale_factor = self.scale_factor();
+        NSSize::new(
+            size.width as f64 / scale_factor,
+            size.height as f64 / scale_factor,
+        )
+    }
+
+    pub fn set_min_size(&mut self, size: Extent) {
+        let size = self.content_size(size);
+        unsafe { self.ns_window.setContentMinSize_(size) };
+    }
+
+    pub fn set_max_size(&mut self, size: Extent) {
+        let size = if size.width == MAXIMUM_WINDOW_SIZE.width
+            && size.height == MAXIMUM_WINDOW_SIZE.height
+        {
+            NSSize::new(CGFloat::MAX, CGFloat::MAX)
+        } else {
+            self.content_size(size)
+        };
+        unsafe { self.ns_window.setContentMaxSize_(size) };
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        let is_fullscreen = unsafe { self.ns_window.styleMask() }
+            .contains(NSWindowStyleMask::NSFullScreenWindowMask);
+        if is_fullscreen != fullscreen {
+            unsafe { self.ns_window.toggleFullScreen_(nil) };
+        }
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        let is_zoomed: BOOL = unsafe { msg_send![self.ns_window, isZoomed] };
+        if (is_zoomed == YES) != maximized {
+            unsafe { self.ns_window.zoom_(nil) };
+        }
+    }
+
+    pub fn minimize(&mut self) {
+        unsafe { self.ns_window.miniaturize_(nil) };
+    }
 }
 unsafe impl<'main, 'child> HasRawWindowHandle for Surface<'main, 'child> {
     fn raw_window_handle(&self) -> RawWindowHandle {
@@ -1,5 +1,7 @@
 use crate::event::inner::InnerFlow;
 use crate::event::{Event, Flow};
+use crate::monitor::Monitor;
+use crate::platform::platform::monitor;
 use super::Loop;
 
 pub struct Main {
@@ -7,8 +9,13 @@ pub struct Main {
 }
 impl Main {
     pub fn new() -> Self {
+        monitor::track();
         Main { inner: Loop::new() }
     }
+
+    pub fn monitors(&self) -> Vec<Monitor> {
+        monitor::monitors()
+    }
 }
 impl crate::event::inner::InnerLoop for Main {
     fn wake_up(&self) {
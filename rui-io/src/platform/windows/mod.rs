@@ -1,4 +1,5 @@
 pub mod event;
+mod monitor;
 mod surface;
 mod util;
 
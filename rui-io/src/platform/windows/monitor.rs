@@ -0,0 +1,142 @@
+use crate::monitor::{Monitor, MonitorEvent, MonitorId, VideoMode};
+use lazy_static::lazy_static;
+use rui_util::{Extent, Offset};
+use std::sync::Mutex;
+use std::{mem, ptr};
+use windows_sys::Win32::Foundation::{BOOL, LPARAM, RECT};
+use windows_sys::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, EnumDisplaySettingsW, GetMonitorInfoW, DEVMODEW, ENUM_CURRENT_SETTINGS,
+    HDC, HMONITOR, MONITORINFO, MONITORINFOEXW, MONITORINFOF_PRIMARY,
+};
+use windows_sys::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+/// The default dpi of windows which corresponds to a scale factor of 1
+const BASE_DPI: u32 = 96;
+
+lazy_static! {
+    /// Monitors that were known the last time changes were looked for
+    static ref KNOWN: Mutex<Vec<Monitor>> = Mutex::new(monitors());
+}
+
+pub fn monitor_id(handle: HMONITOR) -> MonitorId {
+    MonitorId::from(handle as u64)
+}
+
+unsafe extern "system" fn enum_monitor(
+    handle: HMONITOR,
+    _: HDC,
+    _: *mut RECT,
+    data: LPARAM,
+) -> BOOL {
+    let handles = &mut *(data as *mut Vec<HMONITOR>);
+    handles.push(handle);
+    true.into()
+}
+
+/// Returns all monitors with the primary monitor first
+pub fn monitors() -> Vec<Monitor> {
+    let mut handles: Vec<HMONITOR> = vec![];
+    unsafe {
+        EnumDisplayMonitors(
+            0,
+            ptr::null(),
+            Some(enum_monitor),
+            &mut handles as *mut Vec<HMONITOR> as LPARAM,
+        );
+    }
+    let mut monitors = vec![];
+    for handle in handles {
+        let (monitor, primary) = unsafe { monitor_info(handle) };
+        if primary {
+            monitors.insert(0, monitor);
+        } else {
+            monitors.push(monitor);
+        }
+    }
+    monitors
+}
+
+pub unsafe fn monitor(handle: HMONITOR) -> Monitor {
+    monitor_info(handle).0
+}
+
+/// Queries the monitor and whether it's the primary one
+unsafe fn monitor_info(handle: HMONITOR) -> (Monitor, bool) {
+    let mut info: MONITORINFOEXW = mem::zeroed();
+    info.monitorInfo.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+    GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO);
+    let rect = info.monitorInfo.rcMonitor;
+
+    let name_length = info
+        .szDevice
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(info.szDevice.len());
+    let name = String::from_utf16_lossy(&info.szDevice[..name_length]);
+
+    let mut dpi_x = BASE_DPI;
+    let mut dpi_y = BASE_DPI;
+    GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+
+    let mut mode: DEVMODEW = mem::zeroed();
+    mode.dmSize = mem::size_of::<DEVMODEW>() as u16;
+    let refresh_rate =
+        if EnumDisplaySettingsW(info.szDevice.as_ptr(), ENUM_CURRENT_SETTINGS, &mut mode) != 0
+            && mode.dmDisplayFrequency > 1
+        {
+            // Frequencies of 0 and 1 represent the default of the hardware
+            Some(mode.dmDisplayFrequency * 1000)
+        } else {
+            None
+        };
+
+    let mut modes = vec![];
+    let mut index = 0;
+    while EnumDisplaySettingsW(info.szDevice.as_ptr(), index, &mut mode) != 0 {
+        modes.push(VideoMode {
+            size: Extent {
+                width: mode.dmPelsWidth,
+                height: mode.dmPelsHeight,
+            },
+            refresh_rate: mode.dmDisplayFrequency * 1000,
+        });
+        index += 1;
+    }
+
+    let monitor = Monitor {
+        id: monitor_id(handle),
+        name,
+        position: Offset {
+            x: rect.left,
+            y: rect.top,
+        },
+        size: Extent {
+            width: (rect.right - rect.left) as u32,
+            height: (rect.bottom - rect.top) as u32,
+        },
+        scale_factor: dpi_x as f64 / BASE_DPI as f64,
+        refresh_rate,
+        modes,
+    };
+    (
+        monitor,
+        info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+    )
+}
+
+/// Remembers the current monitors so that later changes can be detected
+pub fn track() {
+    lazy_static::initialize(&KNOWN);
+}
+
+/// Compares the current monitors with the ones that were known before.
+///
+/// Windows only informs top level windows that the display configuration changed,
+/// therefore every window asks for the changes and only the first one receives them.
+pub fn changes() -> Vec<(MonitorId, MonitorEvent)> {
+    let current = monitors();
+    let mut known = KNOWN.lock().unwrap();
+    let changes = crate::monitor::changes(&known, &current);
+    *known = current;
+    changes
+}
@@ -1,19 +1,27 @@
 mod window;
 
 use crate::event::LoopTarget;
-use crate::platform::platform::surface::window::{WindowClass, WindowInit};
+use crate::monitor::Monitor;
+use crate::platform::platform::monitor;
+use crate::platform::platform::surface::window::{WindowClass, WindowData, WindowInit};
 use crate::platform::platform::util;
-use crate::surface::{SurfaceAttributes, SurfaceId};
+use crate::surface::{SurfaceAttributes, SurfaceId, MAXIMUM_WINDOW_SIZE};
 use lazy_static::lazy_static;
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle, Win32WindowHandle, WindowsDisplayHandle};
 use rui_util::Extent;
 use std::{io, mem, ptr};
 use windows_sys::Win32::Foundation::{HINSTANCE, HWND, RECT};
-use windows_sys::Win32::Graphics::Gdi::{RedrawWindow, RDW_INTERNALPAINT};
+use windows_sys::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromWindow, RedrawWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    RDW_INTERNALPAINT,
+};
 use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExW, GetClientRect, RegisterClassExW, ShowWindow, CREATESTRUCTW, CS_HREDRAW,
-    CS_VREDRAW, CW_USEDEFAULT, SW_SHOW, WNDCLASSEXW, WS_OVERLAPPEDWINDOW,
+    CreateWindowExW, GetClientRect, GetWindowRect, IsZoomed, RegisterClassExW, SetWindowPos,
+    SetWindowTextW, ShowWindow, CREATESTRUCTW, CS_HREDRAW, CS_VREDRAW, CW_USEDEFAULT,
+    GWL_STYLE, GWL_USERDATA, HWND_TOP, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE,
+    SWP_NOOWNERZORDER, SWP_NOSIZE, SWP_NOZORDER, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, SW_SHOW,
+    WNDCLASSEXW, WS_OVERLAPPEDWINDOW, WS_POPUP,
 };
 
 pub struct Surface<'main, 'child> {
@@ -107,6 +115,130 @@ impl<'main, 'child> Surface<'main, 'child> {
             RedrawWindow(self.handle, ptr::null(), 0, RDW_INTERNALPAINT);
         }
     }
+
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        unsafe {
+            let handle = MonitorFromWindow(self.handle, MONITOR_DEFAULTTONEAREST);
+            if handle == 0 {
+                None
+            } else {
+                Some(monitor::monitor(handle))
+            }
+        }
+    }
+
+    fn data(&self) -> &WindowData {
+        // The window data lives as long as the window itself
+        unsafe { &*(util::get_window_long(self.handle, GWL_USERDATA) as *const WindowData) }
+    }
+
+    /// Lets the window query its min and max size again
+    fn update_size_constraints(&self) {
+        unsafe {
+            SetWindowPos(
+                self.handle,
+                0,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+            );
+        }
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        let title = util::encode_wide(title);
+        unsafe {
+            SetWindowTextW(self.handle, title.as_ptr());
+        }
+    }
+
+    pub fn set_min_size(&mut self, size: Extent) {
+        let size = if size.width == 0 && size.height == 0 {
+            None
+        } else {
+            Some(size)
+        };
+        self.data().min_size.set(size);
+        self.update_size_constraints();
+    }
+
+    pub fn set_max_size(&mut self, size: Extent) {
+        let size = if size.width == MAXIMUM_WINDOW_SIZE.width
+            && size.height == MAXIMUM_WINDOW_SIZE.height
+        {
+            None
+        } else {
+            Some(size)
+        };
+        self.data().max_size.set(size);
+        self.update_size_constraints();
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        let data = self.data();
+        unsafe {
+            match (fullscreen, data.windowed.get()) {
+                (true, None) => {
+                    let style = util::get_window_long(self.handle, GWL_STYLE);
+                    let mut rect: RECT = mem::zeroed();
+                    GetWindowRect(self.handle, &mut rect);
+                    data.windowed.set(Some((style, rect)));
+
+                    let monitor = MonitorFromWindow(self.handle, MONITOR_DEFAULTTONEAREST);
+                    let mut info: MONITORINFO = mem::zeroed();
+                    info.cbSize = mem::size_of::<MONITORINFO>() as u32;
+                    GetMonitorInfoW(monitor, &mut info);
+                    let monitor = info.rcMonitor;
+
+                    util::set_window_long(
+                        self.handle,
+                        GWL_STYLE,
+                        (style & !(WS_OVERLAPPEDWINDOW as isize)) | WS_POPUP as isize,
+                    );
+                    SetWindowPos(
+                        self.handle,
+                        HWND_TOP,
+                        monitor.left,
+                        monitor.top,
+                        monitor.right - monitor.left,
+                        monitor.bottom - monitor.top,
+                        SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+                    );
+                }
+                (false, Some((style, rect))) => {
+                    data.windowed.set(None);
+                    util::set_window_long(self.handle, GWL_STYLE, style);
+                    SetWindowPos(
+                        self.handle,
+                        0,
+                        rect.left,
+                        rect.top,
+                        rect.right - rect.left,
+                        rect.bottom - rect.top,
+                        SWP_NOZORDER | SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        unsafe {
+            let is_maximized = IsZoomed(self.handle) != 0;
+            if is_maximized != maximized {
+                ShowWindow(self.handle, if maximized { SW_MAXIMIZE } else { SW_RESTORE });
+            }
+        }
+    }
+
+    pub fn minimize(&mut self) {
+        unsafe {
+            ShowWindow(self.handle, SW_MINIMIZE);
+        }
+    }
 }
 
 unsafe impl<'main, 'child> HasRawWindowHandle for Surface<'main, 'child> {
@@ -1,4 +1,5 @@
 use crate::event::Event;
+use crate::platform::platform::monitor;
 use crate::platform::platform::surface::window::{WindowData, WindowInit};
 use crate::platform::platform::util;
 use crate::surface::SurfaceEvent;
@@ -10,7 +11,9 @@ use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
 use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     DefWindowProcW, RegisterClassExW, CREATESTRUCTW, CS_HREDRAW, CS_VREDRAW, GWL_USERDATA,
-    WM_CREATE, WM_NCCREATE, WM_NCDESTROY, WM_PAINT, WM_SIZE, WNDCLASSEXW,
+    MINMAXINFO, SIZE_MAXIMIZED, SIZE_MINIMIZED, WM_CREATE, WM_DISPLAYCHANGE, WM_DPICHANGED,
+    WM_GETMINMAXINFO, WM_KILLFOCUS, WM_NCCREATE, WM_NCDESTROY, WM_PAINT, WM_SETFOCUS, WM_SIZE,
+    WNDCLASSEXW,
 };
 
 pub struct Class {
@@ -62,12 +65,63 @@ impl Class {
                 id: util::surface_id(handle),
                 event: SurfaceEvent::Redraw,
             }),
+            WM_GETMINMAXINFO => {
+                let info = &mut *(lparam as *mut MINMAXINFO);
+                if let Some(size) = userdata.min_size.get() {
+                    let size = util::window_size(handle, size);
+                    info.ptMinTrackSize.x = size.width as i32;
+                    info.ptMinTrackSize.y = size.height as i32;
+                }
+                if let Some(size) = userdata.max_size.get() {
+                    let size = util::window_size(handle, size);
+                    info.ptMaxTrackSize.x = size.width as i32;
+                    info.ptMaxTrackSize.y = size.height as i32;
+                }
+                return 0;
+            }
+            WM_DISPLAYCHANGE | WM_DPICHANGED => {
+                for (id, event) in monitor::changes() {
+                    userdata.call(&Event::MonitorEvent { id, event });
+                }
+            }
+            WM_SETFOCUS | WM_KILLFOCUS => userdata.call(&Event::SurfaceEvent {
+                id: util::surface_id(handle),
+                event: SurfaceEvent::Focused(msg == WM_SETFOCUS),
+            }),
             WM_SIZE => {
                 let width = util::loword(lparam as u32) as u32;
                 let height = util::hiword(lparam as u32) as u32;
+                let id = util::surface_id(handle);
+
+                let minimized = wparam as u32 == SIZE_MINIMIZED;
+                if userdata.minimized.replace(minimized) != minimized {
+                    userdata.call(&Event::SurfaceEvent {
+                        id,
+                        event: SurfaceEvent::Suspended(minimized),
+                    });
+                }
+                // A minimized window keeps its maximized state
+                if !minimized {
+                    let maximized = wparam as u32 == SIZE_MAXIMIZED;
+                    if userdata.maximized.replace(maximized) != maximized {
+                        userdata.call(&Event::SurfaceEvent {
+                            id,
+                            event: SurfaceEvent::Maximized(maximized),
+                        });
+                    }
+                }
+                // Fullscreen is emulated by resizing the window to the monitor,
+                // see `Surface::set_fullscreen`
+                let fullscreen = userdata.windowed.get().is_some();
+                if userdata.fullscreen.replace(fullscreen) != fullscreen {
+                    userdata.call(&Event::SurfaceEvent {
+                        id,
+                        event: SurfaceEvent::Fullscreen(fullscreen),
+                    });
+                }
 
                 userdata.call(&Event::SurfaceEvent {
-                    id: util::surface_id(handle),
+                    id,
                     event: SurfaceEvent::Resized(Extent { width, height }),
                 })
             }
@@ -1,13 +1,35 @@
 use crate::event::Event;
-use std::cell::RefCell;
+use rui_util::Extent;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use windows_sys::Win32::Foundation::RECT;
 
 pub struct Data {
     callback: Rc<RefCell<dyn FnMut(&Event)>>,
+    /// Minimum size of the client area used to answer WM_GETMINMAXINFO
+    pub(crate) min_size: Cell<Option<Extent>>,
+    /// Maximum size of the client area used to answer WM_GETMINMAXINFO
+    pub(crate) max_size: Cell<Option<Extent>>,
+    /// Window style and rect before the window entered fullscreen
+    pub(crate) windowed: Cell<Option<(isize, RECT)>>,
+    /// Whether the window was maximized at the last WM_SIZE
+    pub(crate) maximized: Cell<bool>,
+    /// Whether the window was minimized at the last WM_SIZE
+    pub(crate) minimized: Cell<bool>,
+    /// Whether the window was fullscreen at the last WM_SIZE
+    pub(crate) fullscreen: Cell<bool>,
 }
 impl Data {
     pub fn new(callback: Rc<RefCell<dyn FnMut(&Event)>>) -> Self {
-        Data { callback }
+        Data {
+            callback,
+            min_size: Cell::new(None),
+            max_size: Cell::new(None),
+            windowed: Cell::new(None),
+            maximized: Cell::new(false),
+            minimized: Cell::new(false),
+            fullscreen: Cell::new(false),
+        }
     }
     
     pub fn call(&self, event: &Event) {
@@ -1,9 +1,12 @@
 use crate::surface::SurfaceId;
+use rui_util::Extent;
 use std::ffi::OsStr;
 use std::iter::once;
 use std::os::windows::ffi::OsStrExt;
-use windows_sys::Win32::Foundation::HWND;
-use windows_sys::Win32::UI::WindowsAndMessaging::WINDOW_LONG_PTR_INDEX;
+use windows_sys::Win32::Foundation::{HWND, RECT};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    AdjustWindowRectEx, GWL_EXSTYLE, GWL_STYLE, WINDOW_LONG_PTR_INDEX,
+};
 
 pub fn encode_wide(str: impl AsRef<OsStr>) -> Vec<u16> {
     str.as_ref().encode_wide().chain(once(0)).collect()
@@ -13,6 +16,24 @@ pub fn surface_id(hwnd: HWND) -> SurfaceId {
     SurfaceId::from(hwnd as u64)
 }
 
+/// Converts the size of the client area into the size of the whole window
+/// including its borders and title bar
+pub unsafe fn window_size(hwnd: HWND, client_size: Extent) -> Extent {
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: client_size.width as i32,
+        bottom: client_size.height as i32,
+    };
+    let style = get_window_long(hwnd, GWL_STYLE) as u32;
+    let ex_style = get_window_long(hwnd, GWL_EXSTYLE) as u32;
+    AdjustWindowRectEx(&mut rect, style, 0, ex_style);
+    Extent {
+        width: (rect.right - rect.left) as u32,
+        height: (rect.bottom - rect.top) as u32,
+    }
+}
+
 #[inline(always)]
 pub const fn loword(x: u32) -> u16 {
     (x & 0xFFFF) as u16
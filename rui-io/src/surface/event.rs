@@ -1,8 +1,47 @@
 use rui_util::Extent;
+use std::time::Duration;
+
+/// Edges of a surface that touch other surfaces or the edge of the screen, e.g.
+/// because the surface was tiled by the window manager
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TiledEdges {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl TiledEdges {
+    /// Determines whether the surface is tiled on any edge
+    pub fn any(&self) -> bool {
+        self.top || self.right || self.bottom || self.left
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum Event {
     Resized(Extent),
     Redraw,
+    /// The compositor is ready for a new frame. A redraw that was requested in the
+    /// meantime follows right after this event. The timestamp has an undefined base
+    /// and can be used to advance animations.
+    ///
+    /// Only emitted on Wayland, where redraws are driven by frame callbacks.
+    Frame {
+        timestamp: Duration,
+    },
     ShouldClose,
+    /// The surface gained (`true`) or lost (`false`) the keyboard focus
+    Focused(bool),
+    Maximized(bool),
+    Fullscreen(bool),
+    /// The edges of the surface that are tiled changed
+    Tiled(TiledEdges),
+    /// The surface is not visible to the user anymore (e.g. because it got minimized)
+    /// and rendering can be paused until the surface gets resumed (`false`).
+    ///
+    /// Wayland compositors report this through the `suspended` state of xdg-shell
+    /// version 6, which is not bound by the toolkit in use. Therefore this event is
+    /// currently only emitted on Windows and macOS.
+    Suspended(bool),
 }
@@ -7,4 +7,9 @@ impl From<u64> for Id {
     fn from(id: u64) -> Self {
         Id(id)
     }
-}
\ No newline at end of file
+}
+impl From<Id> for u64 {
+    fn from(id: Id) -> Self {
+        id.0
+    }
+}
@@ -3,11 +3,14 @@ use raw_window_handle::{
 };
 
 use crate::event::EventLoopTarget;
+use crate::monitor::Monitor;
 pub use attributes::Attributes as SurfaceAttributes;
 pub use attributes::Modality;
 pub use attributes::WindowState;
+pub use attributes::MAXIMUM_WINDOW_SIZE;
 pub use error::Error as SurfaceError;
 pub use event::Event as SurfaceEvent;
+pub use event::TiledEdges;
 pub use id::Id as SurfaceId;
 use rui_util::Extent;
 
@@ -36,9 +39,38 @@ impl<'main, 'child> Surface<'main, 'child> {
         self.0.id()
     }
 
+    /// Returns the monitor the surface is currently shown on
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        self.0.current_monitor()
+    }
+
     pub fn request_redraw(&mut self) {
         self.0.request_redraw()
     }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.0.set_title(title.into())
+    }
+
+    pub fn set_min_size(&mut self, size: Extent) {
+        self.0.set_min_size(size)
+    }
+
+    pub fn set_max_size(&mut self, size: Extent) {
+        self.0.set_max_size(size)
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.0.set_fullscreen(fullscreen)
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.0.set_maximized(maximized)
+    }
+
+    pub fn minimize(&mut self) {
+        self.0.minimize()
+    }
 }
 
 unsafe impl<'main, 'child> HasRawWindowHandle for Surface<'main, 'child> {
@@ -0,0 +1,42 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coordinates: vec2<f32>,
+}
+
+struct Blur {
+    // Distance of neighbouring texels in texture coordinates along the blur
+    direction: vec2<f32>,
+    // Standard deviation in texels
+    sigma: f32,
+}
+
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> blur: Blur;
+
+// A single triangle that covers the whole texture
+@vertex
+fn vs_main(@builtin(vertex_index) vid: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vid << 1u) & 2u), f32(vid & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.tex_coordinates = uv;
+    return out;
+}
+
+// One direction of a separable gaussian blur. Large blurs skip texels, so that
+// the number of samples stays bounded.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sigma = max(blur.sigma, 0.01);
+    let step = max(1.0, sigma * 3.0 / 32.0);
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = -32; i <= 32; i = i + 1) {
+        let x = f32(i) * step;
+        let weight = exp(-(x * x) / (2.0 * sigma * sigma));
+        sum = sum + textureSampleLevel(tex, tex_sampler, in.tex_coordinates + blur.direction * x, 0.0) * weight;
+        weights = weights + weight;
+    }
+    return sum / weights;
+}
@@ -0,0 +1,29 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coordinates: vec2<f32>,
+}
+
+struct Layer {
+    opacity: f32,
+}
+
+// Texture of the group, it contains premultiplied colors
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> layer: Layer;
+
+// A single triangle that covers the whole surface
+@vertex
+fn vs_main(@builtin(vertex_index) vid: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vid << 1u) & 2u), f32(vid & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.tex_coordinates = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Premultiplied colors are faded by scaling all channels
+    return textureSample(tex, tex_sampler, in.tex_coordinates) * layer.opacity;
+}
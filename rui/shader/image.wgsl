@@ -13,6 +13,18 @@ struct InstanceInput {
     rect: vec4<f32>,
     color: vec4<f32>,
     radii: vec4<f32>,
+    transform_0: vec4<f32>,
+    transform_1: vec4<f32>,
+    gradient_geometry: vec4<f32>,
+    gradient: vec4<u32>,
+    // Bounds of the image relative to the node after it was fitted
+    fit: vec4<f32>,
+    // Left, top, right and bottom slice in texture coordinates
+    slice: vec4<f32>,
+    // Left, top, right and bottom slice relative to the node
+    slice_target: vec4<f32>,
+    // Bounds of the image in its texture, which may be an atlas
+    atlas: vec4<f32>,
 }
 
 struct Globals {
@@ -42,26 +54,37 @@ fn cc(pos: vec4<f32>) -> vec4<f32> {
     //return vec4<f32>(pos.x, pos.y, pos.z, posg.w);
 }
 
+// Applies the affine transform of the instance, the columns of the matrix
+// are stored in (a, b), (c, d) and (e, f)
+fn transform(pos: vec2<f32>, t0: vec4<f32>, t1: vec4<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        t0.x * pos.x + t0.z * pos.y + t1.x,
+        t0.y * pos.x + t0.w * pos.y + t1.y
+    );
+}
+
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     if (model.vid == 0u || model.vid == 3u) {
-        out.position = cc(vec4<f32>(instance.rect.xy, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.xy, 0.0, 1.0);
         out.norm_position = vec2<f32>(0.0, 0.0);
         out.tex_coordinates = vec2<f32>(0.0, 0.0);
     } else if (model.vid == 2u || model.vid == 4u) {
-        out.position = cc(vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y + instance.rect.w, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y + instance.rect.w, 0.0, 1.0);
         out.norm_position = vec2<f32>(1.0, 1.0 / globals.aspect_ratio);
         out.tex_coordinates = vec2<f32>(1.0, 1.0);
     } else if (model.vid == 1u) {
-        out.position = cc(vec4<f32>(instance.rect.x, instance.rect.y + instance.rect.w, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x, instance.rect.y + instance.rect.w, 0.0, 1.0);
         out.norm_position = vec2<f32>(0.0, 1.0 / globals.aspect_ratio);
         out.tex_coordinates = vec2<f32>(0.0, 1.0);
     } else {
-        out.position = cc(vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y, 0.0, 1.0);
         out.norm_position = vec2<f32>(1.0, 0.0);
         out.tex_coordinates = vec2<f32>(1.0, 0.0);
     }
+    let pos = transform(out.position.xy, instance.transform_0, instance.transform_1);
+    out.position = cc(vec4<f32>(pos, 0.0, 1.0));
     out.color = instance.color;
     return out;
 }
@@ -103,7 +126,38 @@ fn border_radii(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
     return color;
 }
 
+// Maps a coordinate on the node to the texture along one axis. The first slice
+// d0 on the node shows s0 of the texture, the last slice d1 shows s1 and the
+// middle is stretched. Without slices this is the identity.
+fn slice_axis(x: f32, d0: f32, d1: f32, s0: f32, s1: f32) -> f32 {
+    let start = x / max(d0, 1e-6) * s0;
+    let end = 1.0 - (1.0 - x) / max(d1, 1e-6) * s1;
+    let middle = s0 + (x - d0) / max(1.0 - d0 - d1, 1e-6) * (1.0 - s0 - s1);
+    return select(select(middle, end, x > 1.0 - d1), start, x < d0);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return border_radii(in, textureSample(tex, tex_sampler, in.tex_coordinates));
+    // Position of the fragment on the fitted image
+    let position = (in.tex_coordinates - instance.fit.xy) / instance.fit.zw;
+    let image_coordinates = vec2<f32>(
+        slice_axis(position.x, instance.slice_target.x, instance.slice_target.z, instance.slice.x, instance.slice.z),
+        slice_axis(position.y, instance.slice_target.y, instance.slice_target.w, instance.slice.y, instance.slice.w)
+    );
+    // Images in an atlas are clamped half a texel inside their bounds, so that
+    // filtering doesn't pick up their neighbours
+    let half_texel = 0.5 / vec2<f32>(textureDimensions(tex));
+    let tex_coordinates = clamp(
+        instance.atlas.xy + image_coordinates * instance.atlas.zw,
+        instance.atlas.xy + half_texel,
+        instance.atlas.xy + instance.atlas.zw - half_texel
+    );
+    // The texture is sampled everywhere to keep the control flow uniform, the
+    // parts of the node that the fitted image leaves empty are masked afterwards
+    let texel = textureSample(tex, tex_sampler, tex_coordinates);
+    let inside = all(position >= vec2<f32>(0.0)) && all(position <= vec2<f32>(1.0));
+    // The alpha of the instance color is the opacity of the image. Colors are
+    // blended with premultiplied alpha.
+    let alpha = texel.a * in.color.a * select(0.0, 1.0, inside);
+    return border_radii(in, vec4<f32>(texel.rgb * alpha, alpha));
 }
\ No newline at end of file
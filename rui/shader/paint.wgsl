@@ -0,0 +1,80 @@
+// Fills of rects and paths. This file is prepended to the shaders that use it.
+// Solid fills use the color of the instance, gradients interpolate their stops.
+
+struct GradientStop {
+    color: vec4<f32>,
+    offset: f32,
+}
+
+@group(1) @binding(0) var<storage, read> stops: array<GradientStop>;
+
+// Maps the position along the gradient into 0..1 with the spread mode
+fn spread(t: f32, mode: u32) -> f32 {
+    // Repeat
+    if (mode == 1u) {
+        return fract(t);
+    }
+    // Reflect
+    if (mode == 2u) {
+        return 1.0 - abs(fract(t * 0.5) * 2.0 - 1.0);
+    }
+    // Pad
+    return clamp(t, 0.0, 1.0);
+}
+
+// Position along the gradient. The position and the geometry are relative to the
+// bounds, the size of the bounds keeps the angles of conic gradients undistorted.
+fn gradient_position(kind: u32, geometry: vec4<f32>, position: vec2<f32>, size: vec2<f32>) -> f32 {
+    // Linear from geometry.xy to geometry.zw
+    if (kind == 1u) {
+        let direction = geometry.zw - geometry.xy;
+        let length_squared = dot(direction, direction);
+        if (length_squared == 0.0) {
+            return 0.0;
+        }
+        return dot(position - geometry.xy, direction) / length_squared;
+    }
+    // Radial around geometry.xy with the radius geometry.z
+    if (kind == 2u) {
+        if (geometry.z <= 0.0) {
+            return 1.0;
+        }
+        return length(position - geometry.xy) / geometry.z;
+    }
+    // Conic around geometry.xy starting at the angle geometry.z. The y axis points
+    // down, therefore the angles run clockwise.
+    let d = (position - geometry.xy) * size;
+    return fract((atan2(d.y, d.x) - geometry.z) / 6.2831853);
+}
+
+fn premultiply(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+// Premultiplied color of the paint at the position. The gradient holds the kind,
+// the spread mode, the index of the first stop and the number of stops.
+fn paint(color: vec4<f32>, geometry: vec4<f32>, gradient: vec4<u32>, position: vec2<f32>, size: vec2<f32>) -> vec4<f32> {
+    if (gradient.x == 0u || gradient.w == 0u) {
+        return premultiply(color);
+    }
+    let t = spread(gradient_position(gradient.x, geometry, position, size), gradient.y);
+    var previous = stops[gradient.z];
+    var result = premultiply(previous.color);
+    for (var i = 1u; i < gradient.w; i = i + 1u) {
+        let next = stops[gradient.z + i];
+        if (t <= next.offset) {
+            let span = next.offset - previous.offset;
+            var f = 1.0;
+            if (span > 0.0) {
+                f = clamp((t - previous.offset) / span, 0.0, 1.0);
+            }
+            // Interpolating premultiplied colors avoids dark fringes at transparent stops
+            result = mix(premultiply(previous.color), premultiply(next.color), f);
+            break;
+        }
+        previous = next;
+        result = premultiply(next.color);
+    }
+    // The alpha of the color is the opacity of the instance
+    return result * color.a;
+}
@@ -1,19 +1,53 @@
-struct VertexInput {
-    @location(0) pos: vec2<f32>
+struct Globals {
+    width_height: u32,
+    aspect_ratio: f32,
+}
+
+struct InstanceInput {
+    // Bounds of the control points as min x, min y, max x, max y
+    @location(0) bounds: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) gradient_geometry: vec4<f32>,
+    @location(3) gradient: vec4<u32>,
 }
 
 struct VertexOutput {
-    @builtin(position) pos: vec4<f32>
+    @builtin(position) pos: vec4<f32>,
+    // Position relative to the bounds in 0..1 with the y axis pointing down
+    @location(0) @interpolate(linear) local: vec2<f32>,
+    @location(1) @interpolate(flat) size: vec2<f32>,
+    @location(2) @interpolate(flat) color: vec4<f32>,
+    @location(3) @interpolate(flat) gradient_geometry: vec4<f32>,
+    @location(4) @interpolate(flat) gradient: vec4<u32>,
 }
 
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+// Covers the bounds of the path with two triangles
 @vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
+fn vs_main(@builtin(vertex_index) vid: u32, instance: InstanceInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[vid];
+    let pos = mix(instance.bounds.xy, instance.bounds.zw, corner);
+
     var out: VertexOutput;
-    out.pos = vec4<f32>(model.pos, 0.0, 1.0);
+    out.pos = vec4<f32>(pos.x / globals.aspect_ratio, pos.y, 0.0, 1.0);
+    out.local = vec2<f32>(corner.x, 1.0 - corner.y);
+    out.size = instance.bounds.zw - instance.bounds.xy;
+    out.color = instance.color;
+    out.gradient_geometry = instance.gradient_geometry;
+    out.gradient = instance.gradient;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
-}
\ No newline at end of file
+    return paint(in.color, in.gradient_geometry, in.gradient, in.local, in.size);
+}
@@ -4,6 +4,12 @@ struct VertexOutput {
     @location(1) @interpolate(linear) norm_position: vec2<f32>,
     @location(2) @interpolate(flat) radii: vec4<f32>,
     @location(3) @interpolate(flat) ar: f32,
+    // Position relative to the rect in 0..1
+    @location(4) @interpolate(linear) local: vec2<f32>,
+    // Proportional to the size of the rect on the surface
+    @location(5) @interpolate(flat) size: vec2<f32>,
+    @location(6) @interpolate(flat) gradient_geometry: vec4<f32>,
+    @location(7) @interpolate(flat) gradient: vec4<u32>,
 }
 
 struct VertexInput {
@@ -14,6 +20,10 @@ struct InstanceInput {
     @location(0) rect: vec4<f32>,
     @location(1) color: vec4<f32>,
     @location(2) radii: vec4<f32>,
+    @location(3) transform_0: vec4<f32>,
+    @location(4) transform_1: vec4<f32>,
+    @location(5) gradient_geometry: vec4<f32>,
+    @location(6) gradient: vec4<u32>,
 }
 
 struct Globals {
@@ -33,23 +43,38 @@ fn cc(pos: vec4<f32>) -> vec4<f32> {
     //return vec4<f32>(pos.x, pos.y, pos.z, posg.w);
 }
 
+// Applies the affine transform of the instance, the columns of the matrix
+// are stored in (a, b), (c, d) and (e, f)
+fn transform(pos: vec2<f32>, t0: vec4<f32>, t1: vec4<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        t0.x * pos.x + t0.z * pos.y + t1.x,
+        t0.y * pos.x + t0.w * pos.y + t1.y
+    );
+}
+
 // Drawing counter clockwise
 @vertex
 fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
     var out: VertexOutput;
     if (model.vid == 0u || model.vid == 3u) {
-        out.position = cc(vec4<f32>(instance.rect.xy, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.xy, 0.0, 1.0);
         out.norm_position = vec2<f32>(0.0, 0.0);
     } else if (model.vid == 2u || model.vid == 4u) {
-        out.position = cc(vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y + instance.rect.w, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y + instance.rect.w, 0.0, 1.0);
         out.norm_position = vec2<f32>(1.0, 1.0 / globals.aspect_ratio);
     } else if (model.vid == 1u) {
-        out.position = cc(vec4<f32>(instance.rect.x, instance.rect.y + instance.rect.w, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x, instance.rect.y + instance.rect.w, 0.0, 1.0);
         out.norm_position = vec2<f32>(0.0, 1.0 / globals.aspect_ratio);
     } else {
-        out.position = cc(vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y, 0.0, 1.0));
+        out.position = vec4<f32>(instance.rect.x + instance.rect.z, instance.rect.y, 0.0, 1.0);
         out.norm_position = vec2<f32>(1.0, 0.0);
     }
+    out.local = vec2<f32>(out.norm_position.x, out.norm_position.y * globals.aspect_ratio);
+    out.size = vec2<f32>(instance.rect.z * globals.aspect_ratio, instance.rect.w);
+    out.gradient_geometry = instance.gradient_geometry;
+    out.gradient = instance.gradient;
+    let pos = transform(out.position.xy, instance.transform_0, instance.transform_1);
+    out.position = cc(vec4<f32>(pos, 0.0, 1.0));
     out.color = instance.color;
     out.radii = instance.radii;
     out.ar = instance.rect.x / instance.rect.y;
@@ -95,5 +120,7 @@ fn border_radii(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return border_radii(in, in.color);
+    // Colors are blended with premultiplied alpha
+    let color = paint(in.color, in.gradient_geometry, in.gradient, in.local, in.size);
+    return border_radii(in, color);
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Position in pixels relative to the quad
+    @location(0) @interpolate(linear) local: vec2<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) @interpolate(flat) shape: vec4<f32>,
+    @location(3) @interpolate(flat) shape_radii: vec4<f32>,
+    @location(4) @interpolate(flat) bounds: vec4<f32>,
+    @location(5) @interpolate(flat) bounds_radii: vec4<f32>,
+    @location(6) @interpolate(flat) params: vec4<f32>,
+}
+
+struct InstanceInput {
+    @location(0) rect: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) shape: vec4<f32>,
+    @location(3) shape_radii: vec4<f32>,
+    @location(4) bounds: vec4<f32>,
+    @location(5) bounds_radii: vec4<f32>,
+    @location(6) params: vec4<f32>,
+    @location(7) transform_0: vec4<f32>,
+    @location(8) transform_1: vec4<f32>,
+}
+
+struct Globals {
+    width_height: u32,
+    aspect_ratio: f32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+// coordinate system conversion
+fn cc(pos: vec2<f32>) -> vec4<f32> {
+    return vec4<f32>(pos.x * 2.0 - 1.0, -2.0 * pos.y + 1.0, 0.0, 1.0);
+}
+
+// Applies the affine transform of the instance, see rect.wgsl
+fn transform(pos: vec2<f32>, t0: vec4<f32>, t1: vec4<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        t0.x * pos.x + t0.z * pos.y + t1.x,
+        t0.y * pos.x + t0.w * pos.y + t1.y
+    );
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vid: u32, instance: InstanceInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+    );
+    let corner = corners[vid];
+    let surface = vec2<f32>(f32(globals.width_height >> 16u), f32(globals.width_height & 0xFFFFu));
+
+    var out: VertexOutput;
+    let pos = instance.rect.xy + corner * instance.rect.zw;
+    out.position = cc(transform(pos, instance.transform_0, instance.transform_1));
+    out.local = corner * instance.rect.zw * surface;
+    out.color = vec4<f32>(instance.color.rgb * instance.color.a, instance.color.a);
+    out.shape = instance.shape;
+    out.shape_radii = instance.shape_radii;
+    out.bounds = instance.bounds;
+    out.bounds_radii = instance.bounds_radii;
+    out.params = instance.params;
+    return out;
+}
+
+// Radius of the corner in the quadrant of the point, the radii are in the order
+// top left, top right, bottom left and bottom right
+fn corner_radius(radii: vec4<f32>, p: vec2<f32>) -> f32 {
+    if (p.y < 0.0) {
+        return select(radii.y, radii.x, p.x < 0.0);
+    }
+    return select(radii.w, radii.z, p.x < 0.0);
+}
+
+// Coverage of the rounded rect at the point with an antialiased edge
+fn coverage(rect: vec4<f32>, radii: vec4<f32>, position: vec2<f32>) -> f32 {
+    let half_size = rect.zw * 0.5;
+    let p = position - rect.xy - half_size;
+    let radius = corner_radius(radii, p);
+    let q = abs(p) - half_size + radius;
+    let distance = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+    return clamp(0.5 - distance, 0.0, 1.0);
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (2.5066283 * sigma);
+}
+
+// Approximation of the error function
+fn erf(x: vec2<f32>) -> vec2<f32> {
+    let s = sign(x);
+    let a = abs(x);
+    var r = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    r = r * r;
+    return s - s / (r * r);
+}
+
+// The blurred shadow of a rounded rect is separable along x except for the
+// corners, therefore it is integrated along y with a few samples
+fn shadow_x(x: f32, y: f32, sigma: f32, radius: f32, half_size: vec2<f32>) -> f32 {
+    let delta = min(half_size.y - radius - abs(y), 0.0);
+    let curved = half_size.x - radius + sqrt(max(0.0, radius * radius - delta * delta));
+    let integral = 0.5 + 0.5 * erf((x + vec2<f32>(-curved, curved)) * (0.70710678 / sigma));
+    return integral.y - integral.x;
+}
+
+fn shadow(rect: vec4<f32>, radii: vec4<f32>, position: vec2<f32>, sigma: f32) -> f32 {
+    let half_size = rect.zw * 0.5;
+    let p = position - rect.xy - half_size;
+    let radius = min(corner_radius(radii, p), min(half_size.x, half_size.y));
+    let low = p.y - half_size.y;
+    let high = p.y + half_size.y;
+    let start = clamp(-3.0 * sigma, low, high);
+    let end = clamp(3.0 * sigma, low, high);
+    let step = (end - start) / 4.0;
+    var y = start + step * 0.5;
+    var value = 0.0;
+    for (var i = 0; i < 4; i = i + 1) {
+        value = value + shadow_x(p.x, p.y - y, sigma, radius, half_size) * gaussian(y, sigma) * step;
+        y = y + step;
+    }
+    return value;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Without blur the edges of the shadow are still antialiased
+    let sigma = max(in.params.x, 0.5);
+    let value = shadow(in.shape, in.shape_radii, in.local, sigma);
+    let inside = coverage(in.bounds, in.bounds_radii, in.local);
+    var alpha = value * (1.0 - inside);
+    if (in.params.y > 0.0) {
+        // Inset shadows are everything within the bounds that is outside the shape
+        alpha = (1.0 - value) * inside;
+    }
+    return in.color * alpha;
+}
@@ -0,0 +1,252 @@
+use std::hash::Hash;
+use std::mem;
+use std::mem::Discriminant;
+use std::time::Duration;
+
+use crate::animation::{Easing, Interpolate, Spring};
+use crate::node::base::BaseNode;
+use crate::util::{id, Color, Paint, Rect};
+
+/// A property of a node together with a value
+#[derive(Debug, Clone)]
+pub enum Property {
+    BoundingRect(Rect),
+    /// Animating the background of a gradient replaces it with a solid color
+    Background(Color),
+    BorderRadii([f32; 4]),
+    Opacity(f32),
+}
+
+impl Property {
+    pub(crate) fn kind(&self) -> Discriminant<Property> {
+        mem::discriminant(self)
+    }
+
+    /// Reads the value of the same property from the node
+    pub(crate) fn read(&self, base: &BaseNode) -> Property {
+        match self {
+            Property::BoundingRect(_) => Property::BoundingRect(base.bounding_rect.clone()),
+            Property::Background(_) => Property::Background(base.background.color()),
+            Property::BorderRadii(_) => Property::BorderRadii(base.border_radii),
+            Property::Opacity(_) => Property::Opacity(base.opacity),
+        }
+    }
+
+    pub(crate) fn apply(&self, base: &mut BaseNode) {
+        match self {
+            Property::BoundingRect(rect) => base.bounding_rect = rect.clone(),
+            Property::Background(color) => base.background = Paint::Solid(*color),
+            Property::BorderRadii(radii) => base.border_radii = *radii,
+            Property::Opacity(opacity) => base.opacity = *opacity,
+        }
+    }
+
+    /// Blends two values of the same property. If the properties differ the target
+    /// is returned.
+    pub(crate) fn interpolate(&self, to: &Property, t: f32) -> Property {
+        match (self, to) {
+            (Property::BoundingRect(from), Property::BoundingRect(to)) => {
+                Property::BoundingRect(from.interpolate(to, t))
+            }
+            (Property::Background(from), Property::Background(to)) => {
+                Property::Background(from.interpolate(to, t))
+            }
+            (Property::BorderRadii(from), Property::BorderRadii(to)) => {
+                Property::BorderRadii(from.interpolate(to, t))
+            }
+            (Property::Opacity(from), Property::Opacity(to)) => {
+                Property::Opacity(from.interpolate(to, t).clamp(0.0, 1.0))
+            }
+            (_, to) => to.clone(),
+        }
+    }
+}
+
+/// Describes how the progress of an animation evolves over time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Timing {
+    Tween { duration: Duration, easing: Easing },
+    Spring(Spring),
+}
+
+impl Timing {
+    /// Returns the progress after `elapsed` time and whether the animation finished
+    pub fn progress(&self, elapsed: Duration) -> (f32, bool) {
+        match self {
+            Timing::Tween { duration, easing } => {
+                if elapsed >= *duration || duration.is_zero() {
+                    (1.0, true)
+                } else {
+                    let t = elapsed.as_secs_f32() / duration.as_secs_f32();
+                    (easing.ease(t), false)
+                }
+            }
+            Timing::Spring(spring) => {
+                let t = elapsed.as_secs_f32();
+                if spring.is_at_rest(t) {
+                    (1.0, true)
+                } else {
+                    (spring.position(t), false)
+                }
+            }
+        }
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::Tween {
+            duration: Duration::from_millis(250),
+            easing: Easing::default(),
+        }
+    }
+}
+
+/// Animation of a single property of the node with the given key.
+///
+/// The animation starts at the current value of the property unless a start value
+/// is given with [Animation::from].
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub(crate) key: u64,
+    pub(crate) from: Option<Property>,
+    pub(crate) to: Property,
+    pub(crate) timing: Timing,
+    pub(crate) delay: Duration,
+}
+
+impl Animation {
+    pub fn new(key: impl Hash, to: Property) -> Self {
+        Animation {
+            key: id(&key),
+            from: None,
+            to,
+            timing: Timing::default(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn from(mut self, from: Property) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Changes the duration of the tween. A spring animation turns into a tween.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.timing = match self.timing {
+            Timing::Tween { easing, .. } => Timing::Tween { duration, easing },
+            Timing::Spring(_) => Timing::Tween {
+                duration,
+                easing: Easing::default(),
+            },
+        };
+        self
+    }
+
+    /// Changes the easing of the tween. A spring animation turns into a tween.
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.timing = match self.timing {
+            Timing::Tween { duration, .. } => Timing::Tween { duration, easing },
+            Timing::Spring(_) => Timing::Tween {
+                duration: Duration::from_millis(250),
+                easing,
+            },
+        };
+        self
+    }
+
+    pub fn spring(mut self, spring: Spring) -> Self {
+        self.timing = Timing::Spring(spring);
+        self
+    }
+
+    pub fn timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Waits before the animation starts
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Animation, Property, Timing};
+    use crate::animation::{Easing, Spring};
+    use crate::util::{Color, Rect};
+    use std::time::Duration;
+
+    #[test]
+    fn tweens_finish_after_their_duration() {
+        let timing = Timing::Tween {
+            duration: Duration::from_millis(200),
+            easing: Easing::Linear,
+        };
+        assert_eq!(timing.progress(Duration::ZERO), (0.0, false));
+        assert_eq!(timing.progress(Duration::from_millis(50)), (0.25, false));
+        assert_eq!(timing.progress(Duration::from_millis(200)), (1.0, true));
+        assert_eq!(timing.progress(Duration::from_secs(1)), (1.0, true));
+
+        let instant = Timing::Tween {
+            duration: Duration::ZERO,
+            easing: Easing::Linear,
+        };
+        assert_eq!(instant.progress(Duration::ZERO), (1.0, true));
+    }
+
+    #[test]
+    fn springs_finish_at_rest() {
+        let timing = Timing::Spring(Spring::DEFAULT);
+        assert_eq!(timing.progress(Duration::ZERO), (0.0, false));
+        assert_eq!(timing.progress(Duration::from_secs(10)), (1.0, true));
+    }
+
+    #[test]
+    fn timing_builders() {
+        let animation = Animation::new(1, Property::Opacity(1.0))
+            .spring(Spring::WOBBLY)
+            .duration(Duration::from_millis(100));
+        assert_eq!(
+            animation.timing,
+            Timing::Tween {
+                duration: Duration::from_millis(100),
+                easing: Easing::EaseInOut
+            }
+        );
+        let animation = animation.easing(Easing::Linear);
+        assert_eq!(
+            animation.timing,
+            Timing::Tween {
+                duration: Duration::from_millis(100),
+                easing: Easing::Linear
+            }
+        );
+    }
+
+    #[test]
+    fn properties_interpolate_with_their_kind() {
+        let from = Property::Opacity(0.0);
+        match from.interpolate(&Property::Opacity(1.0), 1.5) {
+            Property::Opacity(opacity) => assert_eq!(opacity, 1.0),
+            _ => panic!("expected an opacity"),
+        }
+        match Property::BoundingRect(Rect::new(0, 0, 10, 10))
+            .interpolate(&Property::BoundingRect(Rect::new(10, 20, 30, 40)), 0.5)
+        {
+            Property::BoundingRect(rect) => {
+                assert_eq!([rect.offset.x, rect.offset.y], [5, 10]);
+                assert_eq!([rect.extent.width, rect.extent.height], [20, 25]);
+            }
+            _ => panic!("expected a bounding rect"),
+        }
+        // Different properties jump to the target
+        let to = Property::Background(Color::BLACK);
+        assert!(matches!(
+            from.interpolate(&to, 0.1),
+            Property::Background(_)
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+/// Easing curves map the linear progress of a tween onto the progress of the
+/// animated value. The predefined curves match the ones of CSS.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    Linear,
+    Ease,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+    /// Cubic bezier curve from `(0, 0)` to `(1, 1)` with the two control points
+    /// `(x1, y1)` and `(x2, y2)`
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// Returns the eased progress for the linear progress `t` in `0..=1`
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::Ease => cubic_bezier(0.25, 0.1, 0.25, 1.0, t),
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+        }
+    }
+}
+
+/// One coordinate of a cubic bezier curve that starts at 0 and ends at 1
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+}
+
+fn bezier_derivative(p1: f32, p2: f32, s: f32) -> f32 {
+    let inv = 1.0 - s;
+    3.0 * inv * inv * p1 + 6.0 * inv * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+/// Evaluates the curve at `x`. The curve parameter for `x` is found with newton's method
+/// which falls back to bisection when the slope gets too flat.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    const EPSILON: f32 = 1e-6;
+
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < EPSILON {
+            return bezier(y1, y2, s);
+        }
+        let slope = bezier_derivative(x1, x2, s);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        s -= error / slope;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    while high - low > EPSILON {
+        if bezier(x1, x2, s) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+    bezier(y1, y2, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bezier, Easing};
+
+    /// Distance of the point to the curve, found by sampling its parameter densely
+    fn distance(x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) -> f32 {
+        let n = 100_000;
+        (0..=n)
+            .map(|i| {
+                let s = i as f32 / n as f32;
+                let dx = bezier(x1, x2, s) - x;
+                let dy = bezier(y1, y2, s) - y;
+                (dx * dx + dy * dy).sqrt()
+            })
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::Ease,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier(0.7, -0.5, 0.3, 1.5),
+        ] {
+            assert!(easing.ease(0.0).abs() < 1e-4, "{:?}", easing);
+            assert!((easing.ease(1.0) - 1.0).abs() < 1e-4, "{:?}", easing);
+            // Progress outside of the range is clamped
+            assert_eq!(easing.ease(-1.0), easing.ease(0.0));
+            assert_eq!(easing.ease(2.0), easing.ease(1.0));
+        }
+        assert_eq!(Easing::Linear.ease(0.3), 0.3);
+        // The symmetric curve passes through its center
+        assert!((Easing::EaseInOut.ease(0.5) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn newton_and_bisection_solve_the_curve() {
+        // The last curves have flat slopes, where newton's method gives up
+        let curves = [
+            (0.25, 0.1, 0.25, 1.0),
+            (0.42, 0.0, 0.58, 1.0),
+            (0.0, 0.0, 1.0, 1.0),
+            (1.0, 0.0, 0.0, 1.0),
+            (0.9, 0.1, 1.0, 0.0),
+        ];
+        for (x1, y1, x2, y2) in curves {
+            let easing = Easing::CubicBezier(x1, y1, x2, y2);
+            for i in 0..=20 {
+                let x = i as f32 / 20.0;
+                let eased = easing.ease(x);
+                assert!(
+                    distance(x1, y1, x2, y2, x, eased) < 1e-3,
+                    "{:?} at {}: {}",
+                    easing,
+                    x,
+                    eased
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::util::{Color, Rect};
+use rui_util::{Extent, Offset};
+
+/// Values that can be blended between a start and a target value
+pub trait Interpolate {
+    /// Returns the value at the progress `t`, where 0 is `self` and 1 is `to`.
+    /// Springs may overshoot, therefore `t` isn't limited to `0..=1`.
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl<const N: usize> Interpolate for [f32; N] {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        let mut result = *self;
+        for (value, to) in result.iter_mut().zip(to.iter()) {
+            *value = value.interpolate(to, t);
+        }
+        result
+    }
+}
+
+impl Interpolate for i32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        (*self as f32).interpolate(&(*to as f32), t).round() as i32
+    }
+}
+
+impl Interpolate for u32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        (*self as f32)
+            .interpolate(&(*to as f32), t)
+            .round()
+            .max(0.0) as u32
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        let [r, g, b, a] = self.as_raw().interpolate(&to.as_raw(), t);
+        Color::RGBA { r, g, b, a }
+    }
+}
+
+impl Interpolate for Rect {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        Rect {
+            offset: Offset {
+                x: self.offset.x.interpolate(&to.offset.x, t),
+                y: self.offset.y.interpolate(&to.offset.y, t),
+            },
+            extent: Extent {
+                width: self.extent.width.interpolate(&to.extent.width, t),
+                height: self.extent.height.interpolate(&to.extent.height, t),
+            },
+        }
+    }
+}
@@ -0,0 +1,41 @@
+#[allow(clippy::module_inception)]
+mod animation;
+mod easing;
+mod interpolate;
+mod spring;
+mod values;
+
+pub use animation::{Animation, Property, Timing};
+pub use easing::Easing;
+pub use interpolate::Interpolate;
+pub use spring::Spring;
+pub use values::AnimatedValues;
+pub(crate) use values::Animator;
+
+use crate::error::Error;
+use crate::reactor::Reactor;
+use crate::util::id;
+use std::hash::Hash;
+
+/// Starts the animation and resolves once it finished or got replaced by another
+/// animation of the same property. Mounting a new node on the surface keeps the
+/// animation running if the node still has the key, otherwise it resolves.
+///
+/// Fails with [Error::NodeNotFound] if no mounted node has the key of the animation.
+pub async fn animate(animation: Animation) -> Result<(), Error> {
+    Reactor::get().shared.animate(animation).await
+}
+
+/// Plays the animated image with the key from where it was paused.
+///
+/// Fails with [Error::NodeNotFound] if no mounted image has the key.
+pub async fn play(key: impl Hash) -> Result<(), Error> {
+    Reactor::get().shared.play(id(&key), true).await
+}
+
+/// Pauses the animated image with the key at its current frame.
+///
+/// Fails with [Error::NodeNotFound] if no mounted image has the key.
+pub async fn pause(key: impl Hash) -> Result<(), Error> {
+    Reactor::get().shared.play(id(&key), false).await
+}
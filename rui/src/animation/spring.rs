@@ -0,0 +1,139 @@
+/// A damped spring that moves the animated value from its start to its target.
+///
+/// In contrast to a tween the duration is not fixed but results from the physical
+/// parameters. The animation ends once the spring came to rest.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spring {
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+}
+
+impl Spring {
+    pub const DEFAULT: Self = Spring::new(170.0, 26.0);
+    pub const GENTLE: Self = Spring::new(120.0, 14.0);
+    pub const WOBBLY: Self = Spring::new(180.0, 12.0);
+    pub const STIFF: Self = Spring::new(210.0, 20.0);
+
+    /// Distance to the target below which the spring is considered at rest
+    const REST: f32 = 1e-3;
+
+    pub const fn new(stiffness: f32, damping: f32) -> Self {
+        Spring {
+            stiffness,
+            damping,
+            mass: 1.0,
+        }
+    }
+
+    pub const fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    fn angular_frequency(&self) -> f32 {
+        (self.stiffness / self.mass).sqrt()
+    }
+
+    fn damping_ratio(&self) -> f32 {
+        self.damping / (2.0 * (self.stiffness * self.mass).sqrt())
+    }
+
+    /// Returns the progress from 0 to 1 after `t` seconds. The spring starts at rest
+    /// and may overshoot the target if it is underdamped.
+    pub fn position(&self, t: f32) -> f32 {
+        let omega = self.angular_frequency();
+        let zeta = self.damping_ratio();
+        if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            let envelope = (-zeta * omega * t).exp();
+            1.0 - envelope * ((omega_d * t).cos() + zeta * omega / omega_d * (omega_d * t).sin())
+        } else if zeta == 1.0 {
+            1.0 - (-omega * t).exp() * (1.0 + omega * t)
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            1.0 - (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+        }
+    }
+
+    /// Determines whether the spring came to rest after `t` seconds
+    pub fn is_at_rest(&self, t: f32) -> bool {
+        let omega = self.angular_frequency();
+        let zeta = self.damping_ratio();
+        let amplitude = if zeta < 1.0 {
+            // Upper bound of the oscillation around the target
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            (-zeta * omega * t).exp() * (1.0 + (zeta * omega / omega_d).powi(2)).sqrt()
+        } else {
+            // Without oscillation the spring approaches the target monotonically
+            1.0 - self.position(t)
+        };
+        amplitude < Self::REST
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Spring::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spring;
+
+    /// Time in seconds at which the spring comes to rest
+    fn rest_time(spring: &Spring) -> f32 {
+        (0..100_000)
+            .map(|i| i as f32 / 1000.0)
+            .find(|t| spring.is_at_rest(*t))
+            .expect("the spring never comes to rest")
+    }
+
+    #[test]
+    fn springs_come_to_rest_at_the_target() {
+        let springs = [
+            Spring::DEFAULT,
+            Spring::GENTLE,
+            Spring::WOBBLY,
+            Spring::STIFF,
+            // Critically damped
+            Spring::new(100.0, 20.0),
+            // Overdamped
+            Spring::new(100.0, 40.0),
+            Spring::new(100.0, 10.0).mass(4.0),
+        ];
+        for spring in springs {
+            assert_eq!(spring.position(0.0), 0.0, "{:?}", spring);
+            assert!(!spring.is_at_rest(0.0), "{:?}", spring);
+            let rest = rest_time(&spring);
+            // Once at rest the spring stays close to the target
+            for i in 0..100 {
+                let t = rest + i as f32 * 0.01;
+                assert!(spring.is_at_rest(t), "{:?} at {}", spring, t);
+                assert!(
+                    (spring.position(t) - 1.0).abs() < Spring::REST,
+                    "{:?} at {}",
+                    spring,
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn only_underdamped_springs_overshoot() {
+        let overshoot = |spring: Spring| {
+            (0..2000)
+                .map(|i| spring.position(i as f32 / 1000.0))
+                .fold(0.0f32, f32::max)
+        };
+        assert!(overshoot(Spring::WOBBLY) > 1.1);
+        assert!(overshoot(Spring::new(100.0, 20.0)) <= 1.0);
+        assert!(overshoot(Spring::new(100.0, 40.0)) <= 1.0);
+        // Stiffer springs settle faster
+        assert!(rest_time(&Spring::new(400.0, 40.0)) < rest_time(&Spring::new(100.0, 20.0)));
+    }
+}
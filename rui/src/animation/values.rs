@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::time::{Duration, Instant};
+
+use rui_util::alloc::oneshot;
+
+use crate::animation::{Animation, Property, Timing};
+use crate::error::Error;
+use crate::node::base::BaseNode;
+use crate::node::Node;
+
+/// The current values of all running animations, grouped by the key of the node
+#[derive(Default)]
+pub struct AnimatedValues {
+    values: HashMap<u64, Vec<Property>>,
+    /// Time of the animator clock, which animated images are played at
+    clock: Duration,
+    /// Animated images that were played or paused, by key
+    playback: HashMap<u64, Playback>,
+}
+
+/// Playback of an animated image with a key
+#[derive(Copy, Clone)]
+struct Playback {
+    /// Time of the clock at which the image started to play from its first frame
+    start: Duration,
+    /// Time of the clock at which the image was paused
+    paused: Option<Duration>,
+}
+
+impl AnimatedValues {
+    pub(crate) fn new() -> Self {
+        AnimatedValues::default()
+    }
+
+    pub fn get(&self, key: u64) -> &[Property] {
+        self.values.get(&key).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn insert(&mut self, key: u64, value: Property) {
+        let values = self.values.entry(key).or_default();
+        match values.iter_mut().find(|v| v.kind() == value.kind()) {
+            Some(v) => *v = value,
+            None => values.push(value),
+        }
+    }
+
+    /// How long an animated image has played and whether it's still playing. Images
+    /// play from the mount unless autoplay is disabled or they were played or paused.
+    pub fn image_time(&self, key: Option<u64>, autoplay: bool) -> (Duration, bool) {
+        match key.and_then(|key| self.playback.get(&key)) {
+            Some(Playback {
+                start,
+                paused: Some(paused),
+            }) => (paused.saturating_sub(*start), false),
+            Some(Playback {
+                start,
+                paused: None,
+            }) => (self.clock.saturating_sub(*start), true),
+            None if autoplay => (self.clock, true),
+            None => (Duration::ZERO, false),
+        }
+    }
+
+    /// Plays the image with the key from where it was paused
+    fn play(&mut self, key: u64, autoplay: bool) {
+        let (time, _) = self.image_time(Some(key), autoplay);
+        self.playback.insert(
+            key,
+            Playback {
+                start: self.clock.saturating_sub(time),
+                paused: None,
+            },
+        );
+    }
+
+    fn pause(&mut self, key: u64, autoplay: bool) {
+        let (time, _) = self.image_time(Some(key), autoplay);
+        self.playback.insert(
+            key,
+            Playback {
+                start: self.clock.saturating_sub(time),
+                paused: Some(self.clock),
+            },
+        );
+    }
+
+    /// Overrides the properties of the node with the animated values
+    pub(crate) fn apply(&self, node: &mut Node) {
+        let base = node.base_mut();
+        if let Some(key) = base.key {
+            for value in self.get(key) {
+                value.apply(base);
+            }
+        }
+    }
+
+    /// Gives the node its own values back after [AnimatedValues::apply], `original`
+    /// is the base of the node before
+    pub(crate) fn restore(node: &mut Node, original: &BaseNode) {
+        let base = node.base_mut();
+        base.bounding_rect = original.bounding_rect.clone();
+        base.background = original.background.clone();
+        base.border_radii = original.border_radii;
+        base.opacity = original.opacity;
+    }
+
+    /// Drops the values and the playback of the keys for which `mounted` is false
+    fn retain(&mut self, mounted: &mut impl FnMut(u64) -> bool) {
+        self.values.retain(|key, _| mounted(*key));
+        self.playback.retain(|key, _| mounted(*key));
+    }
+
+    /// The keys of the animated nodes with their values
+    pub(crate) fn animated(&self) -> impl Iterator<Item = (u64, &[Property])> {
+        self.values
+            .iter()
+            .map(|(key, values)| (*key, values.as_slice()))
+    }
+}
+
+struct Running {
+    key: u64,
+    kind: Discriminant<Property>,
+    from: Property,
+    to: Property,
+    timing: Timing,
+    /// Time of the animator clock at which the animation starts
+    start: Duration,
+    sender: oneshot::Sender<Result<(), Error>>,
+}
+
+/// Steps the animations and plays the animated images of a surface.
+///
+/// The animator has its own clock that only advances while animations are running or
+/// images are playing. Frame timestamps of the platform are preferred, otherwise the
+/// time between two steps is measured.
+pub(crate) struct Animator {
+    running: Vec<Running>,
+    values: AnimatedValues,
+    /// Whether animated images of the last mount show other frames later
+    playing: bool,
+    clock: Duration,
+    last_frame: Option<Duration>,
+    last_instant: Option<Instant>,
+}
+
+impl Animator {
+    pub(crate) fn new() -> Self {
+        Animator {
+            running: vec![],
+            values: AnimatedValues::new(),
+            playing: false,
+            clock: Duration::ZERO,
+            last_frame: None,
+            last_instant: None,
+        }
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Whether the node has to be mounted again with the next frame, because
+    /// animations are running or images are playing
+    pub(crate) fn is_active(&self) -> bool {
+        self.is_running() || self.playing
+    }
+
+    /// Whether animated images of the last mount show other frames later
+    pub(crate) fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub(crate) fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    /// Plays the animated image with the key, `autoplay` is the one of the image
+    pub(crate) fn play(&mut self, key: u64, autoplay: bool) {
+        self.wake();
+        self.values.play(key, autoplay);
+        // Mount again, so that the renderer tells whether the image is playing
+        self.playing = true;
+    }
+
+    pub(crate) fn pause(&mut self, key: u64, autoplay: bool) {
+        self.values.pause(key, autoplay);
+    }
+
+    /// The clock stood still while nothing was active, don't count the idle time
+    fn wake(&mut self) {
+        if !self.is_active() {
+            self.last_frame = None;
+            self.last_instant = None;
+        }
+    }
+
+    pub(crate) fn values(&self) -> &AnimatedValues {
+        &self.values
+    }
+
+    /// Returns the animated value of the property, if it was animated before
+    pub(crate) fn current(&self, key: u64, property: &Property) -> Option<Property> {
+        self.values
+            .get(key)
+            .iter()
+            .find(|v| v.kind() == property.kind())
+            .cloned()
+    }
+
+    /// Keeps the animations and values of the keys for which `mounted` is true, e.g.
+    /// the ones that a new node still has. The futures of the other running
+    /// animations resolve.
+    pub(crate) fn retain(&mut self, mut mounted: impl FnMut(u64) -> bool) {
+        let mut index = 0;
+        while index < self.running.len() {
+            if mounted(self.running[index].key) {
+                index += 1;
+            } else {
+                self.running.swap_remove(index).sender.send(Ok(()));
+            }
+        }
+        self.values.retain(&mut mounted);
+    }
+
+    /// Starts the animation from `current` unless it has its own start value. A running
+    /// animation of the same property gets replaced and its future resolves.
+    pub(crate) fn start(
+        &mut self,
+        animation: Animation,
+        current: Property,
+        sender: oneshot::Sender<Result<(), Error>>,
+    ) {
+        self.wake();
+        let kind = animation.to.kind();
+        if let Some(index) = self
+            .running
+            .iter()
+            .position(|r| r.key == animation.key && r.kind == kind)
+        {
+            self.running.swap_remove(index).sender.send(Ok(()));
+        }
+        self.running.push(Running {
+            key: animation.key,
+            kind,
+            from: animation.from.unwrap_or(current),
+            to: animation.to,
+            timing: animation.timing,
+            start: self.clock + animation.delay,
+            sender,
+        });
+    }
+
+    /// Advances the clock to the frame timestamp or by the time since the last call
+    pub(crate) fn advance(&mut self, frame: Option<Duration>) {
+        let now = Instant::now();
+        let delta = match (frame, self.last_frame) {
+            (Some(frame), Some(last)) if frame > last => frame - last,
+            _ => self
+                .last_instant
+                .map(|last| now - last)
+                .unwrap_or(Duration::ZERO),
+        };
+        self.clock += delta;
+        self.values.clock = self.clock;
+        self.last_frame = frame;
+        self.last_instant = Some(now);
+    }
+
+    /// Updates the animated values and resolves the futures of finished animations
+    pub(crate) fn step(&mut self) {
+        let clock = self.clock;
+        let mut index = 0;
+        while index < self.running.len() {
+            let running = &self.running[index];
+            let elapsed = clock.saturating_sub(running.start);
+            if clock < running.start {
+                // Still delayed
+                index += 1;
+                continue;
+            }
+            let (progress, finished) = running.timing.progress(elapsed);
+            let value = running.from.interpolate(&running.to, progress);
+            self.values.insert(running.key, value);
+            if finished {
+                self.running.swap_remove(index).sender.send(Ok(()));
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Animator;
+    use crate::animation::{Animation, Easing, Property};
+    use crate::error::Error;
+    use crate::util::id;
+    use rui_util::alloc::oneshot::{self, Receiver};
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Starts a linear animation of the opacity from zero to one over 100ms
+    fn start(animator: &mut Animator, key: u64) -> Receiver<Result<(), Error>> {
+        let (sender, receiver) = oneshot::channel();
+        let animation = Animation::new(key, Property::Opacity(1.0))
+            .duration(ms(100))
+            .easing(Easing::Linear);
+        animator.start(animation, Property::Opacity(0.0), sender);
+        receiver
+    }
+
+    fn opacity(animator: &Animator, key: u64) -> Option<f32> {
+        match animator.current(id(&key), &Property::Opacity(0.0)) {
+            Some(Property::Opacity(opacity)) => Some(opacity),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn step_follows_the_frame_timestamps() {
+        let mut animator = Animator::new();
+        let key = 1;
+        let mut receiver = start(&mut animator, key);
+        assert!(animator.is_active());
+
+        // The first frame only starts the clock
+        animator.advance(Some(ms(1000)));
+        animator.step();
+        assert_eq!(opacity(&animator, key), Some(0.0));
+
+        animator.advance(Some(ms(1050)));
+        animator.step();
+        assert!((opacity(&animator, key).unwrap() - 0.5).abs() < 1e-4);
+        assert!(receiver.try_recv().is_none());
+
+        animator.advance(Some(ms(1150)));
+        animator.step();
+        assert_eq!(opacity(&animator, key), Some(1.0));
+        assert!(matches!(receiver.try_recv(), Some(Ok(()))));
+        assert!(!animator.is_running());
+        // The final value stays
+        assert_eq!(animator.values().get(id(&key)).len(), 1);
+    }
+
+    #[test]
+    fn delayed_animations_wait() {
+        let mut animator = Animator::new();
+        let (sender, _receiver) = oneshot::channel();
+        let animation = Animation::new(1u64, Property::Opacity(1.0))
+            .duration(ms(100))
+            .easing(Easing::Linear)
+            .delay(ms(50));
+        animator.start(animation, Property::Opacity(0.0), sender);
+        animator.advance(Some(ms(0)));
+        animator.advance(Some(ms(40)));
+        animator.step();
+        assert_eq!(opacity(&animator, 1), None);
+        animator.advance(Some(ms(100)));
+        animator.step();
+        assert!((opacity(&animator, 1).unwrap() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn animations_of_the_same_property_replace_each_other() {
+        let mut animator = Animator::new();
+        let mut first = start(&mut animator, 1);
+        let mut other_key = start(&mut animator, 2);
+        let mut second = start(&mut animator, 1);
+        assert!(matches!(first.try_recv(), Some(Ok(()))));
+        assert!(other_key.try_recv().is_none());
+        assert!(second.try_recv().is_none());
+
+        // Animations of other properties run side by side
+        let (sender, mut radii) = oneshot::channel();
+        animator.start(
+            Animation::new(1u64, Property::BorderRadii([1.0; 4])),
+            Property::BorderRadii([0.0; 4]),
+            sender,
+        );
+        assert!(radii.try_recv().is_none());
+        assert!(second.try_recv().is_none());
+    }
+
+    #[test]
+    fn remounts_end_the_animations_of_missing_keys() {
+        let mut animator = Animator::new();
+        let mut kept = start(&mut animator, 1);
+        let mut removed = start(&mut animator, 2);
+        animator.advance(Some(ms(0)));
+        animator.advance(Some(ms(50)));
+        animator.step();
+
+        animator.retain(|key| key == id(&1u64));
+        assert!(kept.try_recv().is_none());
+        assert!(matches!(removed.try_recv(), Some(Ok(()))));
+        assert!(opacity(&animator, 1).is_some());
+        assert!(opacity(&animator, 2).is_none());
+
+        // The kept animation continues where it was
+        animator.advance(Some(ms(100)));
+        animator.step();
+        assert_eq!(opacity(&animator, 1), Some(1.0));
+        assert!(matches!(kept.try_recv(), Some(Ok(()))));
+    }
+
+    #[test]
+    fn images_play_and_pause() {
+        let mut animator = Animator::new();
+        let values = animator.values();
+        assert_eq!(
+            values.image_time(Some(id(&1u64)), true),
+            (Duration::ZERO, true)
+        );
+        assert_eq!(
+            values.image_time(Some(id(&1u64)), false),
+            (Duration::ZERO, false)
+        );
+        assert_eq!(values.image_time(None, false), (Duration::ZERO, false));
+
+        animator.play(id(&1u64), false);
+        animator.advance(Some(ms(0)));
+        animator.advance(Some(ms(30)));
+        assert_eq!(
+            animator.values().image_time(Some(id(&1u64)), false),
+            (ms(30), true)
+        );
+
+        animator.pause(id(&1u64), false);
+        animator.advance(Some(ms(60)));
+        assert_eq!(
+            animator.values().image_time(Some(id(&1u64)), false),
+            (ms(30), false)
+        );
+
+        // Playing again continues from the paused frame
+        animator.play(id(&1u64), false);
+        animator.advance(Some(ms(70)));
+        assert_eq!(
+            animator.values().image_time(Some(id(&1u64)), false),
+            (ms(40), true)
+        );
+
+        animator.retain(|_| false);
+        assert_eq!(
+            animator.values().image_time(Some(id(&1u64)), false),
+            (Duration::ZERO, false)
+        );
+    }
+}
@@ -1,4 +1,57 @@
+use crate::reactor::Reactor;
+use rui_io::surface::SurfaceError;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
 #[derive(Debug)]
 pub enum Error {
     MountError,
+    /// The platform surface failed
+    Surface(SurfaceError),
+    /// No mounted node has the requested key
+    NodeNotFound,
+    /// The SVG source is malformed, the string describes where
+    InvalidSvg(String),
+    /// The file of an image resource couldn't be read for the reason of the kind
+    MissingResource(PathBuf, io::ErrorKind),
+    /// An image resource couldn't be decoded, the string describes why
+    DecodeFailed(String),
+    /// The surface was lost or outdated and configuring it again didn't help
+    SurfaceLost,
+    /// The GPU ran out of memory
+    OutOfMemory,
+    /// The renderer failed, e.g. because no suitable GPU was found or it rejected a
+    /// texture
+    Renderer(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MountError => write!(f, "the node couldn't be mounted"),
+            Error::Surface(err) => write!(f, "surface error: {:?}", err),
+            Error::NodeNotFound => write!(f, "no mounted node has the key"),
+            Error::InvalidSvg(message) => write!(f, "invalid SVG: {}", message),
+            Error::MissingResource(path, kind) => {
+                write!(f, "can't read {}: {}", path.display(), kind)
+            }
+            Error::DecodeFailed(message) => write!(f, "can't decode image: {}", message),
+            Error::SurfaceLost => write!(f, "the surface was lost"),
+            Error::OutOfMemory => write!(f, "out of GPU memory"),
+            Error::Renderer(message) => write!(f, "renderer error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Handles errors that occur while the app runs and that no caller receives, e.g.
+/// when a surface can't be rendered. Without a hook they are printed to stderr.
+pub type ErrorHook = Box<dyn FnMut(Error) + Send>;
+
+/// Replaces the error hook of the running app, see [ErrorHook]
+pub fn on_error(hook: impl FnMut(Error) + Send + 'static) {
+    Reactor::get().shared.set_error_hook(Box::new(hook));
 }
@@ -22,3 +22,15 @@ where
         }
     }
 }
+
+impl<B> From<Error<B>> for crate::error::Error
+where
+    B: Backend,
+{
+    fn from(err: Error<B>) -> Self {
+        match err {
+            Error::SurfaceError(err) => crate::error::Error::Surface(err),
+            Error::RendererError(err) => err.into(),
+        }
+    }
+}
@@ -1,16 +1,19 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::Future;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use raw_window_handle::HasRawWindowHandle;
 
 use rui_async::{Scheduler, Status};
-use rui_io::event::{Event, Flow, MainEventLoop};
+use rui_io::event::{Event, Flow, MainEventLoop, Recorder, Recording, Replayer};
 use rui_io::surface::{SurfaceEvent, SurfaceId};
 use rui_util::alloc::mpsc;
 
+use crate::animation::{AnimatedValues, Animator};
+use crate::error::ErrorHook;
 use crate::instance::error::Error;
-use crate::instance::main_loop_request::MainLoopRequest;
+use crate::instance::main_loop_request::{MainLoopRequest, SurfaceRequest};
 use crate::instance::InstanceShared;
 use crate::renderer::Renderer;
 use crate::surface::SurfaceSharedState;
@@ -24,6 +27,9 @@ where
     nodes: BTreeMap<SurfaceId, Node>,
     // For now just create everything on the main thread
     main_loop_receiver: mpsc::Receiver<MainLoopRequest>,
+    recorder: Option<Recorder>,
+    replayer: Option<Replayer>,
+    error_hook: Option<ErrorHook>,
 }
 //TODO check thread safety for Instance struct
 unsafe impl<B> Send for Instance<B> where B: Backend {}
@@ -41,17 +47,70 @@ where
                 renderer,
                 nodes: BTreeMap::new(),
                 main_loop_receiver,
+                recorder: None,
+                replayer: None,
+                error_hook: None,
             },
             InstanceShared::new(main_loop_sender),
         )
     }
 
+    /// Records all events of the main loop. If no recorder is set the path in the
+    /// `RUI_RECORD` environment variable is used when the instance is started.
+    pub fn record(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Replays the events of a recording. If no replayer is set the recording at the path
+    /// in the `RUI_REPLAY` environment variable is used when the instance is started.
+    pub fn replay(&mut self, replayer: Replayer) {
+        self.replayer = Some(replayer);
+    }
+
+    /// Handles errors that no caller receives, e.g. when a surface can't be rendered.
+    /// Without a hook they are printed to stderr. The app can replace the hook with
+    /// [crate::error::on_error].
+    pub fn on_error(&mut self, hook: impl FnMut(crate::error::Error) + Send + 'static) {
+        self.error_hook = Some(Box::new(hook));
+    }
+
+    fn report(&mut self, err: impl Into<crate::error::Error>) {
+        let err = err.into();
+        match &mut self.error_hook {
+            Some(hook) => hook(err),
+            None => eprintln!("{}", err),
+        }
+    }
+
+    fn recorder_from_env() -> Option<Recorder> {
+        let path = std::env::var_os("RUI_RECORD")?;
+        match Recorder::create(&path) {
+            Ok(recorder) => Some(recorder),
+            Err(err) => {
+                eprintln!("Could not create event recording {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    fn replayer_from_env() -> Option<Replayer> {
+        let path = std::env::var_os("RUI_REPLAY")?;
+        match Recording::load(&path) {
+            Ok(recording) => Some(Replayer::new(recording)),
+            Err(err) => {
+                eprintln!("Could not load event recording {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
     pub fn mount(
         &mut self,
         surface: &rui_io::surface::Surface,
         mut node: Node,
+        values: &AnimatedValues,
     ) -> Result<(), Error<B>> {
-        if let Err(err) = self.renderer.mount(surface, &mut node) {
+        if let Err(err) = self.renderer.mount(surface, &mut node, values) {
             return Err(Error::RendererError(err));
         }
         self.nodes.insert(surface.id(), node);
@@ -88,11 +147,20 @@ where
     pub fn run(mut self, start_app: impl Future<Output = ()>) -> ! {
         let mut start_app = Some(start_app);
         let mut main_event_loop = MainEventLoop::new();
+        if let Some(recorder) = self.recorder.take().or_else(Self::recorder_from_env) {
+            main_event_loop.record(recorder);
+        }
+        if let Some(replayer) = self.replayer.take().or_else(Self::replayer_from_env) {
+            main_event_loop.replay(replayer);
+        }
         let scheduler = Scheduler::new();
         let mut main_worker = scheduler.new_worker();
 
-        let mut surfaces = HashMap::new();
+        let mut surfaces: HashMap<SurfaceId, (_, Arc<RwLock<SurfaceSharedState>>)> = HashMap::new();
         let mut mounted = HashSet::new();
+        let mut animators: HashMap<SurfaceId, Animator> = HashMap::new();
+        // Latest frame timestamp of each surface, used to advance its animations
+        let mut frames: HashMap<SurfaceId, Duration> = HashMap::new();
 
         main_event_loop.run(move |target, event, flow| {
             *flow = Flow::Wait;
@@ -101,23 +169,84 @@ where
                 main_worker.spawn(start_app.take().unwrap());
             }
             if let Some(Event::SurfaceEvent { id, event }) = event {
+                let suspended = match surfaces.get(id) {
+                    Some((_, shared_state)) => {
+                        let mut shared_state = shared_state.write().unwrap();
+                        shared_state.update(event);
+                        shared_state.suspended
+                    }
+                    None => false,
+                };
                 match event {
-                    SurfaceEvent::Resized(extent) => match surfaces.get(id) {
+                    // The node is mounted again after reconfiguring the surface, so that
+                    // it's placed in pixels of the new size, e.g. paths with a view box.
+                    // The new size is presented with the next redraw.
+                    SurfaceEvent::Resized(extent) => match surfaces.get_mut(id) {
                         Some((surface, _)) => {
                             if mounted.contains(id) {
-                                self.renderer.resize(surface, extent.clone()).unwrap();
-                                self.renderer.render(surface).unwrap();
+                                let empty = AnimatedValues::new();
+                                let values = animators.get(id).map_or(&empty, Animator::values);
+                                let node = self.nodes.get_mut(id).unwrap();
+                                let resized = self
+                                    .renderer
+                                    .resize(surface, extent.clone())
+                                    .and_then(|()| self.renderer.mount(surface, node, values));
+                                match resized {
+                                    Ok(()) => surface.request_redraw(),
+                                    Err(err) => self.report(err),
+                                }
                             }
                         }
                         None => {}
                     },
                     SurfaceEvent::Redraw => {
-                        if mounted.contains(id) {
-                            let (surface, _) = surfaces.get(id).unwrap();
-                            self.renderer.render(surface).unwrap();
+                        if mounted.contains(id) && !suspended {
+                            let (surface, _) = surfaces.get_mut(id).unwrap();
+                            if let Some(animator) = animators.get_mut(id).filter(|a| a.is_active())
+                            {
+                                animator.advance(frames.get(id).copied());
+                                animator.step();
+                                // Animated leaves are patched into the last mount, other
+                                // nodes and new frames of images need a mount
+                                if animator.is_playing()
+                                    || !self.renderer.animate(surface, animator.values())
+                                {
+                                    let node = self.nodes.get_mut(id).unwrap();
+                                    // The last mounted node stays on the surface if mounting
+                                    // fails
+                                    if let Err(err) =
+                                        self.renderer.mount(surface, node, animator.values())
+                                    {
+                                        self.report(err);
+                                    }
+                                    animator.set_playing(self.renderer.is_playing(surface));
+                                }
+                                // Keep redrawing until all animations finished and all
+                                // images stopped
+                                if animator.is_active() {
+                                    surface.request_redraw();
+                                }
+                            }
+                            if let Err(err) = self.renderer.render(surface) {
+                                self.report(err);
+                            }
+                        }
+                    }
+                    SurfaceEvent::Frame { timestamp } => {
+                        frames.insert(*id, *timestamp);
+                    }
+                    // Render again once the surface becomes visible
+                    SurfaceEvent::Suspended(false) => {
+                        if let Some((surface, _)) = surfaces.get_mut(id) {
+                            surface.request_redraw();
                         }
                     }
-                    SurfaceEvent::ShouldClose => {}
+                    SurfaceEvent::ShouldClose
+                    | SurfaceEvent::Focused(_)
+                    | SurfaceEvent::Maximized(_)
+                    | SurfaceEvent::Fullscreen(_)
+                    | SurfaceEvent::Tiled(_)
+                    | SurfaceEvent::Suspended(true) => {}
                 }
             }
             match self.main_loop_receiver.try_recv() {
@@ -140,15 +269,111 @@ where
                         surface_id,
                         node,
                         sender,
-                    } => match surfaces.get(&surface_id) {
+                    } => match surfaces.get_mut(&surface_id) {
                         None => sender.send(Err(crate::error::Error::MountError)),
                         Some((surface, _)) => {
-                            self.mount(surface, node).unwrap();
-                            mounted.insert(surface.id());
-                            self.renderer.render(surface).unwrap();
-                            sender.send(Ok(()))
+                            let empty = AnimatedValues::new();
+                            let values =
+                                animators.get(&surface_id).map_or(&empty, Animator::values);
+                            match self.mount(surface, *node, values) {
+                                Err(err) => sender.send(Err(err.into())),
+                                Ok(()) => {
+                                    mounted.insert(surface.id());
+                                    let animator =
+                                        animators.entry(surface_id).or_insert_with(Animator::new);
+                                    // Animations continue on the new node, those of keys
+                                    // that it lacks end
+                                    animator.retain(|key| self.renderer.is_mounted(surface, key));
+                                    animator.set_playing(self.renderer.is_playing(surface));
+                                    let rendered = self.renderer.render(surface);
+                                    if animator.is_active() {
+                                        surface.request_redraw();
+                                    }
+                                    sender.send(rendered.map_err(Into::into))
+                                }
+                            }
                         }
                     },
+                    MainLoopRequest::UpdateSurface {
+                        surface_id,
+                        request,
+                    } => {
+                        if let Some((surface, shared_state)) = surfaces.get_mut(&surface_id) {
+                            let mut shared_state = shared_state.write().unwrap();
+                            match request {
+                                SurfaceRequest::SetTitle(title) => {
+                                    shared_state.attr.title = title.clone();
+                                    surface.set_title(title);
+                                }
+                                SurfaceRequest::SetMinSize(size) => {
+                                    shared_state.attr.minimum_size = size;
+                                    surface.set_min_size(size);
+                                }
+                                SurfaceRequest::SetMaxSize(size) => {
+                                    shared_state.attr.maximum_size = size;
+                                    surface.set_max_size(size);
+                                }
+                                SurfaceRequest::SetFullscreen(fullscreen) => {
+                                    surface.set_fullscreen(fullscreen);
+                                }
+                                SurfaceRequest::SetMaximized(maximized) => {
+                                    surface.set_maximized(maximized);
+                                }
+                                SurfaceRequest::Minimize => surface.minimize(),
+                                SurfaceRequest::Redraw => surface.request_redraw(),
+                            }
+                        }
+                    }
+                    MainLoopRequest::Animate { animation, sender } => {
+                        // Look for the surface with the animated node
+                        let found = self.nodes.keys().find_map(|id| {
+                            let (surface, _) = surfaces.get(id)?;
+                            self.renderer
+                                .read(surface, animation.key, &animation.to)
+                                .map(|value| (*id, value))
+                        });
+                        match found {
+                            None => sender.send(Err(crate::error::Error::NodeNotFound)),
+                            Some((surface_id, value)) => {
+                                let animator =
+                                    animators.entry(surface_id).or_insert_with(Animator::new);
+                                // Continue from the animated value if the property was
+                                // animated before
+                                let current = animator
+                                    .current(animation.key, &animation.to)
+                                    .unwrap_or(value);
+                                animator.start(*animation, current, sender);
+                                if let Some((surface, _)) = surfaces.get_mut(&surface_id) {
+                                    surface.request_redraw();
+                                }
+                            }
+                        }
+                    }
+                    MainLoopRequest::Play { key, play, sender } => {
+                        // Look for the surface with the image
+                        let found = self.nodes.keys().find_map(|id| {
+                            let (surface, _) = surfaces.get(id)?;
+                            self.renderer
+                                .autoplay(surface, key)
+                                .map(|autoplay| (*id, autoplay))
+                        });
+                        match found {
+                            None => sender.send(Err(crate::error::Error::NodeNotFound)),
+                            Some((surface_id, autoplay)) => {
+                                let animator =
+                                    animators.entry(surface_id).or_insert_with(Animator::new);
+                                match play {
+                                    true => animator.play(key, autoplay),
+                                    false => animator.pause(key, autoplay),
+                                }
+                                if let Some((surface, _)) = surfaces.get_mut(&surface_id) {
+                                    surface.request_redraw();
+                                }
+                                sender.send(Ok(()));
+                            }
+                        }
+                    }
+                    MainLoopRequest::SetErrorHook(hook) => self.error_hook = Some(hook),
                 },
             }
 
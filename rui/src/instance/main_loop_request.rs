@@ -1,12 +1,26 @@
 use std::sync::{Arc, RwLock};
 
-use crate::error::Error;
+use crate::animation::Animation;
+use crate::error::{Error, ErrorHook};
 use rui_io::surface::{SurfaceAttributes, SurfaceId};
 use rui_util::alloc::oneshot;
+use rui_util::Extent;
 
 use crate::surface::SurfaceSharedState;
 use crate::Node;
 
+/// Changes of a surface that was already created
+pub enum SurfaceRequest {
+    SetTitle(String),
+    SetMinSize(Extent),
+    SetMaxSize(Extent),
+    SetFullscreen(bool),
+    SetMaximized(bool),
+    Minimize,
+    /// Redraws the surface as soon as the platform is ready for a new frame
+    Redraw,
+}
+
 pub enum MainLoopRequest {
     CreateSurface {
         attr: SurfaceAttributes,
@@ -14,7 +28,23 @@ pub enum MainLoopRequest {
     },
     MountNode {
         surface_id: SurfaceId,
-        node: Node,
+        node: Box<Node>,
+        sender: oneshot::Sender<Result<(), Error>>,
+    },
+    UpdateSurface {
+        surface_id: SurfaceId,
+        request: SurfaceRequest,
+    },
+    /// Animates a property of the node with the key of the animation
+    Animate {
+        animation: Box<Animation>,
+        sender: oneshot::Sender<Result<(), Error>>,
+    },
+    SetErrorHook(ErrorHook),
+    /// Plays or pauses the animated image with the key
+    Play {
+        key: u64,
+        play: bool,
         sender: oneshot::Sender<Result<(), Error>>,
     },
 }
@@ -6,4 +6,5 @@ mod shared;
 
 pub use error::Error;
 pub use instance::Instance;
+pub(crate) use main_loop_request::SurfaceRequest;
 pub(crate) use shared::Shared as InstanceShared;
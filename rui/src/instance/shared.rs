@@ -1,9 +1,10 @@
-use crate::error::Error;
+use crate::animation::Animation;
+use crate::error::{Error, ErrorHook};
 use rui_io::surface::{SurfaceAttributes, SurfaceId};
 use rui_util::alloc::{mpsc, oneshot};
 use std::sync::{Arc, RwLock};
 
-use crate::instance::main_loop_request::MainLoopRequest;
+use crate::instance::main_loop_request::{MainLoopRequest, SurfaceRequest};
 use crate::instance::InstanceShared;
 use crate::reactor::Reactor;
 use crate::surface::SurfaceSharedState;
@@ -21,7 +22,7 @@ impl Shared {
         let (sender, mut receiver) = oneshot::channel();
         self.main_loop_sender.send(MainLoopRequest::MountNode {
             surface_id,
-            node,
+            node: Box::new(node),
             sender,
         });
         receiver.recv().await
@@ -38,4 +39,33 @@ impl Shared {
             .send(MainLoopRequest::CreateSurface { attr, sender });
         receiver.recv().await
     }
+
+    pub(crate) fn update_surface(&self, surface_id: SurfaceId, request: SurfaceRequest) {
+        self.main_loop_sender.send(MainLoopRequest::UpdateSurface {
+            surface_id,
+            request,
+        });
+    }
+
+    pub(crate) fn set_error_hook(&self, hook: ErrorHook) {
+        self.main_loop_sender
+            .send(MainLoopRequest::SetErrorHook(hook));
+    }
+
+    /// Plays or pauses the animated image with the key
+    pub(crate) async fn play(&self, key: u64, play: bool) -> Result<(), Error> {
+        let (sender, mut receiver) = oneshot::channel();
+        self.main_loop_sender
+            .send(MainLoopRequest::Play { key, play, sender });
+        receiver.recv().await
+    }
+
+    pub(crate) async fn animate(&self, animation: Animation) -> Result<(), Error> {
+        let (sender, mut receiver) = oneshot::channel();
+        self.main_loop_sender.send(MainLoopRequest::Animate {
+            animation: Box::new(animation),
+            sender,
+        });
+        receiver.recv().await
+    }
 }
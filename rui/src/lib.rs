@@ -7,6 +7,7 @@ use crate::component::Component;
 use crate::instance::backend::Backend;
 use crate::node::Node;
 
+pub mod animation;
 pub mod component;
 pub mod error;
 pub mod instance;
@@ -15,6 +16,7 @@ pub mod node;
 pub mod reactor;
 pub mod renderer;
 pub mod surface;
+pub mod svg;
 pub mod util;
 
 /*
@@ -1,12 +1,15 @@
 mod num;
+pub mod path;
 pub mod rect;
 mod solve;
+mod transform;
 mod vec;
 
 use crate::math::num::{Max, Min};
 pub use crate::math::num::{Result, Ring, Sqrt};
 pub use solve::*;
 use std::ops::{Add, Mul};
+pub use transform::Transform;
 pub use vec::*;
 
 #[inline]
@@ -79,7 +82,7 @@ where
     let _3: T = T::ONE + T::ONE + T::ONE;
     let _6: T = _3 + _3;
 
-    let a = -b0 * _3 * b1 - b2 * _3 + b3;
+    let a = -b0 + b1 * _3 - b2 * _3 + b3;
     let b = b0 * _3 - b1 * _6 + b2 * _3;
     let c = -b0 * _3 + b1 * _3;
     let d = b0;
@@ -0,0 +1,483 @@
+use crate::math::{
+    add, cross, distance, dot, lerp, magnitude, normal, scale, solve_minmax_cubic_bezier, sub,
+};
+use crate::node::path::{FillRule, PathNode};
+use crate::util::{PathSegment, Point2D};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum distance in path coordinates between a curve and its flattened polyline
+pub const TOLERANCE: f32 = 0.001;
+
+/// Tolerance of the parameters of intersections along edges
+const EPSILON: f32 = 1e-5;
+
+/// Combination of the areas of two paths
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Areas inside of either path
+    Union,
+    /// Areas inside of both paths
+    Intersection,
+    /// Areas inside of the first path but not the second
+    Difference,
+    /// Areas inside of exactly one of the paths
+    Xor,
+}
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+/// Tight bounds `[x_min, y_min, x_max, y_max]` of the path. Unlike the bounds of the
+/// control points they only contain the extrema of the curves.
+pub fn bounds(path: &PathNode) -> [f32; 4] {
+    let mut bounds = [path.from[0], path.from[1], path.from[0], path.from[1]];
+    let mut from = path.from;
+    for segment in &path.normalized().segments {
+        let [x_min, y_min, x_max, y_max] = match segment {
+            PathSegment::CubicBezier { to, params } => {
+                let extrema = solve_minmax_cubic_bezier(
+                    from.into(),
+                    params[0].into(),
+                    params[1].into(),
+                    (*to).into(),
+                );
+                [extrema[0], extrema[1], extrema[2], extrema[3]]
+            }
+            segment => {
+                let to = segment.to();
+                [to[0], to[1], to[0], to[1]]
+            }
+        };
+        bounds = [
+            bounds[0].min(x_min),
+            bounds[1].min(y_min),
+            bounds[2].max(x_max),
+            bounds[3].max(y_max),
+        ];
+        from = *segment.to();
+    }
+    bounds
+}
+
+/// Flattens each subpath of the path into a polyline, which deviates at most by
+/// `tolerance` from the curves
+pub fn flatten(path: &PathNode, tolerance: f32) -> Vec<Vec<Point2D>> {
+    let mut polylines = vec![];
+    let mut points = vec![path.from];
+    for segment in &path.normalized().segments {
+        let from = *points.last().unwrap();
+        match segment {
+            PathSegment::Move { to } => polylines.push(std::mem::replace(&mut points, vec![*to])),
+            PathSegment::CubicBezier { to, params } => {
+                // Wang's formula for the number of lines
+                let [a, b] = *params;
+                let deviation = magnitude(add(sub(from, scale(a, 2.0)), b))
+                    .max(magnitude(add(sub(a, scale(b, 2.0)), *to)));
+                let n =
+                    ((3.0 * deviation / (4.0 * tolerance)).sqrt().ceil() as usize).clamp(1, 256);
+                points.extend((1..=n).map(|i| {
+                    let t = i as f32 / n as f32;
+                    let (ab, bc) = (lerp(from, a, t), lerp(a, b, t));
+                    let cd = lerp(b, *to, t);
+                    lerp(lerp(ab, bc, t), lerp(bc, cd, t), t)
+                }));
+            }
+            segment => points.push(*segment.to()),
+        }
+    }
+    polylines.push(points);
+    polylines
+}
+
+/// Whether the point lies inside of the path with the fill rule. Subpaths are
+/// closed implicitly like when the path is filled.
+pub fn contains(path: &PathNode, point: Point2D, fill_rule: FillRule) -> bool {
+    inside(winding(&flatten(path, TOLERANCE), point), fill_rule)
+}
+
+/// Length of all subpaths, the moves between them don't count
+pub fn length(path: &PathNode) -> f32 {
+    flatten(path, TOLERANCE)
+        .iter()
+        .flat_map(|points| points.windows(2))
+        .map(|pair| distance(pair[0], pair[1]))
+        .sum()
+}
+
+/// Point at the distance along the path from its start. Distances beyond the
+/// length of the path end at its last point.
+pub fn point_at_length(path: &PathNode, distance_along: f32) -> Point2D {
+    let mut remaining = distance_along.max(0.0);
+    let mut last = path.from;
+    for points in flatten(path, TOLERANCE) {
+        for pair in points.windows(2) {
+            let length = distance(pair[0], pair[1]);
+            if remaining <= length && length > 0.0 {
+                return lerp(pair[0], pair[1], remaining / length);
+            }
+            remaining -= length;
+        }
+        last = *points.last().unwrap();
+    }
+    last
+}
+
+/// Combines the areas of the paths, each with its own fill rule. The curves are
+/// flattened, the result consists of polygons that are filled with the non-zero
+/// rule. Strokes are not taken into account.
+///
+/// The edges of both paths are split where they intersect, and the pieces that
+/// separate the inside of the result from its outside are joined into polygons.
+/// This is quadratic in the number of edges.
+pub fn boolean(a: &PathNode, b: &PathNode, op: BooleanOp) -> PathNode {
+    let (polygons_a, polygons_b) = (flatten(a, TOLERANCE), flatten(b, TOLERANCE));
+    let mut edges = vec![];
+    for polygon in polygons_a.iter().chain(&polygons_b) {
+        for (index, from) in polygon.iter().enumerate() {
+            let to = polygon[(index + 1) % polygon.len()];
+            if *from != to {
+                edges.push([*from, to]);
+            }
+        }
+    }
+    if edges.is_empty() {
+        return PathNode::new(a.from, vec![]);
+    }
+
+    // Distance of the points on both sides of an edge that are tested
+    let extent = edges
+        .iter()
+        .flatten()
+        .fold([f32::MAX, f32::MIN], |[min, max], point| {
+            [
+                min.min(point[0]).min(point[1]),
+                max.max(point[0]).max(point[1]),
+            ]
+        });
+    let offset = EPSILON * (extent[1] - extent[0]).max(1.0);
+    let inside_result = |point: Point2D| {
+        op.apply(
+            inside(winding(&polygons_a, point), a.fill_rule),
+            inside(winding(&polygons_b, point), b.fill_rule),
+        )
+    };
+
+    let mut seen = HashSet::new();
+    let mut boundary = vec![];
+    for [from, to] in split(&edges) {
+        let middle = lerp(from, to, 0.5);
+        let normal = scale(normal(from, to), offset);
+        let left = inside_result(add(middle, normal));
+        if left == inside_result(sub(middle, normal)) {
+            continue;
+        }
+        // The inside of the result lies left of its boundary, coincident edges of
+        // both paths are only kept once
+        let edge = if left { [from, to] } else { [to, from] };
+        if seen.insert([key(edge[0]), key(edge[1])]) {
+            boundary.push(edge);
+        }
+    }
+    polygons_to_path(chain(boundary))
+}
+
+/// Splits the edges where they intersect or overlap each other
+fn split(edges: &[[Point2D; 2]]) -> Vec<[Point2D; 2]> {
+    let mut cuts: Vec<Vec<(f32, Point2D)>> = vec![vec![]; edges.len()];
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            let (a, b) = intersect(edges[i], edges[j]);
+            cuts[i].extend(a);
+            cuts[j].extend(b);
+        }
+    }
+
+    let mut pieces = vec![];
+    for (edge, mut cuts) in edges.iter().zip(cuts) {
+        cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut from = edge[0];
+        for (_, point) in cuts.into_iter().chain(std::iter::once((1.0, edge[1]))) {
+            if point != from {
+                pieces.push([from, point]);
+                from = point;
+            }
+        }
+    }
+    pieces
+}
+
+/// Points at which an edge has to be cut with their parameter along the edge
+type Cuts = Vec<(f32, Point2D)>;
+
+/// Points at which the edges have to be cut, with their parameter along each edge.
+/// Intersections close to an end point are snapped onto it, so both edges are cut
+/// at exactly the same point.
+fn intersect(a: [Point2D; 2], b: [Point2D; 2]) -> (Cuts, Cuts) {
+    let interior = |t: f32| t > EPSILON && t < 1.0 - EPSILON;
+    let (r, s) = (sub(a[1], a[0]), sub(b[1], b[0]));
+    let between = sub(b[0], a[0]);
+    let denominator = cross(r, s);
+    let (mut cuts_a, mut cuts_b) = (vec![], vec![]);
+
+    if denominator.abs() > EPSILON * magnitude(r) * magnitude(s) {
+        let t = cross(between, s) / denominator;
+        let u = cross(between, r) / denominator;
+        let range = -EPSILON..=1.0 + EPSILON;
+        if !range.contains(&t) || !range.contains(&u) {
+            return (cuts_a, cuts_b);
+        }
+        let point = if !interior(t) {
+            if t < 0.5 {
+                a[0]
+            } else {
+                a[1]
+            }
+        } else if !interior(u) {
+            if u < 0.5 {
+                b[0]
+            } else {
+                b[1]
+            }
+        } else {
+            lerp(a[0], a[1], t)
+        };
+        if interior(t) {
+            cuts_a.push((t, point));
+        }
+        if interior(u) {
+            cuts_b.push((u, point));
+        }
+    } else if cross(between, r).abs() <= EPSILON * dot(r, r) {
+        // Collinear edges are cut at the end points of the other edge
+        for point in b {
+            let t = dot(sub(point, a[0]), r) / dot(r, r);
+            if interior(t) {
+                cuts_a.push((t, point));
+            }
+        }
+        for point in a {
+            let u = dot(sub(point, b[0]), s) / dot(s, s);
+            if interior(u) {
+                cuts_b.push((u, point));
+            }
+        }
+    }
+    (cuts_a, cuts_b)
+}
+
+/// Joins directed edges into polygons by following the edge that starts where the
+/// previous one ended
+fn chain(edges: Vec<[Point2D; 2]>) -> Vec<Vec<Point2D>> {
+    let mut outgoing: HashMap<[u32; 2], Vec<usize>> = HashMap::new();
+    for (index, edge) in edges.iter().enumerate() {
+        outgoing.entry(key(edge[0])).or_default().push(index);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut polygons = vec![];
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        let mut polygon = vec![];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            polygon.push(edges[current][0]);
+            let next = outgoing
+                .get(&key(edges[current][1]))
+                .and_then(|candidates| candidates.iter().copied().find(|index| !used[*index]));
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        polygons.push(polygon);
+    }
+    polygons
+}
+
+/// Path with a closed subpath for each polygon, which keep their orientation
+fn polygons_to_path(polygons: Vec<Vec<Point2D>>) -> PathNode {
+    let polygons: Vec<Vec<Point2D>> = polygons
+        .into_iter()
+        .filter(|polygon| polygon.len() > 2)
+        .collect();
+    let from = match polygons.first() {
+        Some(first) => first[0],
+        None => return PathNode::new([0.0, 0.0], vec![]),
+    };
+    let mut segments = vec![];
+    for (index, polygon) in polygons.iter().enumerate() {
+        if index > 0 {
+            segments.push(PathSegment::Move { to: polygon[0] });
+        }
+        segments.extend(
+            polygon[1..]
+                .iter()
+                .map(|to| PathSegment::Linear { to: *to }),
+        );
+        segments.push(PathSegment::Linear { to: polygon[0] });
+    }
+    PathNode::new(from, segments)
+}
+
+/// Sum of the windings of the closed polygons around the point
+fn winding(polygons: &[Vec<Point2D>], point: Point2D) -> i32 {
+    let mut winding = 0;
+    for polygon in polygons {
+        for (index, from) in polygon.iter().enumerate() {
+            let to = polygon[(index + 1) % polygon.len()];
+            let side = cross(sub(to, *from), sub(point, *from));
+            if from[1] <= point[1] {
+                if to[1] > point[1] && side > 0.0 {
+                    winding += 1;
+                }
+            } else if to[1] <= point[1] && side < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+    winding
+}
+
+fn inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Identifies a point exactly, negative zero is the same as zero
+fn key(point: Point2D) -> [u32; 2] {
+    [(point[0] + 0.0).to_bits(), (point[1] + 0.0).to_bits()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boolean, bounds, contains, length, point_at_length, BooleanOp};
+    use crate::node::path::{FillRule, PathNode};
+    use crate::util::PathSegment;
+
+    fn square(x: f32, y: f32, size: f32) -> PathNode {
+        let segments = [[x + size, y], [x + size, y + size], [x, y + size], [x, y]]
+            .map(|to| PathSegment::Linear { to });
+        PathNode::new([x, y], segments.to_vec())
+    }
+
+    /// Area of the path sampled on a grid over `-1..4` in both directions
+    fn area(path: &PathNode) -> f32 {
+        let step = 0.02;
+        let n = (5.0 / step) as usize;
+        let mut inside = 0;
+        for i in 0..n {
+            for j in 0..n {
+                let point = [
+                    -1.0 + (i as f32 + 0.5) * step,
+                    -1.0 + (j as f32 + 0.5) * step,
+                ];
+                if contains(path, point, FillRule::NonZero) {
+                    inside += 1;
+                }
+            }
+        }
+        inside as f32 * step * step
+    }
+
+    fn assert_area(path: &PathNode, expected: f32) {
+        let area = area(path);
+        assert!(
+            (area - expected).abs() < 0.05,
+            "area {} != {}",
+            area,
+            expected
+        );
+    }
+
+    #[test]
+    fn boolean_areas() {
+        // Two squares of area 4 that overlap in a square of area 1
+        let (a, b) = (square(0.0, 0.0, 2.0), square(1.0, 1.0, 2.0));
+        assert_area(&boolean(&a, &b, BooleanOp::Union), 7.0);
+        assert_area(&boolean(&a, &b, BooleanOp::Intersection), 1.0);
+        assert_area(&boolean(&a, &b, BooleanOp::Difference), 3.0);
+        assert_area(&boolean(&b, &a, BooleanOp::Difference), 3.0);
+        assert_area(&boolean(&a, &b, BooleanOp::Xor), 6.0);
+    }
+
+    #[test]
+    fn boolean_of_nested_and_disjoint_paths() {
+        let (outer, inner) = (square(0.0, 0.0, 3.0), square(1.0, 1.0, 1.0));
+        let hole = boolean(&outer, &inner, BooleanOp::Difference);
+        assert_area(&hole, 8.0);
+        assert!(!contains(&hole, [1.5, 1.5], FillRule::NonZero));
+        assert!(contains(&hole, [0.5, 1.5], FillRule::NonZero));
+        assert_area(&boolean(&outer, &inner, BooleanOp::Union), 9.0);
+
+        let (a, b) = (square(0.0, 0.0, 1.0), square(2.0, 2.0, 1.0));
+        assert_area(&boolean(&a, &b, BooleanOp::Union), 2.0);
+        assert_area(&boolean(&a, &b, BooleanOp::Intersection), 0.0);
+    }
+
+    #[test]
+    fn fill_rules() {
+        // Both subpaths wind in the same direction
+        let mut path = square(0.0, 0.0, 3.0);
+        path.segments.push(PathSegment::Move { to: [1.0, 1.0] });
+        path.segments.extend(
+            [[2.0, 1.0], [2.0, 2.0], [1.0, 2.0], [1.0, 1.0]].map(|to| PathSegment::Linear { to }),
+        );
+        assert!(contains(&path, [1.5, 1.5], FillRule::NonZero));
+        assert!(!contains(&path, [1.5, 1.5], FillRule::EvenOdd));
+        assert!(contains(&path, [0.5, 0.5], FillRule::EvenOdd));
+        assert!(!contains(&path, [3.5, 0.5], FillRule::NonZero));
+    }
+
+    #[test]
+    fn bounds_contain_only_the_extrema_of_curves() {
+        let path = PathNode::new(
+            [0.0, 0.0],
+            vec![PathSegment::CubicBezier {
+                to: [3.0, 0.0],
+                params: [[1.0, 4.0], [2.0, 4.0]],
+            }],
+        );
+        let [x_min, y_min, x_max, y_max] = bounds(&path);
+        assert_eq!([x_min, y_min, x_max], [0.0, 0.0, 3.0]);
+        // The curve reaches three quarters of its control points
+        assert!((y_max - 3.0).abs() < 1e-3, "{}", y_max);
+    }
+
+    #[test]
+    fn length_and_points_along_the_path() {
+        let path = square(0.0, 0.0, 2.0);
+        assert!((length(&path) - 8.0).abs() < 1e-4);
+        assert_eq!(point_at_length(&path, 3.0), [2.0, 1.0]);
+        assert_eq!(point_at_length(&path, 100.0), [0.0, 0.0]);
+
+        // A half circle of radius one
+        let arc = PathNode::new(
+            [1.0, 0.0],
+            vec![PathSegment::Arc {
+                to: [-1.0, 0.0],
+                radii: [1.0, 1.0],
+                rotation: 0.0,
+                large_arc: false,
+                sweep: true,
+            }],
+        );
+        assert!((length(&arc) - std::f32::consts::PI).abs() < 1e-2);
+        let [x, y] = point_at_length(&arc, std::f32::consts::FRAC_PI_2);
+        assert!(
+            x.abs() < 1e-2 && (y.abs() - 1.0).abs() < 1e-2,
+            "{:?}",
+            [x, y]
+        );
+    }
+}
@@ -111,18 +111,18 @@ pub fn solve_minmax_cubic_bezier<T>(p0: Vec2<T>, p1: Vec2<T>, p2: Vec2<T>, p3: V
 where
     T: Field + Min<Output = T> + Max<Output = T> + Sqrt + PartialEq + PartialOrd + Debug,
 {
-    let _3 = T::ONE + T::ONE + T::ONE;
-    let _6 = _3 + _3;
+    let _2 = T::ONE + T::ONE;
+    let _3 = _2 + T::ONE;
 
     // Calculate derivative parameters for x coordinate
     let ax = -p0[0] + _3 * p1[0] - _3 * p2[0] + p3[0];
-    let bx = _3 * p0[0] - _6 * p1[0] + _3 * p2[0];
-    let cx = -_3 * p0[0] + _3 * p1[0];
+    let bx = _2 * (p0[0] - _2 * p1[0] + p2[0]);
+    let cx = p1[0] - p0[0];
 
     // Calculate derivative parameters for y coordinate
     let ay = -p0[1] + _3 * p1[1] - _3 * p2[1] + p3[1];
-    let by = _3 * p0[1] - _6 * p1[1] + _3 * p2[1];
-    let cy = -_3 * p0[1] + _3 * p1[1];
+    let by = _2 * (p0[1] - _2 * p1[1] + p2[1]);
+    let cy = p1[1] - p0[1];
 
     let (mut x_min, mut x_max) = (min(p0[0], p3[0]), max(p0[0], p3[0]));
     let (mut y_min, mut y_max) = (min(p0[1], p3[1]), max(p0[1], p3[1]));
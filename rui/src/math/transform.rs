@@ -0,0 +1,183 @@
+use crate::util::Point2D;
+use std::ops::Mul;
+
+/// A 2D affine transform in the layout of the SVG/CSS `matrix(a, b, c, d, e, f)`:
+///
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Transform::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Rotation by `angle` radians, clockwise on screen because the y axis points down
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Skew by the angles `x` and `y` in radians
+    pub fn skew(x: f32, y: f32) -> Self {
+        Transform::new(1.0, y.tan(), x.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Applies `self` first and then `other`
+    pub fn then(&self, other: &Transform) -> Self {
+        *other * *self
+    }
+
+    /// Applies the transform around `origin` instead of `(0, 0)`
+    pub fn around(&self, origin: Point2D) -> Self {
+        Transform::translate(origin[0], origin[1])
+            * *self
+            * Transform::translate(-origin[0], -origin[1])
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Returns the inverse transform, unless the transform collapses the plane
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Transform::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * self.e + c * self.f),
+            -(b * self.e + d * self.f),
+        ))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::IDENTITY
+    }
+
+    pub fn apply(&self, point: Point2D) -> Point2D {
+        [
+            self.a * point[0] + self.c * point[1] + self.e,
+            self.b * point[0] + self.d * point[1] + self.f,
+        ]
+    }
+
+    /// The columns of the matrix in the layout the shaders expect
+    pub(crate) fn as_raw(&self) -> [f32; 8] {
+        [self.a, self.b, self.c, self.d, self.e, self.f, 0.0, 0.0]
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+/// Matrix product, `lhs * rhs` applies `rhs` first
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Self::Output {
+        Transform::new(
+            self.a * rhs.a + self.c * rhs.b,
+            self.b * rhs.a + self.d * rhs.b,
+            self.a * rhs.c + self.c * rhs.d,
+            self.b * rhs.c + self.d * rhs.d,
+            self.a * rhs.e + self.c * rhs.f + self.e,
+            self.b * rhs.e + self.d * rhs.f + self.f,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: [f32; 2], b: [f32; 2]) {
+        assert!(
+            (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn apply_maps_points() {
+        assert_near(Transform::translate(3.0, 4.0).apply([1.0, 2.0]), [4.0, 6.0]);
+        assert_near(Transform::scale(2.0, 3.0).apply([1.0, 2.0]), [2.0, 6.0]);
+        // Clockwise on screen
+        assert_near(Transform::rotate(FRAC_PI_2).apply([1.0, 0.0]), [0.0, 1.0]);
+        assert_near(
+            Transform::skew(FRAC_PI_2 / 2.0, 0.0).apply([0.0, 1.0]),
+            [1.0, 1.0],
+        );
+        assert_near(Transform::IDENTITY.apply([5.0, 6.0]), [5.0, 6.0]);
+    }
+
+    #[test]
+    fn products_apply_the_right_hand_side_first() {
+        let translate = Transform::translate(10.0, 0.0);
+        let scale = Transform::scale(2.0, 2.0);
+        assert_near((translate * scale).apply([1.0, 1.0]), [12.0, 2.0]);
+        assert_near((scale * translate).apply([1.0, 1.0]), [22.0, 2.0]);
+        assert_eq!(scale.then(&translate), translate * scale);
+
+        let around = Transform::rotate(FRAC_PI_2).around([5.0, 5.0]);
+        assert_near(around.apply([5.0, 5.0]), [5.0, 5.0]);
+        assert_near(around.apply([6.0, 5.0]), [5.0, 6.0]);
+    }
+
+    #[test]
+    fn inverses_undo_the_transform() {
+        let transform = Transform::translate(3.0, -7.0)
+            * Transform::rotate(0.3)
+            * Transform::skew(0.2, -0.1)
+            * Transform::scale(2.0, 0.5);
+        let inverse = transform.inverse().unwrap();
+        for point in [[0.0, 0.0], [1.0, 2.0], [-30.0, 12.5]] {
+            assert_near(inverse.apply(transform.apply(point)), point);
+            assert_near(transform.apply(inverse.apply(point)), point);
+        }
+        let product = transform * inverse;
+        assert_near([product.a, product.b], [1.0, 0.0]);
+        assert_near([product.c, product.d], [0.0, 1.0]);
+        assert_near([product.e, product.f], [0.0, 0.0]);
+
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+        assert!(Transform::new(1.0, 2.0, 2.0, 4.0, 0.0, 0.0)
+            .inverse()
+            .is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::math::num::{AddInv, Field, Max, Min, MulInv, Num, Ring};
 use crate::math::{max, min};
+use crate::util::Point2D;
 use std::ops::{
     Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Deref,
     DerefMut, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Not, Rem, RemAssign, Shl,
@@ -491,3 +492,47 @@ where
         }
     }
 }
+
+// Arithmetic on plain points, which the path and stroke geometry uses
+
+pub(crate) fn add(a: Point2D, b: Point2D) -> Point2D {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+pub(crate) fn sub(a: Point2D, b: Point2D) -> Point2D {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+pub(crate) fn scale(a: Point2D, factor: f32) -> Point2D {
+    [a[0] * factor, a[1] * factor]
+}
+
+pub(crate) fn dot(a: Point2D, b: Point2D) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+pub(crate) fn cross(a: Point2D, b: Point2D) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+pub(crate) fn magnitude(a: Point2D) -> f32 {
+    dot(a, a).sqrt()
+}
+
+pub(crate) fn distance(a: Point2D, b: Point2D) -> f32 {
+    magnitude(sub(b, a))
+}
+
+pub(crate) fn lerp(a: Point2D, b: Point2D, t: f32) -> Point2D {
+    add(a, scale(sub(b, a), t))
+}
+
+pub(crate) fn normalize(a: Point2D) -> Point2D {
+    scale(a, 1.0 / magnitude(a))
+}
+
+/// Unit normal on the left of the line from `a` to `b`
+pub(crate) fn normal(a: Point2D, b: Point2D) -> Point2D {
+    let [x, y] = normalize(sub(b, a));
+    [-y, x]
+}
@@ -1,16 +1,109 @@
-use crate::util::{Color, Flags, Rect};
+use crate::math::Transform;
+use crate::node::path::{FillRule, PathNode};
+use crate::node::{Clip, Shadow};
+use crate::util::{Color, Flags, Paint, Point2D, Rect};
 use rui_util::{Extent, Offset};
 
+#[derive(Clone)]
 pub struct BaseNode {
+    /// Identifies the node, e.g. as the target of animations
+    pub(crate) key: Option<u64>,
     pub(crate) flags: Flags,
     pub(crate) bounding_rect: Rect,
-    pub(crate) background: Color,
+    pub(crate) background: Paint,
     pub(crate) border_radii: [f32; 4],
+    pub(crate) opacity: f32,
+    /// Renders the node and its children into a layer that is composited with this
+    /// opacity, so overlapping children don't shine through each other
+    pub(crate) group_opacity: Option<f32>,
+    /// Transform of the node and its children relative to the parent
+    pub(crate) transform: Transform,
+    /// Point the transform is applied around, relative to the size of the bounding
+    /// rect. `[0.5, 0.5]` is the center of the node.
+    pub(crate) transform_origin: [f32; 2],
+    /// Area the node and its children are painted in
+    pub(crate) clip: Option<Clip>,
+    /// Shadows in paint order, outer shadows are painted below the node and inset
+    /// shadows above it
+    pub(crate) shadows: Vec<Shadow>,
+    /// Standard deviation in pixels of the blur of the node and its children
+    pub(crate) blur: f32,
+    /// Standard deviation in pixels of the blur of what is painted behind the node
+    pub(crate) backdrop_blur: f32,
+}
+
+impl BaseNode {
+    /// Composes the transform of the node with the transform of its parent.
+    /// The result maps the coordinates of the node to surface coordinates.
+    pub(crate) fn world_transform(&self, parent: &Transform) -> Transform {
+        if self.transform.is_identity() {
+            return *parent;
+        }
+        let rect = &self.bounding_rect;
+        let origin = [
+            rect.offset.x as f32 + self.transform_origin[0] * rect.extent.width as f32,
+            rect.offset.y as f32 + self.transform_origin[1] * rect.extent.height as f32,
+        ];
+        *parent * self.transform.around(origin)
+    }
+
+    /// Whether the clip of the node lets the point through. The point is in the
+    /// coordinates of the node, before its transform is applied. Path clips without
+    /// a view box depend on the size of the surface and let every point through.
+    pub(crate) fn clip_contains(&self, point: Point2D) -> bool {
+        let rect = &self.bounding_rect;
+        match &self.clip {
+            None => true,
+            Some(Clip::Bounds) => rect.contains(point[0], point[1]),
+            Some(Clip::Rounded(radii)) => {
+                let bounds = rect.as_raw();
+                // Like border radii the radii are relative to the width of the node
+                let radii = radii.map(|radius| radius * bounds[2]);
+                PathNode::rounded_rect(bounds, radii).contains(point, FillRule::NonZero)
+            }
+            Some(Clip::Path(path)) => match path.view_box() {
+                Some(view_box) => match view_box.transform(rect).and_then(|t| t.inverse()) {
+                    Some(to_view_box) => {
+                        let outline = path.outline();
+                        outline.contains(to_view_box.apply(point), outline.fill_rule())
+                    }
+                    // Nothing is painted within empty view boxes
+                    None => false,
+                },
+                None => true,
+            },
+        }
+    }
+
+    /// Bounding rect of the node clamped to the parent. Nodes with an auto width or
+    /// height extend to the right or bottom edge of the parent.
+    pub(crate) fn rect(&self, parent: &Rect) -> Rect {
+        let rect = &self.bounding_rect;
+        let mut width = rect
+            .extent
+            .width
+            .clamp(0, (parent.extent.width as i32 - rect.offset.x) as u32);
+        let mut height = rect
+            .extent
+            .height
+            .clamp(0, (parent.extent.height as i32 - rect.offset.y) as u32);
+        if self.flags.test(Flags::AUTO_WIDTH) {
+            width = (parent.extent.width as i32 - rect.offset.x).clamp(0, i32::MAX) as u32;
+        }
+        if self.flags.test(Flags::AUTO_HEIGHT) {
+            height = (parent.extent.height as i32 - rect.offset.y).clamp(0, i32::MAX) as u32;
+        }
+        Rect {
+            offset: rect.offset.clone(),
+            extent: Extent { width, height },
+        }
+    }
 }
 
 impl Default for BaseNode {
     fn default() -> Self {
         BaseNode {
+            key: None,
             flags: Flags::DEFAULT,
             bounding_rect: Rect {
                 offset: Offset { x: 0, y: 0 },
@@ -19,8 +112,16 @@ impl Default for BaseNode {
                     height: 0,
                 },
             },
-            background: Color::BLACK,
+            background: Paint::Solid(Color::BLACK),
             border_radii: [0.0, 0.0, 0.0, 0.0],
+            opacity: 1.0,
+            group_opacity: None,
+            transform: Transform::IDENTITY,
+            transform_origin: [0.5, 0.5],
+            clip: None,
+            shadows: vec![],
+            blur: 0.0,
+            backdrop_blur: 0.0,
         }
     }
 }
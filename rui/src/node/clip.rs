@@ -0,0 +1,14 @@
+use crate::node::path::PathNode;
+
+/// Restricts the painting of a node and its children to an area of the node
+#[derive(Clone)]
+pub enum Clip {
+    /// Clips to the bounding rect of the node
+    Bounds,
+    /// Clips to the bounding rect with rounded corners. The radii are in the same
+    /// order and units as the border radii of [crate::node::rect].
+    Rounded([f32; 4]),
+    /// Clips to the area of the path, which is given in the coordinates of path nodes
+    /// or placed into the bounding rect with its view box
+    Path(PathNode),
+}
@@ -2,10 +2,13 @@ use crate::node::Node;
 
 pub struct CompositionNode {
     layers: Vec<Node>,
+    /// Opacity of each layer
+    opacities: Vec<f32>,
 }
 impl CompositionNode {
     pub fn new(layers: Vec<Node>) -> Self {
-        CompositionNode { layers }
+        let opacities = vec![1.0; layers.len()];
+        CompositionNode { layers, opacities }
     }
 
     pub fn layers(&self) -> &Vec<Node> {
@@ -15,4 +18,26 @@ impl CompositionNode {
     pub fn layers_mut(&mut self) -> &mut Vec<Node> {
         &mut self.layers
     }
+
+    pub fn layer_opacity(&self, index: usize) -> f32 {
+        self.opacities.get(index).copied().unwrap_or(1.0)
+    }
+
+    /// Sets the opacity of the layer at `index`, which is applied on top of the
+    /// opacity of the layer's node
+    pub fn set_layer_opacity(&mut self, index: usize, opacity: f32) {
+        if self.opacities.len() < self.layers.len() {
+            self.opacities.resize(self.layers.len(), 1.0);
+        }
+        self.opacities[index] = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Layers together with their opacity, from bottom to top
+    pub(crate) fn layers_with_opacity_mut(&mut self) -> impl Iterator<Item = (&mut Node, f32)> {
+        let opacities = &self.opacities;
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, node)| (node, opacities.get(index).copied().unwrap_or(1.0)))
+    }
 }
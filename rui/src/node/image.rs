@@ -1,14 +1,131 @@
 use crate::util::Resource;
 
+/// How an image is sized within the bounds of its node, like the CSS `object-fit`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ImageFit {
+    /// Stretches the image to the bounds
+    #[default]
+    Fill,
+    /// Scales the image uniformly, so that all of it fits into the bounds
+    Contain,
+    /// Scales the image uniformly, so that it covers the bounds. The image is cut off
+    /// at the bounds.
+    Cover,
+    /// Keeps the size of the image in pixels
+    None,
+    /// Like [ImageFit::Contain], but never scales the image up
+    ScaleDown,
+}
+
+impl ImageFit {
+    /// Bounds `[x, y, width, height]` of an image of `image` texels on a node of `node`
+    /// pixels relative to the bounds of the node, aligned by `position`
+    pub(crate) fn rect(self, position: [f32; 2], image: [f32; 2], node: [f32; 2]) -> [f32; 4] {
+        let [image_width, image_height] = image;
+        let [width, height] = node;
+        if image_width <= 0.0 || image_height <= 0.0 || width <= 0.0 || height <= 0.0 {
+            return [0.0, 0.0, 1.0, 1.0];
+        }
+        let contain = (width / image_width).min(height / image_height);
+        let scale = match self {
+            ImageFit::Fill => return [0.0, 0.0, 1.0, 1.0],
+            ImageFit::Contain => contain,
+            ImageFit::Cover => (width / image_width).max(height / image_height),
+            ImageFit::None => 1.0,
+            ImageFit::ScaleDown => contain.min(1.0),
+        };
+        let size = [image_width * scale / width, image_height * scale / height];
+        [
+            (1.0 - size[0]) * position[0],
+            (1.0 - size[1]) * position[1],
+            size[0],
+            size[1],
+        ]
+    }
+}
+
+/// How the texels of an image are filtered when it's scaled
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Sampling {
+    /// Takes the closest texel, which keeps pixel art sharp
+    Nearest,
+    /// Interpolates between the closest texels
+    #[default]
+    Linear,
+    /// Interpolates between the texels of downscaled copies of the image as well,
+    /// which avoids aliasing when the image is shown a lot smaller than it is
+    Mipmapped,
+}
+
+/// Scales an image like the CSS `border-image`. The corners keep their size, the
+/// edges are stretched along them and the center in both directions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NineSlice {
+    /// Distances of the slices from the top, right, bottom and left edge of the image
+    /// in texels
+    pub insets: [f32; 4],
+    /// Sizes of the slices on the node in pixels in the same order as the insets
+    pub widths: [f32; 4],
+}
+impl NineSlice {
+    /// Slices with the same size on the node as in the image
+    pub fn new(insets: [f32; 4]) -> Self {
+        NineSlice {
+            insets,
+            widths: insets,
+        }
+    }
+
+    pub fn widths(mut self, widths: [f32; 4]) -> Self {
+        self.widths = widths;
+        self
+    }
+}
+
 pub struct ImageNode {
     resource: Resource,
+    pub(crate) fit: ImageFit,
+    pub(crate) position: [f32; 2],
+    pub(crate) slice: Option<NineSlice>,
+    pub(crate) sampling: Sampling,
+    pub(crate) autoplay: bool,
 }
 impl ImageNode {
     pub fn new(resource: Resource) -> Self {
-        ImageNode { resource }
+        ImageNode {
+            resource,
+            fit: ImageFit::Fill,
+            position: [0.5, 0.5],
+            slice: None,
+            sampling: Sampling::Linear,
+            autoplay: true,
+        }
     }
 
     pub fn resource(&self) -> &Resource {
         &self.resource
     }
+
+    pub fn fit(&self) -> ImageFit {
+        self.fit
+    }
+
+    /// Position of the image in the space that its fit leaves, relative to the size
+    /// of the space. `[0.5, 0.5]` centers the image.
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    pub fn slice(&self) -> Option<&NineSlice> {
+        self.slice.as_ref()
+    }
+
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    /// Whether an animated image plays once it's mounted
+    pub fn autoplay(&self) -> bool {
+        self.autoplay
+    }
 }
@@ -1,21 +1,27 @@
 pub(crate) mod base;
 mod border;
+mod clip;
 mod composition;
 mod grid;
 pub mod image;
 mod node;
 pub mod path;
+mod shadow;
+mod stroke;
 pub mod text;
 
 use crate::Component;
+pub use clip::Clip;
 pub use node::Node;
+pub use shadow::Shadow;
 use std::path::Path;
+pub use stroke::{LineCap, LineJoin, Stroke};
 
 use crate::node::base::BaseNode;
 use crate::node::composition::CompositionNode;
 use crate::node::image::ImageNode;
 use crate::node::path::PathNode;
-use crate::util::{Color, Point2D, Resource};
+use crate::util::{Paint, Point2D, Resource};
 
 pub fn component<T>(component: T) -> Node
 where
@@ -24,20 +30,26 @@ where
     Node::Component(BaseNode::default(), Box::new(component))
 }
 
-pub fn rect(color: impl Into<Color>, radii: [f32; 4]) -> Node {
-    let mut base = BaseNode::default();
-    base.background = color.into();
-    base.border_radii = radii;
+pub fn rect(paint: impl Into<Paint>, radii: [f32; 4]) -> Node {
+    let base = BaseNode {
+        background: paint.into(),
+        border_radii: radii,
+        ..BaseNode::default()
+    };
     Node::Rectangle(base)
 }
 
 pub fn image(image: impl AsRef<Path>, radii: [f32; 4]) -> Node {
-    let mut base = BaseNode::default();
-    base.border_radii = radii;
-    Node::Image(
-        base,
-        ImageNode::new(Resource::Path(image.as_ref().to_path_buf())),
-    )
+    image_from(image.as_ref(), radii)
+}
+
+/// Image node of any resource, e.g. `image_from(include_bytes!("logo.png"), radii)`
+pub fn image_from(resource: impl Into<Resource>, radii: [f32; 4]) -> Node {
+    let base = BaseNode {
+        border_radii: radii,
+        ..BaseNode::default()
+    };
+    Node::Image(base, ImageNode::new(resource.into()))
 }
 
 pub fn comp(layers: impl Into<Vec<Node>>) -> Node {
@@ -45,8 +57,19 @@ pub fn comp(layers: impl Into<Vec<Node>>) -> Node {
     Node::Composition(base, CompositionNode::new(layers.into()))
 }
 
-pub fn path(color: impl Into<Color>, from: impl Into<Point2D>) -> path::Builder {
-    let mut base = BaseNode::default();
-    base.background = color.into();
+/// Node that fills an existing path, e.g. the result of [PathNode::union]
+pub fn shape(paint: impl Into<Paint>, path: PathNode) -> Node {
+    let base = BaseNode {
+        background: paint.into(),
+        ..BaseNode::default()
+    };
+    Node::Path(base, path)
+}
+
+pub fn path(paint: impl Into<Paint>, from: impl Into<Point2D>) -> path::Builder {
+    let base = BaseNode {
+        background: paint.into(),
+        ..BaseNode::default()
+    };
     PathNode::builder(base, from)
 }
@@ -1,10 +1,15 @@
 use crate::component::Component;
+use crate::math::Transform;
 use crate::node::base::BaseNode;
 use crate::node::border::BorderNode;
 use crate::node::composition::CompositionNode;
-use crate::node::image::ImageNode;
+use crate::node::image::{ImageFit, ImageNode, NineSlice, Sampling};
 use crate::node::path::PathNode;
 use crate::node::text::TextNode;
+use crate::node::{Clip, Shadow};
+use crate::util::{id, Flags, Paint, Point2D};
+use async_recursion::async_recursion;
+use std::hash::Hash;
 
 pub enum Node {
     Rectangle(BaseNode),
@@ -15,3 +20,249 @@ pub enum Node {
     Text(BaseNode, TextNode),
     Component(BaseNode, Box<dyn Component + Sync + Send>),
 }
+impl Node {
+    pub(crate) fn base_mut(&mut self) -> &mut BaseNode {
+        match self {
+            Node::Rectangle(base)
+            | Node::Border(base, _)
+            | Node::Path(base, _)
+            | Node::Composition(base, _)
+            | Node::Image(base, _)
+            | Node::Text(base, _)
+            | Node::Component(base, _) => base,
+        }
+    }
+
+    /// Sets the key that identifies the node, e.g. for [crate::animation::Animation]
+    pub fn key(mut self, key: impl Hash) -> Self {
+        self.base_mut().key = Some(id(&key));
+        self
+    }
+
+    /// Fills the node with a color or gradient
+    pub fn paint(mut self, paint: impl Into<Paint>) -> Self {
+        self.base_mut().background = paint.into();
+        self
+    }
+
+    /// Sets the opacity in `0..=1` that the colors of the node are multiplied with
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.base_mut().opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fades the node and its children as a whole. In contrast to [Node::opacity] the
+    /// children are composited first, so overlapping children don't shine through
+    /// each other. This requires an extra render pass, therefore use it sparingly.
+    pub fn group_opacity(mut self, opacity: f32) -> Self {
+        self.base_mut().group_opacity = Some(opacity.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Sets the opacity of a layer of a composition. Has no effect on other nodes.
+    pub fn layer_opacity(mut self, index: usize, opacity: f32) -> Self {
+        if let Node::Composition(_, c) = &mut self {
+            c.set_layer_opacity(index, opacity);
+        }
+        self
+    }
+
+    /// Sets how an image is sized within the bounds of the node. Has no effect on
+    /// other nodes.
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        if let Node::Image(_, i) = &mut self {
+            i.fit = fit;
+        }
+        self
+    }
+
+    /// Aligns an image in the space that its fit leaves, `(0.5, 0.5)` centers it like
+    /// the CSS `object-position: 50% 50%`. Has no effect on other nodes.
+    pub fn object_position(mut self, x: f32, y: f32) -> Self {
+        if let Node::Image(_, i) = &mut self {
+            i.position = [x, y];
+        }
+        self
+    }
+
+    /// Scales an image in nine slices instead of as a whole. Replaces the fit. Has no
+    /// effect on other nodes.
+    pub fn nine_slice(mut self, slice: NineSlice) -> Self {
+        if let Node::Image(_, i) = &mut self {
+            i.slice = Some(slice);
+        }
+        self
+    }
+
+    /// Sets how the texels of an image are filtered. Has no effect on other nodes.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        if let Node::Image(_, i) = &mut self {
+            i.sampling = sampling;
+        }
+        self
+    }
+
+    /// Sets whether an animated image plays once it's mounted, which it does by
+    /// default. Images with a key can be played and paused with
+    /// [crate::animation::play] and [crate::animation::pause]. Has no effect on other
+    /// nodes.
+    pub fn autoplay(mut self, autoplay: bool) -> Self {
+        if let Node::Image(_, i) = &mut self {
+            i.autoplay = autoplay;
+        }
+        self
+    }
+
+    /// Doesn't paint the node itself, its children are still painted
+    pub fn transparent(mut self) -> Self {
+        let base = self.base_mut();
+        base.flags = base.flags | Flags::TRANSPARENT;
+        self
+    }
+
+    /// Transforms the node and its children around the transform origin
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.base_mut().transform = transform;
+        self
+    }
+
+    /// Sets the point the transform is applied around relative to the size of the
+    /// node, e.g. `(0.0, 0.0)` for the top left corner. Defaults to the center.
+    pub fn transform_origin(mut self, x: f32, y: f32) -> Self {
+        self.base_mut().transform_origin = [x, y];
+        self
+    }
+
+    /// Clips the node and its children. Clips of nested nodes intersect.
+    pub fn clip(mut self, clip: Clip) -> Self {
+        self.base_mut().clip = Some(clip);
+        self
+    }
+
+    /// Adds a shadow. Multiple shadows are painted in the order they were added.
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.base_mut().shadows.push(shadow);
+        self
+    }
+
+    /// Blurs the node and its children like the CSS `filter: blur()`, the radius is
+    /// the standard deviation of the blur in pixels. Like [Node::group_opacity] this
+    /// renders the node into its own layer.
+    pub fn blur(mut self, radius: f32) -> Self {
+        self.base_mut().blur = radius.max(0.0);
+        self
+    }
+
+    /// Blurs what is painted behind the node within its bounding rect and border
+    /// radii, e.g. for frosted panels. See [Node::blur] for the radius. The backdrop
+    /// is rendered a second time into its own layer, therefore use it sparingly.
+    pub fn backdrop_blur(mut self, radius: f32) -> Self {
+        self.base_mut().backdrop_blur = radius.max(0.0);
+        self
+    }
+
+    /// Whether the point in surface coordinates hits the node or one of its
+    /// children. The transforms of the node and its ancestors are taken into account,
+    /// points outside of the clips of the node and its ancestors never hit.
+    pub async fn hit_test(&mut self, point: impl Into<Point2D>) -> bool {
+        self.hit(Transform::IDENTITY, point.into()).await
+    }
+
+    #[async_recursion]
+    async fn hit(&mut self, parent: Transform, point: Point2D) -> bool {
+        let transform = self.base_mut().world_transform(&parent);
+        let [x, y] = match transform.inverse() {
+            Some(inverse) => inverse.apply(point),
+            // The node was collapsed onto a line or point
+            None => return false,
+        };
+        if !self.base_mut().clip_contains([x, y]) {
+            return false;
+        }
+        match self {
+            Node::Border(base, b) => {
+                b.node_mut().hit(transform, point).await || base.bounding_rect.contains(x, y)
+            }
+            Node::Composition(base, c) => {
+                // Upper layers are hit first
+                for node in c.layers_mut().iter_mut().rev() {
+                    if node.hit(transform, point).await {
+                        return true;
+                    }
+                }
+                base.bounding_rect.contains(x, y)
+            }
+            Node::Component(_, c) => c.node().await.hit(transform, point).await,
+            Node::Rectangle(base)
+            | Node::Path(base, _)
+            | Node::Image(base, _)
+            | Node::Text(base, _) => base.bounding_rect.contains(x, y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Transform;
+    use crate::node::path::PathNode;
+    use crate::node::{comp, rect, Clip, Node};
+    use crate::util::{Color, Flags, Rect};
+
+    fn placed(mut node: Node, x: i32, y: i32, width: u32, height: u32) -> Node {
+        let base = node.base_mut();
+        base.bounding_rect = Rect::new(x, y, width, height);
+        base.flags = Flags::NONE;
+        node
+    }
+
+    fn hits(node: &mut Node, point: [f32; 2]) -> bool {
+        pollster::block_on(node.hit_test(point))
+    }
+
+    #[test]
+    fn transforms_move_the_hit_area() {
+        let mut node = placed(rect(Color::BLACK, [0.0; 4]), 0, 0, 10, 10)
+            .transform(Transform::scale(2.0, 2.0))
+            .transform_origin(0.0, 0.0);
+        assert!(hits(&mut node, [15.0, 15.0]));
+        assert!(!hits(&mut node, [25.0, 5.0]));
+
+        let mut collapsed = placed(rect(Color::BLACK, [0.0; 4]), 0, 0, 10, 10)
+            .transform(Transform::scale(0.0, 1.0));
+        assert!(!hits(&mut collapsed, [5.0, 5.0]));
+    }
+
+    #[test]
+    fn containers_are_hit_within_their_bounds() {
+        let child = placed(rect(Color::BLACK, [0.0; 4]), 150, 0, 10, 10);
+        let mut node = placed(comp([child]), 0, 0, 100, 100);
+        assert!(hits(&mut node, [50.0, 50.0]));
+        assert!(hits(&mut node, [155.0, 5.0]));
+        assert!(!hits(&mut node, [155.0, 50.0]));
+    }
+
+    #[test]
+    fn clips_reject_points_of_children() {
+        let child = || placed(rect(Color::BLACK, [0.0; 4]), 0, 0, 200, 200);
+        let mut bounds = placed(comp([child()]), 0, 0, 100, 100).clip(Clip::Bounds);
+        assert!(hits(&mut bounds, [50.0, 50.0]));
+        assert!(!hits(&mut bounds, [150.0, 150.0]));
+
+        // A circle, the radii are relative to the width
+        let mut rounded = placed(comp([child()]), 0, 0, 100, 100).clip(Clip::Rounded([0.5; 4]));
+        assert!(hits(&mut rounded, [50.0, 50.0]));
+        assert!(!hits(&mut rounded, [5.0, 5.0]));
+
+        // The clip of an ancestor applies to nested nodes as well
+        let nested = placed(comp([child()]), 0, 0, 200, 200);
+        let mut ancestor = placed(comp([nested]), 0, 0, 100, 100).clip(Clip::Bounds);
+        assert!(!hits(&mut ancestor, [150.0, 150.0]));
+
+        // The lower left half of the node
+        let triangle = PathNode::from_polygons(vec![vec![[0.0, 0.0], [0.0, 1.0], [1.0, 1.0]]])
+            .with_view_box([0.0, 0.0, 1.0, 1.0]);
+        let mut path = placed(comp([child()]), 0, 0, 100, 100).clip(Clip::Path(triangle));
+        assert!(hits(&mut path, [10.0, 90.0]));
+        assert!(!hits(&mut path, [90.0, 10.0]));
+    }
+}
@@ -1,21 +1,147 @@
+use crate::math::path::{self, BooleanOp};
+use crate::math::Transform;
 use crate::node::base::BaseNode;
-use crate::util::{PathSegment, Point2D};
+use crate::node::stroke::Stroke;
+use crate::util::{PathSegment, Point2D, Rect};
 use crate::Node;
 
+/// Decides which areas enclosed by the subpaths of a path are inside of it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Areas that the subpaths wind around in total at least once are inside
+    #[default]
+    NonZero,
+    /// Areas that are enclosed by an odd number of subpaths are inside, e.g. holes
+    EvenOdd,
+}
+
+/// How a view box is scaled into the bounds of a node when their aspect ratios
+/// differ, like the SVG `preserveAspectRatio` attribute
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AspectRatio {
+    /// Scales both axes independently, so the view box fills the bounds exactly
+    Stretch,
+    /// Scales uniformly, so the whole view box fits into the bounds. `align` places
+    /// it in the remaining space relative to its size, `[0.5, 0.5]` centers it.
+    Meet { align: [f32; 2] },
+    /// Scales uniformly, so the view box covers the bounds. What lies outside of the
+    /// bounds is still painted unless the node is clipped, see [crate::node::Clip].
+    Slice { align: [f32; 2] },
+}
+impl Default for AspectRatio {
+    fn default() -> Self {
+        AspectRatio::Meet { align: [0.5, 0.5] }
+    }
+}
+
+/// Own coordinate space of a path. The rect `[x, y, width, height]` of the view box
+/// is mapped into the bounding rect of the node, the y axis points down like the
+/// one of pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ViewBox {
+    pub rect: [f32; 4],
+    pub aspect_ratio: AspectRatio,
+}
+impl ViewBox {
+    pub fn new(rect: [f32; 4]) -> Self {
+        ViewBox {
+            rect,
+            aspect_ratio: AspectRatio::default(),
+        }
+    }
+
+    pub fn aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Maps the view box to the bounds in pixels. Empty view boxes aren't painted.
+    pub(crate) fn transform(&self, bounds: &Rect) -> Option<Transform> {
+        let [x, y, width, height] = self.rect;
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+        let bounds_width = bounds.extent.width as f32;
+        let bounds_height = bounds.extent.height as f32;
+        let (scale_x, scale_y) = (bounds_width / width, bounds_height / height);
+        let (scale_x, scale_y, align) = match self.aspect_ratio {
+            AspectRatio::Stretch => (scale_x, scale_y, [0.0, 0.0]),
+            AspectRatio::Meet { align } => (scale_x.min(scale_y), scale_x.min(scale_y), align),
+            AspectRatio::Slice { align } => (scale_x.max(scale_y), scale_x.max(scale_y), align),
+        };
+        Some(Transform::new(
+            scale_x,
+            0.0,
+            0.0,
+            scale_y,
+            bounds.offset.x as f32 + (bounds_width - width * scale_x) * align[0] - x * scale_x,
+            bounds.offset.y as f32 + (bounds_height - height * scale_y) * align[1] - y * scale_y,
+        ))
+    }
+}
+
+impl From<[f32; 4]> for ViewBox {
+    fn from(rect: [f32; 4]) -> Self {
+        ViewBox::new(rect)
+    }
+}
+
 pub struct Builder {
     base: BaseNode,
     from: Point2D,
+    /// Start of the current subpath
+    start: Point2D,
     segments: Vec<PathSegment>,
+    stroke: Option<Stroke>,
+    fill_rule: FillRule,
+    view_box: Option<ViewBox>,
 }
 impl Builder {
     pub fn new(base: BaseNode, from: Point2D) -> Self {
         Builder {
             base,
             from,
+            start: from,
             segments: vec![],
+            stroke: None,
+            fill_rule: FillRule::NonZero,
+            view_box: None,
         }
     }
 
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Starts a new subpath at `to`. Subpaths are closed implicitly when they are
+    /// filled, but not when they are stroked.
+    pub fn move_to(mut self, to: impl Into<Point2D>) -> Self {
+        self.start = to.into();
+        self.segments.push(PathSegment::Move { to: self.start });
+        self
+    }
+
+    /// Closes the current subpath with a line to its start
+    pub fn close_subpath(mut self) -> Self {
+        self.segments.push(PathSegment::Linear { to: self.start });
+        self
+    }
+
+    /// Strokes the outline of the path with the paint of the node instead of
+    /// filling it, e.g. `stroke(0.01)` or `stroke(Stroke::new(0.01).join(LineJoin::Round))`
+    pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.stroke = Some(stroke.into());
+        self
+    }
+
+    /// Gives the path its own coordinates, e.g. pixels with `view_box([0.0, 0.0,
+    /// 24.0, 24.0])`, which are mapped into the bounding rect of the node
+    pub fn view_box(mut self, view_box: impl Into<ViewBox>) -> Self {
+        self.view_box = Some(view_box.into());
+        self
+    }
+
     pub fn cubic_bezier(
         mut self,
         to: impl Into<Point2D>,
@@ -29,6 +155,46 @@ impl Builder {
         self
     }
 
+    pub fn quadratic_bezier(mut self, to: impl Into<Point2D>, param: impl Into<Point2D>) -> Self {
+        self.segments.push(PathSegment::QuadraticBezier {
+            to: to.into(),
+            param: param.into(),
+        });
+        self
+    }
+
+    /// Elliptical arc with the flags of the SVG arc command, see [PathSegment::Arc]
+    pub fn arc(
+        mut self,
+        to: impl Into<Point2D>,
+        radii: [f32; 2],
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+    ) -> Self {
+        self.segments.push(PathSegment::Arc {
+            to: to.into(),
+            radii,
+            rotation,
+            large_arc,
+            sweep,
+        });
+        self
+    }
+
+    /// Smooth curve through all points of `through` to `to`
+    pub fn catmull_rom(
+        mut self,
+        to: impl Into<Point2D>,
+        through: impl IntoIterator<Item = impl Into<Point2D>>,
+    ) -> Self {
+        self.segments.push(PathSegment::CatmullRom {
+            to: to.into(),
+            through: through.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
     pub fn linear(mut self, to: impl Into<Point2D>) -> Self {
         self.segments.push(PathSegment::Linear { to: to.into() });
         self
@@ -40,19 +206,16 @@ impl Builder {
             PathNode {
                 from: self.from,
                 segments: self.segments,
+                stroke: self.stroke,
+                fill_rule: self.fill_rule,
+                view_box: self.view_box,
             },
         )
     }
 
-    pub fn close(mut self) -> Node {
-        self.segments.push(PathSegment::Linear { to: self.from });
-        Node::Path(
-            self.base,
-            PathNode {
-                from: self.from,
-                segments: self.segments,
-            },
-        )
+    /// Closes the current subpath and builds the node
+    pub fn close(self) -> Node {
+        self.close_subpath().build()
     }
 }
 
@@ -60,10 +223,21 @@ impl Builder {
 pub struct PathNode {
     pub(crate) from: Point2D,
     pub(crate) segments: Vec<PathSegment>,
+    pub(crate) stroke: Option<Stroke>,
+    pub(crate) fill_rule: FillRule,
+    /// Without a view box the points are in the coordinates of path nodes, which
+    /// span `-1..1` vertically with the y axis pointing up and the surface centered
+    pub(crate) view_box: Option<ViewBox>,
 }
 impl PathNode {
     pub fn new(from: Point2D, segments: Vec<PathSegment>) -> Self {
-        PathNode { from, segments }
+        PathNode {
+            from,
+            segments,
+            stroke: None,
+            fill_rule: FillRule::NonZero,
+            view_box: None,
+        }
     }
 
     pub fn builder(base: BaseNode, from: impl Into<Point2D>) -> Builder {
@@ -77,4 +251,224 @@ impl PathNode {
     pub fn segments(&self) -> &Vec<PathSegment> {
         &self.segments
     }
+
+    pub fn stroke(&self) -> Option<&Stroke> {
+        self.stroke.as_ref()
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    pub fn view_box(&self) -> Option<&ViewBox> {
+        self.view_box.as_ref()
+    }
+
+    /// The same path in the coordinates of the view box, see [Builder::view_box]
+    pub fn with_view_box(mut self, view_box: impl Into<ViewBox>) -> Self {
+        self.view_box = Some(view_box.into());
+        self
+    }
+
+    /// Tight bounds `[x_min, y_min, x_max, y_max]` of the path without its stroke
+    pub fn bounds(&self) -> [f32; 4] {
+        path::bounds(self)
+    }
+
+    /// Whether the point lies inside of the path with the fill rule
+    pub fn contains(&self, point: impl Into<Point2D>, fill_rule: FillRule) -> bool {
+        path::contains(self, point.into(), fill_rule)
+    }
+
+    /// Polylines of the subpaths that deviate at most by `tolerance` from the curves
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec<Point2D>> {
+        path::flatten(self, tolerance)
+    }
+
+    /// Length of all subpaths
+    pub fn length(&self) -> f32 {
+        path::length(self)
+    }
+
+    /// Point at the distance along the path, see [path::point_at_length]
+    pub fn point_at_length(&self, length: f32) -> Point2D {
+        path::point_at_length(self, length)
+    }
+
+    /// Area inside of either path, see [path::boolean]
+    pub fn union(&self, other: &PathNode) -> PathNode {
+        path::boolean(self, other, BooleanOp::Union)
+    }
+
+    /// Area inside of both paths
+    pub fn intersection(&self, other: &PathNode) -> PathNode {
+        path::boolean(self, other, BooleanOp::Intersection)
+    }
+
+    /// Area inside of this path but not the other
+    pub fn difference(&self, other: &PathNode) -> PathNode {
+        path::boolean(self, other, BooleanOp::Difference)
+    }
+
+    /// Area inside of exactly one of the paths
+    pub fn xor(&self, other: &PathNode) -> PathNode {
+        path::boolean(self, other, BooleanOp::Xor)
+    }
+
+    /// The path that is filled when the node is painted, which is the outline of
+    /// the stroke for stroked paths
+    pub(crate) fn outline(&self) -> PathNode {
+        match &self.stroke {
+            Some(stroke) => stroke.outline(self),
+            None => self.clone(),
+        }
+    }
+
+    /// The same path with only moves, lines and cubic bezier curves
+    pub(crate) fn normalized(&self) -> PathNode {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut from = self.from;
+        for segment in &self.segments {
+            segment.to_cubics(from, &mut segments);
+            from = *segment.to();
+        }
+        PathNode {
+            from: self.from,
+            segments,
+            stroke: self.stroke.clone(),
+            fill_rule: self.fill_rule,
+            view_box: self.view_box,
+        }
+    }
+
+    /// Path with a subpath for each polygon. The polygons are oriented
+    /// counter-clockwise, so that their windings add up where they overlap.
+    pub(crate) fn from_polygons(polygons: Vec<Vec<Point2D>>) -> PathNode {
+        let mut from = None;
+        let mut segments = vec![];
+        for mut polygon in polygons {
+            let area: f32 = polygon
+                .iter()
+                .zip(polygon.iter().cycle().skip(1))
+                .map(|(a, b)| a[0] * b[1] - a[1] * b[0])
+                .sum();
+            if area == 0.0 {
+                continue;
+            }
+            if area < 0.0 {
+                polygon.reverse();
+            }
+            if from.is_none() {
+                from = Some(polygon[0]);
+            } else {
+                segments.push(PathSegment::Move { to: polygon[0] });
+            }
+            segments.extend(
+                polygon[1..]
+                    .iter()
+                    .map(|to| PathSegment::Linear { to: *to }),
+            );
+        }
+        PathNode::new(from.unwrap_or([0.0, 0.0]), segments)
+    }
+
+    /// Rect `[x, y, width, height]` with the corner radii in the order top left,
+    /// top right, bottom left and bottom right. The corners are cubic bezier
+    /// approximations of circular arcs.
+    pub(crate) fn rounded_rect(rect: [f32; 4], radii: [f32; 4]) -> PathNode {
+        // Distance of the control points for a quarter circle with radius one
+        const KAPPA: f32 = 0.552_284_8;
+        let [x, y, width, height] = rect;
+        let max = width.min(height) / 2.0;
+        let [top_left, top_right, bottom_left, bottom_right] = radii.map(|r| r.clamp(0.0, max));
+        let (right, bottom) = (x + width, y + height);
+
+        let corner =
+            |to: Point2D, a: Point2D, b: Point2D| PathSegment::CubicBezier { to, params: [a, b] };
+        let segments = vec![
+            PathSegment::Linear {
+                to: [right - top_right, y],
+            },
+            corner(
+                [right, y + top_right],
+                [right - top_right + KAPPA * top_right, y],
+                [right, y + top_right - KAPPA * top_right],
+            ),
+            PathSegment::Linear {
+                to: [right, bottom - bottom_right],
+            },
+            corner(
+                [right - bottom_right, bottom],
+                [right, bottom - bottom_right + KAPPA * bottom_right],
+                [right - bottom_right + KAPPA * bottom_right, bottom],
+            ),
+            PathSegment::Linear {
+                to: [x + bottom_left, bottom],
+            },
+            corner(
+                [x, bottom - bottom_left],
+                [x + bottom_left - KAPPA * bottom_left, bottom],
+                [x, bottom - bottom_left + KAPPA * bottom_left],
+            ),
+            PathSegment::Linear {
+                to: [x, y + top_left],
+            },
+            corner(
+                [x + top_left, y],
+                [x, y + top_left - KAPPA * top_left],
+                [x + top_left - KAPPA * top_left, y],
+            ),
+        ];
+        PathNode::new([x + top_left, y], segments)
+    }
+
+    /// Maps all control points with the transform. Affine transforms keep bezier
+    /// curves and splines intact, arcs are converted into bezier curves first.
+    pub(crate) fn transformed(&self, transform: &Transform) -> PathNode {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut from = self.from;
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Move { to } => segments.push(PathSegment::Move {
+                    to: transform.apply(*to),
+                }),
+                PathSegment::Linear { to } => segments.push(PathSegment::Linear {
+                    to: transform.apply(*to),
+                }),
+                PathSegment::Arc { .. } => {
+                    let mut cubics = vec![];
+                    segment.to_cubics(from, &mut cubics);
+                    segments.extend(PathNode::new(from, cubics).transformed(transform).segments);
+                }
+                PathSegment::QuadraticBezier { to, param } => {
+                    segments.push(PathSegment::QuadraticBezier {
+                        to: transform.apply(*to),
+                        param: transform.apply(*param),
+                    })
+                }
+                PathSegment::CubicBezier { to, params } => {
+                    segments.push(PathSegment::CubicBezier {
+                        to: transform.apply(*to),
+                        params: [transform.apply(params[0]), transform.apply(params[1])],
+                    })
+                }
+                PathSegment::CatmullRom { to, through } => segments.push(PathSegment::CatmullRom {
+                    to: transform.apply(*to),
+                    through: through
+                        .iter()
+                        .map(|point| transform.apply(*point))
+                        .collect(),
+                }),
+            }
+            from = *segment.to();
+        }
+        PathNode {
+            from: transform.apply(self.from),
+            segments,
+            stroke: self.stroke.clone(),
+            fill_rule: self.fill_rule,
+            // The transformed points are in other coordinates
+            view_box: None,
+        }
+    }
 }
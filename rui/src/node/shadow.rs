@@ -0,0 +1,36 @@
+use crate::util::Color;
+
+/// Shadow of the bounding rect of a node with its border radii, like the CSS
+/// `box-shadow`. Lengths are in pixels.
+#[derive(Debug, Copy, Clone)]
+pub struct Shadow {
+    pub offset: [f32; 2],
+    /// Blur radius, the standard deviation of the blur is half of it
+    pub blur: f32,
+    /// Grows the shadow in all directions, negative values shrink it
+    pub spread: f32,
+    pub color: Color,
+    /// Casts the shadow inside of the node instead of outside
+    pub inset: bool,
+}
+impl Shadow {
+    pub fn new(offset: [f32; 2], blur: f32, color: impl Into<Color>) -> Self {
+        Shadow {
+            offset,
+            blur,
+            spread: 0.0,
+            color: color.into(),
+            inset: false,
+        }
+    }
+
+    pub fn spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn inset(mut self) -> Self {
+        self.inset = true;
+        self
+    }
+}
@@ -0,0 +1,394 @@
+use crate::math::path::{flatten, TOLERANCE};
+use crate::math::{add, cross, distance, dot, lerp, normal, normalize, scale, sub};
+use crate::node::path::PathNode;
+use crate::util::Point2D;
+use std::f32::consts::PI;
+
+/// Shape of the outer corner where two segments of a stroke meet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Extends the outer edges until they meet, see [Stroke::miter_limit]
+    #[default]
+    Miter,
+    Round,
+    /// Cuts the corner off with a straight line
+    Bevel,
+}
+
+/// Shape of the ends of open strokes and dashes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point
+    #[default]
+    Butt,
+    /// Adds a half circle with the diameter of the width
+    Round,
+    /// Extends the end by half of the width
+    Square,
+}
+
+/// Outline of a path. Lengths are in the coordinates of the path, which are the ones
+/// of its view box or otherwise those of path nodes, in which the height of the
+/// surface is two.
+#[derive(Debug, Clone)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Miter joins longer than `miter_limit` times the width are beveled
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps. Patterns of odd length are repeated
+    /// once, an empty pattern draws a solid line.
+    pub dashes: Vec<f32>,
+    /// Distance into the dash pattern at which the stroke starts
+    pub dash_offset: f32,
+}
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Stroke {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dashes: vec![],
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+
+    pub fn dashes(mut self, dashes: impl Into<Vec<f32>>, offset: f32) -> Self {
+        self.dashes = dashes.into();
+        self.dash_offset = offset;
+        self
+    }
+
+    /// Fillable path that covers the stroke of `path`. The outline is the union of a
+    /// quad for every line, a polygon for every join and cap, which all have the same
+    /// orientation, therefore their windings never cancel out.
+    pub(crate) fn outline(&self, path: &PathNode) -> PathNode {
+        let half = self.width / 2.0;
+        let mut polygons = vec![];
+        if half <= 0.0 {
+            return PathNode::from_polygons(polygons);
+        }
+
+        let solid =
+            self.dashes.iter().any(|dash| *dash < 0.0) || self.dashes.iter().sum::<f32>() <= 0.0;
+        // Each subpath starts the dash pattern anew
+        for points in flatten(path, TOLERANCE) {
+            let closed = points.len() > 2 && points.first() == points.last();
+            if solid {
+                self.polyline(&points, closed, half, &mut polygons);
+            } else {
+                for dash in self.dash(&points) {
+                    self.polyline(&dash, false, half, &mut polygons);
+                }
+            }
+        }
+        PathNode::from_polygons(polygons)
+    }
+
+    /// Splits the polyline into the dashes of the pattern
+    fn dash(&self, points: &[Point2D]) -> Vec<Vec<Point2D>> {
+        let mut pattern = self.dashes.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_slice(&self.dashes);
+        }
+        let total: f32 = pattern.iter().sum();
+
+        // Advance to the dash at the offset
+        let mut index = 0;
+        let mut remaining = pattern[0];
+        let mut skip = self.dash_offset.rem_euclid(total);
+        while skip > 0.0 {
+            if skip < remaining {
+                remaining -= skip;
+                break;
+            }
+            skip -= remaining;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+
+        let mut dashes = vec![];
+        let mut current = vec![points[0]];
+        for pair in points.windows(2) {
+            let (mut from, to) = (pair[0], pair[1]);
+            let mut length = distance(from, to);
+            while length > remaining {
+                let at = lerp(from, to, remaining / length);
+                if index % 2 == 0 {
+                    current.push(at);
+                    dashes.push(std::mem::take(&mut current));
+                } else {
+                    current = vec![at];
+                }
+                length -= remaining;
+                from = at;
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+            }
+            remaining -= length;
+            if index % 2 == 0 {
+                current.push(to);
+            }
+        }
+        if index % 2 == 0 {
+            dashes.push(current);
+        }
+        dashes
+    }
+
+    fn polyline(
+        &self,
+        points: &[Point2D],
+        closed: bool,
+        half: f32,
+        polygons: &mut Vec<Vec<Point2D>>,
+    ) {
+        let mut points = points.to_vec();
+        points.dedup();
+        if points.len() < 2 {
+            // Zero length subpaths only show their caps
+            let center = points[0];
+            match self.cap {
+                LineCap::Butt => {}
+                LineCap::Round => polygons.push(arc(center, [half, 0.0], 2.0 * PI, half)),
+                LineCap::Square => polygons.push(vec![
+                    [center[0] - half, center[1] - half],
+                    [center[0] + half, center[1] - half],
+                    [center[0] + half, center[1] + half],
+                    [center[0] - half, center[1] + half],
+                ]),
+            }
+            return;
+        }
+
+        for pair in points.windows(2) {
+            let normal = scale(normal(pair[0], pair[1]), half);
+            polygons.push(vec![
+                add(pair[0], normal),
+                add(pair[1], normal),
+                sub(pair[1], normal),
+                sub(pair[0], normal),
+            ]);
+        }
+        for triple in points.windows(3) {
+            self.push_join(triple[0], triple[1], triple[2], half, polygons);
+        }
+        if closed {
+            let n = points.len();
+            self.push_join(points[n - 2], points[0], points[1], half, polygons);
+        } else {
+            let n = points.len();
+            self.push_cap(points[1], points[0], half, polygons);
+            self.push_cap(points[n - 2], points[n - 1], half, polygons);
+        }
+    }
+
+    /// Fills the gap on the outer side of the corner at `at`
+    fn push_join(
+        &self,
+        from: Point2D,
+        at: Point2D,
+        to: Point2D,
+        half: f32,
+        polygons: &mut Vec<Vec<Point2D>>,
+    ) {
+        let (incoming, outgoing) = (normal(from, at), normal(at, to));
+        let turn = cross(sub(at, from), sub(to, at));
+        // The outer side lies right of the segments on left turns
+        let side = if turn > 0.0 { -half } else { half };
+        let (a, b) = (scale(incoming, side), scale(outgoing, side));
+        let angle = cross(a, b).atan2(dot(a, b));
+        if angle.abs() < f32::EPSILON {
+            return;
+        }
+
+        let mut polygon = vec![at, add(at, a)];
+        match self.join {
+            LineJoin::Round => polygon = arc(at, a, angle, half),
+            LineJoin::Miter => {
+                // Ratio of the miter length to the width
+                let cos = (angle / 2.0).cos();
+                if cos > 0.0 && 1.0 / cos <= self.miter_limit {
+                    let bisector = normalize(add(a, b));
+                    polygon.push(add(at, scale(bisector, half / cos)));
+                }
+            }
+            LineJoin::Bevel => {}
+        }
+        if self.join != LineJoin::Round {
+            polygon.push(add(at, b));
+        }
+        polygons.push(polygon);
+    }
+
+    /// Adds the cap to the end `at` of the line coming from `from`
+    fn push_cap(&self, from: Point2D, at: Point2D, half: f32, polygons: &mut Vec<Vec<Point2D>>) {
+        let normal = scale(normal(from, at), half);
+        match self.cap {
+            LineCap::Butt => {}
+            LineCap::Round => polygons.push(arc(at, normal, -PI, half)),
+            LineCap::Square => {
+                let extension = scale(normalize(sub(at, from)), half);
+                polygons.push(vec![
+                    add(at, normal),
+                    add(add(at, normal), extension),
+                    add(sub(at, normal), extension),
+                    sub(at, normal),
+                ]);
+            }
+        }
+    }
+}
+
+impl From<f32> for Stroke {
+    fn from(width: f32) -> Self {
+        Stroke::new(width)
+    }
+}
+
+fn steps(n: f32) -> usize {
+    (n.ceil() as usize).clamp(1, 256)
+}
+
+/// Polygon of the center and the arc with the radius that starts at `center + from`
+/// and turns by `angle` radians
+fn arc(center: Point2D, from: Point2D, angle: f32, radius: f32) -> Vec<Point2D> {
+    let step = 2.0 * (1.0 - TOLERANCE / radius).clamp(-1.0, 1.0).acos();
+    let n = steps(angle.abs() / step.max(f32::EPSILON)).max(4);
+    let start = from[1].atan2(from[0]);
+    let mut polygon = vec![center];
+    polygon.extend((0..=n).map(|i| {
+        let angle = start + angle * i as f32 / n as f32;
+        [
+            center[0] + radius * angle.cos(),
+            center[1] + radius * angle.sin(),
+        ]
+    }));
+    polygon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCap, LineJoin, Stroke};
+    use crate::node::path::{FillRule, PathNode};
+    use crate::util::{PathSegment, Point2D};
+
+    fn polyline(points: &[Point2D]) -> PathNode {
+        let segments = points[1..]
+            .iter()
+            .map(|to| PathSegment::Linear { to: *to })
+            .collect();
+        PathNode::new(points[0], segments)
+    }
+
+    fn covers(stroke: &Stroke, path: &PathNode, point: Point2D) -> bool {
+        stroke.outline(path).contains(point, FillRule::NonZero)
+    }
+
+    #[test]
+    fn caps() {
+        let line = polyline(&[[0.0, 0.0], [4.0, 0.0]]);
+        let butt = Stroke::new(1.0);
+        assert!(covers(&butt, &line, [0.1, 0.4]));
+        assert!(!covers(&butt, &line, [0.0, 0.6]));
+        assert!(!covers(&butt, &line, [-0.1, 0.0]));
+        assert!(!covers(&butt, &line, [4.1, 0.0]));
+
+        let square = Stroke::new(1.0).cap(LineCap::Square);
+        assert!(covers(&square, &line, [-0.4, 0.0]));
+        assert!(covers(&square, &line, [-0.4, 0.4]));
+        assert!(covers(&square, &line, [4.4, -0.4]));
+        assert!(!covers(&square, &line, [-0.6, 0.0]));
+
+        let round = Stroke::new(1.0).cap(LineCap::Round);
+        assert!(covers(&round, &line, [-0.45, 0.0]));
+        assert!(covers(&round, &line, [4.3, 0.3]));
+        assert!(!covers(&round, &line, [-0.4, 0.4]));
+        assert!(!covers(&round, &line, [-0.6, 0.0]));
+    }
+
+    #[test]
+    fn closed_subpaths_have_no_caps() {
+        let closed = polyline(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]]);
+        let stroke = Stroke::new(1.0).cap(LineCap::Round);
+        // The corner at the start is joined with a miter instead
+        assert!(covers(&stroke, &closed, [-0.45, -0.45]));
+        assert!(!covers(&stroke, &closed, [1.0, 1.0]));
+    }
+
+    #[test]
+    fn joins() {
+        // The outer corner of the turn is at `[2.5, -0.5]`
+        let corner = polyline(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0]]);
+        let miter = Stroke::new(1.0);
+        assert!(covers(&miter, &corner, [2.45, -0.45]));
+        assert!(!covers(&miter, &corner, [2.55, -0.55]));
+
+        let round = Stroke::new(1.0).join(LineJoin::Round);
+        assert!(covers(&round, &corner, [2.3, -0.3]));
+        assert!(!covers(&round, &corner, [2.45, -0.45]));
+
+        // The bevel cuts the corner from `[2.0, -0.5]` to `[2.5, 0.0]`
+        let bevel = Stroke::new(1.0).join(LineJoin::Bevel);
+        assert!(covers(&bevel, &corner, [2.2, -0.2]));
+        assert!(!covers(&bevel, &corner, [2.3, -0.3]));
+
+        // The miter of a right angle is √2 times the width
+        let limited = Stroke::new(1.0).miter_limit(1.2);
+        assert!(!covers(&limited, &corner, [2.45, -0.45]));
+        assert!(covers(&limited, &corner, [2.2, -0.2]));
+    }
+
+    #[test]
+    fn dashes() {
+        let line = polyline(&[[0.0, 0.0], [10.0, 0.0]]);
+        // Patterns of odd length are repeated once, so the second 2 is a gap at first
+        // and a dash the next time: dashes at 0..2, 3..5 and 7..8
+        let dashed = Stroke::new(1.0).dashes([2.0, 1.0, 2.0], 0.0);
+        assert!(covers(&dashed, &line, [1.0, 0.0]));
+        assert!(!covers(&dashed, &line, [2.5, 0.0]));
+        assert!(covers(&dashed, &line, [4.0, 0.0]));
+        assert!(!covers(&dashed, &line, [6.0, 0.0]));
+        assert!(covers(&dashed, &line, [7.5, 0.0]));
+        assert!(!covers(&dashed, &line, [9.0, 0.0]));
+
+        // The phase moves the pattern backwards along the path
+        let shifted = Stroke::new(1.0).dashes([2.0, 1.0], 1.0);
+        assert!(covers(&shifted, &line, [0.5, 0.0]));
+        assert!(!covers(&shifted, &line, [1.5, 0.0]));
+        assert!(covers(&shifted, &line, [3.5, 0.0]));
+        assert!(!covers(&shifted, &line, [4.5, 0.0]));
+
+        // A negative phase starts in the pattern before the path
+        let negative = Stroke::new(1.0).dashes([2.0, 1.0], -1.0);
+        assert!(!covers(&negative, &line, [0.5, 0.0]));
+        assert!(covers(&negative, &line, [1.5, 0.0]));
+
+        // Invalid patterns draw a solid line
+        let solid = Stroke::new(1.0).dashes([1.0, -1.0], 0.0);
+        assert!(covers(&solid, &line, [1.5, 0.0]));
+    }
+
+    #[test]
+    fn empty_width() {
+        let line = polyline(&[[0.0, 0.0], [4.0, 0.0]]);
+        assert!(Stroke::new(0.0).outline(&line).segments.is_empty());
+    }
+}
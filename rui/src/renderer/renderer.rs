@@ -1,3 +1,4 @@
+use crate::animation::{AnimatedValues, Property};
 use crate::node::Node;
 use crate::Backend;
 use rui_util::Extent;
@@ -8,12 +9,14 @@ pub trait Renderer<B>
 where
     B: Backend,
 {
-    type Error: Error + Debug;
+    /// Errors that are returned to the app are converted into [crate::error::Error]
+    type Error: Error + Debug + Into<crate::error::Error>;
 
     fn mount(
         &mut self,
         surface: &rui_io::surface::Surface,
         node: &mut Node,
+        values: &AnimatedValues,
     ) -> Result<(), Self::Error>;
     fn resize(
         &mut self,
@@ -21,5 +24,24 @@ where
         size: Extent,
     ) -> Result<(), Self::Error>;
     fn render(&mut self, surface: &rui_io::surface::Surface) -> Result<(), Self::Error>;
+    /// Patches the animated values into the node that was mounted last on the
+    /// surface. Returns `false` if the node has to be mounted again instead.
+    fn animate(&mut self, surface: &rui_io::surface::Surface, values: &AnimatedValues) -> bool;
+    /// Reads the property of the node with the key from the node that was mounted last
+    /// on the surface, without its animated value
+    fn read(
+        &self,
+        surface: &rui_io::surface::Surface,
+        key: u64,
+        property: &Property,
+    ) -> Option<Property>;
+    /// Whether the node that was mounted last on the surface has a node with the key
+    fn is_mounted(&self, surface: &rui_io::surface::Surface, key: u64) -> bool;
+    /// Whether the image with the key plays once it's mounted, `None` if the node that
+    /// was mounted last on the surface has no such image
+    fn autoplay(&self, surface: &rui_io::surface::Surface, key: u64) -> Option<bool>;
+    /// Whether animated images of the node that was mounted last on the surface show
+    /// other frames later, so that the node has to be mounted again
+    fn is_playing(&self, surface: &rui_io::surface::Surface) -> bool;
     fn request_render(&self) -> Result<(), Self::Error>;
 }
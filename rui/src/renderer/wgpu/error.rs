@@ -3,6 +3,15 @@ use std::fmt::{Debug, Display, Formatter};
 pub enum Error {
     AdapterNotFound,
     DeviceCreationFailed(wgpu::RequestDeviceError),
+    /// The node couldn't be mounted, e.g. because an image couldn't be loaded
+    Mount(crate::error::Error),
+    /// The surface was lost or outdated and configuring it again didn't help
+    SurfaceLost,
+    OutOfMemory,
+    /// Nothing was mounted yet, so there is no GPU device to render with
+    NotMounted,
+    /// No node was mounted on the surface
+    UnknownSurface,
 }
 
 impl Debug for Error {
@@ -12,6 +21,11 @@ impl Debug for Error {
                 write!(f, "Adapter not found!")
             }
             Error::DeviceCreationFailed(err) => Debug::fmt(err, f),
+            Error::Mount(err) => Debug::fmt(err, f),
+            Error::SurfaceLost => write!(f, "Surface lost!"),
+            Error::OutOfMemory => write!(f, "Out of memory!"),
+            Error::NotMounted => write!(f, "Nothing is mounted!"),
+            Error::UnknownSurface => write!(f, "No node is mounted on the surface!"),
         }
     }
 }
@@ -23,3 +37,14 @@ impl Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+impl From<Error> for crate::error::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Mount(err) => err,
+            Error::SurfaceLost => crate::error::Error::SurfaceLost,
+            Error::OutOfMemory => crate::error::Error::OutOfMemory,
+            err => crate::error::Error::Renderer(err.to_string()),
+        }
+    }
+}
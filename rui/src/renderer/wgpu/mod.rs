@@ -2,6 +2,8 @@ mod error;
 mod pipeline;
 mod primitive;
 mod renderer;
+mod texture_cache;
 
 pub use error::Error as RendererError;
 pub use renderer::Renderer;
+pub use texture_cache::DEFAULT_TEXTURE_BUDGET;
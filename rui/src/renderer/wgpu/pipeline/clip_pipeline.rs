@@ -0,0 +1,139 @@
+use crate::renderer::wgpu::pipeline::{
+    stencil_only_target, STENCIL_CLIP_BIT, STENCIL_WINDING_MASK,
+};
+use crate::renderer::MSAA;
+use std::borrow::Cow;
+use wgpu_types::{CompareFunction, StencilOperation};
+
+/// Maintains the clip bit of the stencil buffer. A clip is the intersection of the
+/// current clip with the windings of a path that the path pipeline drew into the
+/// stencil buffer.
+pub struct ClipPipeline {
+    /// Marks the whole surface as inside of the clip
+    fill_pipeline: wgpu::RenderPipeline,
+    /// Removes the fragments without windings from the clip and resets the windings
+    intersect_pipeline: wgpu::RenderPipeline,
+    reset_windings_pipeline: wgpu::RenderPipeline,
+    /// Removes the clip from the whole surface
+    clear_pipeline: wgpu::RenderPipeline,
+}
+impl ClipPipeline {
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, msaa: &MSAA) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "../../../../shader/clip.wgsl"
+            ))),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let build = |label: &str,
+                     compare: CompareFunction,
+                     pass_op: StencilOperation,
+                     fail_op: StencilOperation,
+                     read_mask: u32,
+                     write_mask: u32| {
+            let face = wgpu::StencilFaceState {
+                compare,
+                fail_op,
+                depth_fail_op: fail_op,
+                pass_op,
+            };
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(stencil_only_target(config.format))],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth24PlusStencil8,
+                    depth_write_enabled: false,
+                    depth_compare: CompareFunction::Always,
+                    stencil: wgpu::StencilState {
+                        front: face,
+                        back: face,
+                        read_mask,
+                        write_mask,
+                    },
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: (*msaa).into(),
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        };
+
+        ClipPipeline {
+            // Replaces the clip bit with the reference, which is the clip bit itself
+            fill_pipeline: build(
+                "Clip fill pipeline",
+                CompareFunction::Always,
+                StencilOperation::Replace,
+                StencilOperation::Keep,
+                0,
+                STENCIL_CLIP_BIT,
+            ),
+            // Fragments without windings equal the masked reference of zero
+            intersect_pipeline: build(
+                "Clip intersect pipeline",
+                CompareFunction::Equal,
+                StencilOperation::Zero,
+                StencilOperation::Keep,
+                STENCIL_WINDING_MASK,
+                STENCIL_CLIP_BIT,
+            ),
+            reset_windings_pipeline: build(
+                "Clip reset windings pipeline",
+                CompareFunction::Always,
+                StencilOperation::Zero,
+                StencilOperation::Keep,
+                0,
+                STENCIL_WINDING_MASK,
+            ),
+            clear_pipeline: build(
+                "Clip clear pipeline",
+                CompareFunction::Always,
+                StencilOperation::Zero,
+                StencilOperation::Keep,
+                0,
+                STENCIL_CLIP_BIT,
+            ),
+        }
+    }
+
+    /// Marks the whole surface as inside of the clip. The stencil reference has to
+    /// be [STENCIL_CLIP_BIT].
+    pub(crate) fn record_fill<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.fill_pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Intersects the clip with the windings of the stencil buffer and resets them
+    pub(crate) fn record_intersect<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.intersect_pipeline);
+        render_pass.draw(0..3, 0..1);
+        render_pass.set_pipeline(&self.reset_windings_pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    pub(crate) fn record_clear<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.clear_pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::renderer::wgpu::pipeline::{clip_test, color_target};
+use crate::renderer::MSAA;
+use std::borrow::Cow;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu_types::BufferUsages;
+
+/// Offscreen target of a group. The nodes of the group are rendered into the texture,
+/// which is composited with the opacity of the group afterwards.
+pub struct Layer {
+    pub(crate) view: wgpu::TextureView,
+    opacity_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Draws the texture of a layer over the whole surface
+pub struct CompositePipeline {
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+impl CompositePipeline {
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, msaa: &MSAA) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("layer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "../../../../shader/composite.wgsl"
+            ))),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Composite pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(color_target(config.format))],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(clip_test()),
+            multisample: wgpu::MultisampleState {
+                count: (*msaa).into(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        CompositePipeline {
+            pipeline,
+            sampler,
+            bind_group_layout,
+        }
+    }
+
+    /// Creates a surface sized layer
+    pub fn layer(
+        &self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        opacity: f32,
+    ) -> Layer {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            label: Some("Layer texture"),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let opacity_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Layer opacity buffer"),
+            contents: bytemuck::cast_slice(&[opacity, 0.0, 0.0, 0.0]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: opacity_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Layer bind group"),
+        });
+
+        Layer {
+            view,
+            opacity_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn set_opacity(&self, queue: &wgpu::Queue, layer: &Layer, opacity: f32) {
+        queue.write_buffer(
+            &layer.opacity_buffer,
+            0,
+            bytemuck::cast_slice(&[opacity, 0.0, 0.0, 0.0]),
+        );
+    }
+
+    pub fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, layer: &'a Layer) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &layer.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::Error;
+use crate::node::path::PathNode;
+use crate::renderer::wgpu::pipeline::clip_pipeline::ClipPipeline;
+use crate::renderer::wgpu::pipeline::composite_pipeline::{CompositePipeline, Layer};
+use crate::renderer::wgpu::pipeline::image_pipeline::ImagePipeline;
+use crate::renderer::wgpu::pipeline::new_path_pipeline::PathPipeline;
+use crate::renderer::wgpu::pipeline::rect_pipeline::RectPipeline;
+use crate::renderer::wgpu::pipeline::renderer_job::Keyed;
+use crate::renderer::wgpu::pipeline::shadow_pipeline::ShadowPipeline;
+use crate::renderer::wgpu::pipeline::STENCIL_CLIP_BIT;
+use crate::renderer::wgpu::primitive;
+use crate::renderer::wgpu::texture_cache::TextureCache;
+use crate::renderer::MSAA;
+use rui_util::Extent;
+use std::ops::Range;
+
+/// A batch of consecutive primitives that are drawn by the same pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Draw {
+    Rects(Range<usize>),
+    Images(Range<usize>),
+    Paths(Range<usize>),
+    Shadows(Range<usize>),
+    /// Composites the layer of the group with the index
+    Group(usize),
+    /// Restricts the following draws to the rect `[x, y, width, height]` in pixels
+    Scissor([u32; 4]),
+    /// Intersects the clip with the path of the index, until the matching `PopClip`
+    PushClip(usize),
+    PopClip,
+}
+
+/// Primitives of a flattened node tree in paint order
+#[derive(Default, Clone)]
+pub(crate) struct Primitives {
+    pub(crate) rects: Vec<primitive::Rect>,
+    pub(crate) images: Vec<primitive::Image>,
+    pub(crate) paths: Vec<(PathNode, primitive::Paint)>,
+    pub(crate) shadows: Vec<primitive::Shadow>,
+    /// Stops of the gradients of the primitives
+    pub(crate) stops: Vec<primitive::GradientStop>,
+    pub(crate) draws: Vec<Draw>,
+    /// Nodes with a key in the order they were flattened
+    pub(crate) keyed: Vec<Keyed>,
+}
+impl Primitives {
+    /// Appends the primitive at `index` to the last batch if it has the same kind,
+    /// otherwise a new batch is started
+    fn extend(&mut self, index: usize, batch: fn(Range<usize>) -> Draw) {
+        let next = batch(index..index + 1);
+        match (self.draws.last_mut(), &next) {
+            (Some(Draw::Rects(range)), Draw::Rects(_))
+            | (Some(Draw::Images(range)), Draw::Images(_))
+            | (Some(Draw::Paths(range)), Draw::Paths(_))
+            | (Some(Draw::Shadows(range)), Draw::Shadows(_)) => range.end = index + 1,
+            _ => self.draws.push(next),
+        }
+    }
+
+    pub(crate) fn push_rect(&mut self, rect: primitive::Rect) {
+        self.rects.push(rect);
+        self.extend(self.rects.len() - 1, Draw::Rects);
+    }
+
+    pub(crate) fn push_image(&mut self, image: primitive::Image) {
+        self.images.push(image);
+        self.extend(self.images.len() - 1, Draw::Images);
+    }
+
+    pub(crate) fn push_path(&mut self, path: PathNode, paint: primitive::Paint) {
+        self.paths.push((path, paint));
+        self.extend(self.paths.len() - 1, Draw::Paths);
+    }
+
+    pub(crate) fn push_shadow(&mut self, shadow: primitive::Shadow) {
+        self.shadows.push(shadow);
+        self.extend(self.shadows.len() - 1, Draw::Shadows);
+    }
+
+    pub(crate) fn push_group(&mut self, index: usize) {
+        self.draws.push(Draw::Group(index));
+    }
+
+    pub(crate) fn push_scissor(&mut self, scissor: [u32; 4]) {
+        self.draws.push(Draw::Scissor(scissor));
+    }
+
+    /// Clips the following draws to the path. The path isn't painted itself.
+    pub(crate) fn push_clip(&mut self, path: PathNode) {
+        self.paths.push((path, primitive::Paint::default()));
+        self.draws.push(Draw::PushClip(self.paths.len() - 1));
+    }
+
+    pub(crate) fn pop_clip(&mut self) {
+        self.draws.push(Draw::PopClip);
+    }
+}
+
+/// The pipelines of a layer together with the order their primitives are drawn in
+pub(crate) struct DrawList {
+    rect_pipeline: RectPipeline,
+    image_pipeline: ImagePipeline,
+    path_pipeline: PathPipeline,
+    shadow_pipeline: ShadowPipeline,
+    clip_pipeline: ClipPipeline,
+    draws: Vec<Draw>,
+    /// Size of the target, scissor rects must not exceed it
+    extent: Extent,
+}
+impl DrawList {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        msaa: &MSAA,
+    ) -> Self {
+        DrawList {
+            rect_pipeline: RectPipeline::new(device, config, msaa),
+            image_pipeline: ImagePipeline::new(device, config, msaa),
+            path_pipeline: PathPipeline::new(device, config, msaa),
+            shadow_pipeline: ShadowPipeline::new(device, config, msaa),
+            clip_pipeline: ClipPipeline::new(device, config, msaa),
+            draws: vec![],
+            extent: Extent {
+                width: config.width,
+                height: config.height,
+            },
+        }
+    }
+
+    /// Fails if an image can't be loaded, the draw list keeps its primitives then
+    pub(crate) async fn mount(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        textures: &mut TextureCache,
+        primitives: Primitives,
+    ) -> Result<(), Error> {
+        self.image_pipeline
+            .mount(device, queue, textures, &primitives.images)
+            .await?;
+        self.rect_pipeline
+            .mount(device, &primitives.rects, &primitives.stops);
+        self.path_pipeline
+            .mount(device, &primitives.paths, &primitives.stops);
+        self.shadow_pipeline.mount(device, &primitives.shadows);
+        self.draws = primitives.draws;
+        Ok(())
+    }
+
+    /// Replaces the rect with the index of the last mount
+    pub(crate) fn patch_rect(&self, queue: &wgpu::Queue, index: usize, rect: &primitive::Rect) {
+        self.rect_pipeline.patch(queue, index, rect);
+    }
+
+    /// Replaces the instance and the size of the image with the index of the last mount
+    pub(crate) fn patch_image(
+        &mut self,
+        queue: &wgpu::Queue,
+        index: usize,
+        instance: primitive::Instance,
+        size: [f32; 2],
+    ) {
+        self.image_pipeline.patch(queue, index, instance, size);
+    }
+
+    /// Replaces the paint of the path with the index of the last mount
+    pub(crate) fn patch_path(&self, queue: &wgpu::Queue, index: usize, paint: &primitive::Paint) {
+        self.path_pipeline.patch(queue, index, paint);
+    }
+
+    /// Whether animated images of the last mount show other frames later
+    pub(crate) fn is_playing(&self) -> bool {
+        self.image_pipeline.is_playing()
+    }
+
+    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, config: &wgpu::SurfaceConfiguration) {
+        self.extent = Extent {
+            width: config.width,
+            height: config.height,
+        };
+        self.rect_pipeline.resize(queue, config);
+        self.image_pipeline.resize(queue, config);
+        self.path_pipeline.resize(queue, config);
+        self.shadow_pipeline.resize(queue, config);
+    }
+
+    /// Records the batches in paint order. The layers are the ones of the groups the
+    /// draw list refers to.
+    pub(crate) fn record<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        composite_pipeline: &'a CompositePipeline,
+        layers: &[&'a Layer],
+    ) {
+        // Paths of the active clips from the outermost to the innermost one
+        let mut clips = vec![];
+        render_pass.set_stencil_reference(0);
+        for draw in &self.draws {
+            match draw {
+                Draw::Rects(range) => self.rect_pipeline.record(render_pass, range.clone()),
+                Draw::Images(range) => self.image_pipeline.record(render_pass, range.clone()),
+                Draw::Paths(range) => self.path_pipeline.record(render_pass, range.clone()),
+                Draw::Shadows(range) => self.shadow_pipeline.record(render_pass, range.clone()),
+                Draw::Group(index) => composite_pipeline.record(render_pass, layers[*index]),
+                Draw::Scissor([x, y, width, height]) => {
+                    // The draw list may have been mounted before the last resize
+                    let x = (*x).min(self.extent.width);
+                    let y = (*y).min(self.extent.height);
+                    render_pass.set_scissor_rect(
+                        x,
+                        y,
+                        (*width).min(self.extent.width - x),
+                        (*height).min(self.extent.height - y),
+                    );
+                }
+                Draw::PushClip(index) => {
+                    if clips.is_empty() {
+                        render_pass.set_stencil_reference(STENCIL_CLIP_BIT);
+                        self.clip_pipeline.record_fill(render_pass);
+                    }
+                    self.record_clip(render_pass, *index);
+                    clips.push(*index);
+                }
+                Draw::PopClip => {
+                    clips.pop();
+                    if clips.is_empty() {
+                        self.clip_pipeline.record_clear(render_pass);
+                        render_pass.set_stencil_reference(0);
+                    } else {
+                        // A single bit can't remember the outer clips, therefore they
+                        // are intersected again
+                        self.clip_pipeline.record_fill(render_pass);
+                        for index in &clips {
+                            self.record_clip(render_pass, *index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_clip<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, index: usize) {
+        self.path_pipeline
+            .record_stencil(render_pass, index..index + 1);
+        self.clip_pipeline.record_intersect(render_pass);
+    }
+}
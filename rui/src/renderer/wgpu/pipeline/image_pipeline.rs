@@ -1,27 +1,38 @@
+use crate::error::Error;
+use crate::node::image::Sampling;
+use crate::renderer::wgpu::pipeline::{clip_test, color_target};
 use crate::renderer::wgpu::primitive;
+use crate::renderer::wgpu::texture_cache::{CachedImage, Texture, TextureCache};
 use crate::renderer::MSAA;
 use crate::util;
-use crate::util::Resource;
 use std::borrow::Cow;
-use std::fs::File;
-use std::io::Read;
+use std::mem;
+use std::ops::Range;
+use std::sync::Arc;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::BindGroupLayout;
 use wgpu_types::BufferUsages;
 
-#[allow(dead_code)]
-pub struct Texture {
-    texture: wgpu::Texture,
-    texture_view: wgpu::TextureView,
-    instance_buffer: wgpu::Buffer,
-    instance_bind_group: wgpu::BindGroup,
-    texture_bind_group: wgpu::BindGroup,
-}
-
 pub struct ImagePipeline {
     pipeline: wgpu::RenderPipeline,
-    textures: Vec<Texture>,
-    sampler: wgpu::Sampler,
+    /// Images of the last mount, which keeps their textures from being evicted
+    images: Vec<Arc<CachedImage>>,
+    /// Primitives of the last mount, their uniforms are written again when they're
+    /// patched
+    sources: Vec<primitive::Image>,
+    /// Index of the texture bind group of each image. Images that share a texture,
+    /// e.g. an atlas, and their sampling share a bind group.
+    texture_indices: Vec<usize>,
+    texture_bind_groups: Vec<wgpu::BindGroup>,
+    /// Uniforms of all images, each one is bound with its offset
+    instance_buffer: Option<wgpu::Buffer>,
+    instance_bind_group: Option<wgpu::BindGroup>,
+    /// Distance between the uniforms of consecutive images in bytes
+    instance_stride: u64,
+    /// Whether animated images of the last mount show other frames later
+    playing: bool,
+    /// Samplers indexed by [Sampling]
+    samplers: [wgpu::Sampler; 3],
     globals_buffer: wgpu::Buffer,
     globals_bind_group: wgpu::BindGroup,
     texture_bind_group_layout: BindGroupLayout,
@@ -29,15 +40,22 @@ pub struct ImagePipeline {
 }
 impl ImagePipeline {
     pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, msaa: &MSAA) -> Self {
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let sampler = |filter, mipmap_filter| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_filter,
+                ..Default::default()
+            })
+        };
+        let samplers = [
+            sampler(wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest),
+            sampler(wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest),
+            sampler(wgpu::FilterMode::Linear, wgpu::FilterMode::Linear),
+        ];
 
         let globals = primitive::Globals {
             width_height: util::pack(config.width as u16, config.height as u16),
@@ -82,8 +100,10 @@ impl ImagePipeline {
                     visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(mem::size_of::<
+                            primitive::ImageInstance,
+                        >() as u64),
                     },
                     count: None,
                 }],
@@ -133,7 +153,7 @@ impl ImagePipeline {
         });
 
         let multisample = wgpu::MultisampleState {
-            count: msaa.clone().into(),
+            count: (*msaa).into(),
             mask: !0,
             alpha_to_coverage_enabled: false,
         };
@@ -149,21 +169,30 @@ impl ImagePipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(config.format.into())],
+                targets: &[Some(color_target(config.format))],
             }),
-            primitive: wgpu::PrimitiveState {
-                cull_mode: Some(wgpu::Face::Back),
-                ..Default::default()
-            },
-            depth_stencil: None,
+            // Transforms may mirror the primitives, therefore nothing is culled
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(clip_test()),
             multisample,
             multiview: None,
         });
 
+        // Offsets of uniforms have to be aligned
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let size = mem::size_of::<primitive::ImageInstance>() as u64;
+
         ImagePipeline {
             pipeline,
-            textures: vec![],
-            sampler,
+            images: vec![],
+            sources: vec![],
+            texture_indices: vec![],
+            texture_bind_groups: vec![],
+            instance_buffer: None,
+            instance_bind_group: None,
+            playing: false,
+            instance_stride: size.div_ceil(alignment) * alignment,
+            samplers,
             globals_buffer,
             globals_bind_group,
             texture_bind_group_layout,
@@ -171,25 +200,49 @@ impl ImagePipeline {
         }
     }
 
-    pub fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+    /// Records the images with the indices in `range`. Texture bind groups are only
+    /// switched between images on different textures.
+    pub fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, range: Range<usize>) {
+        let instance_bind_group = match &self.instance_bind_group {
+            Some(instance_bind_group) => instance_bind_group,
+            None => return,
+        };
         render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
 
-        for instance in &self.textures {
-            render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
-            render_pass.set_bind_group(1, &instance.instance_bind_group, &[]);
-            render_pass.set_bind_group(2, &instance.texture_bind_group, &[]);
+        let mut bound = None;
+        for index in range {
+            let offset = index as u64 * self.instance_stride;
+            render_pass.set_bind_group(1, instance_bind_group, &[offset as u32]);
+            let texture = self.texture_indices[index];
+            if bound != Some(texture) {
+                render_pass.set_bind_group(2, &self.texture_bind_groups[texture], &[]);
+                bound = Some(texture);
+            }
             render_pass.draw(0..6, 0..1);
         }
     }
 
-    async fn load(resource: &Resource) -> Vec<u8> {
-        match resource {
-            Resource::Path(path) => {
-                let mut file = File::open(path).unwrap();
-                let mut contents = vec![];
-                file.read_to_end(&mut contents).unwrap();
-                contents
-            }
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Replaces the instance and the size of the image with the index of the last
+    /// mount. The image keeps its texture and frame.
+    pub fn patch(
+        &mut self,
+        queue: &wgpu::Queue,
+        index: usize,
+        instance: primitive::Instance,
+        size: [f32; 2],
+    ) {
+        let source = &mut self.sources[index];
+        source.instance = instance;
+        source.size = size;
+        let uniform = source.uniform(&self.images[index]);
+        if let Some(buffer) = &self.instance_buffer {
+            let offset = index as u64 * self.instance_stride;
+            queue.write_buffer(buffer, offset, bytemuck::bytes_of(&uniform));
         }
     }
 
@@ -201,99 +254,96 @@ impl ImagePipeline {
         queue.write_buffer(&self.globals_buffer, 0, bytemuck::cast_slice(&[globals]));
     }
 
+    /// Gets the textures of the images from the cache, which loads the images that
+    /// aren't cached yet. Animated images show the frame at their time.
+    ///
+    /// Fails if an image can't be loaded, the images of the last mount are kept then.
     pub async fn mount(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        images: &Vec<primitive::Image>,
-    ) {
-        let mut textures = Vec::with_capacity(images.len());
-        for i in images {
-            let bytes = Self::load(&i.resource).await;
-            let image = image::load_from_memory(&bytes).unwrap();
-            let rgba = image.to_rgba8();
-            let dimensions = rgba.dimensions();
+        textures: &mut TextureCache,
+        images: &[primitive::Image],
+    ) -> Result<(), Error> {
+        let stride = self.instance_stride as usize;
+        let mut instances = vec![0; images.len() * stride];
+        let mut cached = Vec::with_capacity(images.len());
+        let mut texture_indices = Vec::with_capacity(images.len());
+        let mut bound: Vec<(*const Texture, Sampling)> = vec![];
+        let mut texture_bind_groups = vec![];
+        let mut playing = false;
+        for (index, i) in images.iter().enumerate() {
+            let mipmapped = i.sampling == Sampling::Mipmapped;
+            let (image, animated) = textures
+                .get(device, queue, &i.resource, mipmapped, i.time)
+                .await?;
+            playing |= animated && i.playing;
+            let instance = i.uniform(&image);
+            instances[index * stride..][..mem::size_of_val(&instance)]
+                .copy_from_slice(bytemuck::bytes_of(&instance));
 
-            let instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: Some("instance_buffer"),
-                contents: bytemuck::cast_slice(&[i.instance]),
-                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            });
-
-            let texture_size = wgpu::Extent3d {
-                width: dimensions.0,
-                height: dimensions.1,
-                depth_or_array_layers: 1,
+            let key = (Arc::as_ptr(&image.texture), i.sampling);
+            let texture_index = match bound.iter().position(|bound| *bound == key) {
+                Some(texture_index) => texture_index,
+                None => {
+                    texture_bind_groups.push(device.create_bind_group(
+                        &wgpu::BindGroupDescriptor {
+                            layout: &self.texture_bind_group_layout,
+                            entries: &[
+                                wgpu::BindGroupEntry {
+                                    binding: 0,
+                                    resource: wgpu::BindingResource::TextureView(
+                                        &image.texture.view,
+                                    ),
+                                },
+                                wgpu::BindGroupEntry {
+                                    binding: 1,
+                                    resource: wgpu::BindingResource::Sampler(
+                                        &self.samplers[i.sampling as usize],
+                                    ),
+                                },
+                            ],
+                            label: Some("texture_bind_group"),
+                        },
+                    ));
+                    bound.push(key);
+                    bound.len() - 1
+                }
             };
-            let texture = device.create_texture(&wgpu::TextureDescriptor {
-                // All textures are stored as 3D, we represent our 2D texture
-                // by setting depth to 1.
-                size: texture_size,
-                mip_level_count: 1, // We'll talk about this a little later
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                // Most images are stored using sRGB so we need to reflect that here.
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                // TEXTURE_BINDING tells wgpu that we want to use this texture in shaders
-                // COPY_DST means that we want to copy data to this texture
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                label: Some("texture"),
-            });
-            queue.write_texture(
-                // Tells wgpu where to copy the pixel data
-                wgpu::ImageCopyTexture {
-                    texture: &texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                // The actual pixel data
-                rgba.as_raw(),
-                // The layout of the texture
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: std::num::NonZeroU32::new(4 * dimensions.0),
-                    rows_per_image: std::num::NonZeroU32::new(dimensions.1),
-                },
-                texture_size,
-            );
-
-            // We don't need to configure the texture view much, so let's
-            // let wgpu define it.
-            let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-            let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &self.texture_bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&self.sampler),
-                    },
-                ],
-                label: Some("diffuse_bind_group"),
-            });
+            texture_indices.push(texture_index);
+            cached.push(image);
+        }
 
-            let instance_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        self.instance_buffer = match images.is_empty() {
+            true => None,
+            false => Some(device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("instance_buffer"),
+                contents: &instances,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            })),
+        };
+        self.instance_bind_group = match &self.instance_buffer {
+            None => None,
+            Some(instance_buffer) => Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &self.instance_bind_group_layout,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: instance_buffer.as_entire_binding(),
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: instance_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(
+                            mem::size_of::<primitive::ImageInstance>() as u64
+                        ),
+                    }),
                 }],
-                label: Some("camera_bind_group"),
-            });
-
-            textures.push(Texture {
-                texture,
-                texture_view,
-                instance_buffer,
-                instance_bind_group,
-                texture_bind_group,
-            });
-        }
-        self.textures = textures;
+                label: Some("instance_bind_group"),
+            })),
+        };
+        self.images = cached;
+        self.sources = images.to_vec();
+        self.texture_indices = texture_indices;
+        self.texture_bind_groups = texture_bind_groups;
+        self.playing = playing;
+        Ok(())
     }
 }
@@ -1,5 +1,103 @@
+pub(crate) mod blur_pipeline;
+pub(crate) mod clip_pipeline;
+pub(crate) mod composite_pipeline;
+pub(crate) mod draw_list;
 pub(crate) mod image_pipeline;
 mod new_path_pipeline;
 pub(crate) mod path_pipeline;
 pub(crate) mod rect_pipeline;
 pub mod renderer_job;
+pub(crate) mod shadow_pipeline;
+
+use crate::renderer::wgpu::primitive;
+use bytemuck::Zeroable;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+/// Color target with premultiplied alpha blending. All pipelines output colors
+/// that are premultiplied with their alpha.
+pub(crate) fn color_target(format: wgpu::TextureFormat) -> wgpu::ColorTargetState {
+    wgpu::ColorTargetState {
+        format,
+        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+        write_mask: wgpu::ColorWrites::ALL,
+    }
+}
+
+/// Color target for pipelines that only write to the stencil buffer, so they can
+/// be used in the same render pass as the other pipelines
+pub(crate) fn stencil_only_target(format: wgpu::TextureFormat) -> wgpu::ColorTargetState {
+    wgpu::ColorTargetState {
+        format,
+        blend: None,
+        write_mask: wgpu::ColorWrites::empty(),
+    }
+}
+
+/// The lower bits of the stencil buffer count the windings of paths
+pub(crate) const STENCIL_WINDING_MASK: u32 = 0x7F;
+/// The highest bit of the stencil buffer marks the fragments inside the current clip
+pub(crate) const STENCIL_CLIP_BIT: u32 = 0x80;
+
+/// Depth stencil state of pipelines that only draw inside the current clip. While a
+/// clip is active the stencil reference is [STENCIL_CLIP_BIT], otherwise it is zero
+/// and the test always passes.
+pub(crate) fn clip_test() -> wgpu::DepthStencilState {
+    let face = wgpu::StencilFaceState {
+        compare: wgpu::CompareFunction::LessEqual,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: wgpu::StencilOperation::Keep,
+    };
+    wgpu::DepthStencilState {
+        format: wgpu::TextureFormat::Depth24PlusStencil8,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: STENCIL_CLIP_BIT,
+            write_mask: 0,
+        },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+/// Layout of the storage buffer with the stops of the gradients, see `shader/paint.wgsl`
+pub(crate) fn stops_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("stops_bind_group_layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+    })
+}
+
+pub(crate) fn stops_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    stops: &[primitive::GradientStop],
+) -> wgpu::BindGroup {
+    // Bindings can't be empty
+    let empty = [primitive::GradientStop::zeroed()];
+    let stops = if stops.is_empty() { &empty[..] } else { stops };
+    let buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Stops storage Buffer"),
+        contents: bytemuck::cast_slice(stops),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+        label: Some("stops_bind_group"),
+    })
+}
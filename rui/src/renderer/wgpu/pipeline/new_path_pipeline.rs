@@ -1,25 +1,29 @@
-use crate::node::path::PathNode;
-use crate::renderer::wgpu::pipeline::new_path_pipeline::vertex::{
-    ColorVertex, FanVertex, SegmentVertex,
+use crate::node::path::{FillRule, PathNode};
+use crate::renderer::wgpu::pipeline::new_path_pipeline::vertex::{FanVertex, SegmentVertex};
+use crate::renderer::wgpu::pipeline::{
+    color_target, stencil_only_target, stops_bind_group, stops_bind_group_layout, STENCIL_CLIP_BIT,
+    STENCIL_WINDING_MASK,
 };
 use crate::renderer::wgpu::primitive;
 use crate::renderer::MSAA;
 use crate::util;
-use crate::util::PathSegment;
+use crate::util::{PathSegment, Point2D};
 use alloc::borrow::Cow;
+use std::mem;
+use std::ops::Range;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu_types::{
-    BlendComponent, BlendFactor, BlendOperation, BlendState, BufferUsages, ColorTargetState,
-    ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Face, FrontFace, IndexFormat,
-    PolygonMode, PrimitiveTopology, StencilFaceState, StencilOperation, StencilState,
-    TextureFormat,
+    BlendComponent, BlendFactor, BlendOperation, BlendState, BufferUsages, CompareFunction,
+    DepthBiasState, DepthStencilState, Face, FrontFace, IndexFormat, PolygonMode,
+    PrimitiveTopology, StencilFaceState, StencilOperation, StencilState, TextureFormat,
 };
 
+/// Maximum number of paths in a batch, which limits the cost of the overlap tests
+const MAX_BATCH: usize = 256;
+
 mod vertex {
     use std::mem;
 
-    pub type ColorVertex = FanVertex;
-
     /// Represents a Vertex for the fan pipeline
     #[repr(C)]
     #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -68,18 +72,23 @@ mod vertex {
 struct Mount {
     fan_vertex_buffer: wgpu::Buffer,
     fan_index_buffer: wgpu::Buffer,
-    fan_index_count: u32,
 
     segment_vertex_buffer: wgpu::Buffer,
     segment_index_buffer: wgpu::Buffer,
-    segment_index_count: u32,
 
     segments_buffer: wgpu::Buffer,
     segments_buffer_bind_group: wgpu::BindGroup,
 
-    color_vertex_buffer: wgpu::Buffer,
-    color_index_buffer: wgpu::Buffer,
-    color_index_count: u32,
+    /// Bounds and paints of the paths
+    instance_buffer: wgpu::Buffer,
+    stops_bind_group: wgpu::BindGroup,
+
+    /// Range of the fan and segment indices of each path
+    fan_ranges: Vec<Range<u32>>,
+    segment_ranges: Vec<Range<u32>>,
+    fill_rules: Vec<FillRule>,
+    /// End of the batch each path belongs to, see [PathPipeline::batch_ends]
+    batch_ends: Vec<usize>,
 }
 
 pub struct PathPipeline {
@@ -89,18 +98,19 @@ pub struct PathPipeline {
     globals_bind_group_layout: wgpu::BindGroupLayout,
 
     /// Pipeline for rendering triangle fans
-    /// to the stencil buffer
-    fans_pipeline: wgpu::RenderPipeline,
+    /// to the stencil buffer, one for each fill rule
+    fans_pipeline: [wgpu::RenderPipeline; 2],
 
     /// Pipeline for rendering curve-segments
-    /// to the stencil buffer
-    segments_convex_pipeline: wgpu::RenderPipeline,
-    segments_concave_pipeline: wgpu::RenderPipeline,
+    /// to the stencil buffer, one for each fill rule
+    segments_convex_pipeline: [wgpu::RenderPipeline; 2],
+    segments_concave_pipeline: [wgpu::RenderPipeline; 2],
     segments_buffer_bind_group_layout: wgpu::BindGroupLayout,
 
     /// Final pipeline which uses the stencil buffer to draw
     /// vector graphics
     color_pipeline: wgpu::RenderPipeline,
+    stops_bind_group_layout: wgpu::BindGroupLayout,
 
     /// Mount of the path pipeline
     mount: Option<Mount>,
@@ -109,25 +119,40 @@ impl PathPipeline {
     pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, msaa: &MSAA) -> Self {
         let (globals_buffer, globals_bind_group_layout, globals_bind_group) =
             Self::build_globals_buffer(device, config);
-        let fans_pipeline =
-            Self::build_fans_pipeline(device, config, msaa, &globals_bind_group_layout);
+        let fill_rules = [FillRule::NonZero, FillRule::EvenOdd];
+        let fans_pipeline = fill_rules.map(|fill_rule| {
+            Self::build_fans_pipeline(device, config, msaa, &globals_bind_group_layout, fill_rule)
+        });
         let segments_buffer_bind_group_layout =
             Self::build_segments_buffer_bind_group_layout(device);
-        let segments_concave_pipeline = Self::build_segments_concave_pipeline(
-            device,
-            config,
-            msaa,
-            &segments_buffer_bind_group_layout,
-            &globals_bind_group_layout,
-        );
-        let segments_convex_pipeline = Self::build_segments_convex_pipeline(
+        let segments_concave_pipeline = fill_rules.map(|fill_rule| {
+            Self::build_segments_concave_pipeline(
+                device,
+                config,
+                msaa,
+                &segments_buffer_bind_group_layout,
+                &globals_bind_group_layout,
+                fill_rule,
+            )
+        });
+        let segments_convex_pipeline = fill_rules.map(|fill_rule| {
+            Self::build_segments_convex_pipeline(
+                device,
+                config,
+                msaa,
+                &segments_buffer_bind_group_layout,
+                &globals_bind_group_layout,
+                fill_rule,
+            )
+        });
+        let stops_bind_group_layout = stops_bind_group_layout(device);
+        let color_pipeline = Self::build_color_pipeline(
             device,
             config,
             msaa,
-            &segments_buffer_bind_group_layout,
             &globals_bind_group_layout,
+            &stops_bind_group_layout,
         );
-        let color_pipeline = Self::build_color_pipeline(device, config, msaa);
 
         PathPipeline {
             globals_buffer,
@@ -138,6 +163,7 @@ impl PathPipeline {
             segments_concave_pipeline,
             segments_buffer_bind_group_layout,
             color_pipeline,
+            stops_bind_group_layout,
             mount: None,
         }
     }
@@ -191,6 +217,8 @@ impl PathPipeline {
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
         msaa: &MSAA,
+        globals_bind_group_layout: &wgpu::BindGroupLayout,
+        stops_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> wgpu::RenderPipeline {
         let multisample = wgpu::MultisampleState {
             count: msaa.clone().into(),
@@ -200,33 +228,30 @@ impl PathPipeline {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                "../../../../shader/path/color.wgsl"
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                include_str!("../../../../shader/paint.wgsl"),
+                include_str!("../../../../shader/path/color.wgsl")
             ))),
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[],
+            bind_group_layouts: &[globals_bind_group_layout, stops_bind_group_layout],
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
-                buffers: &[ColorVertex::DESCRIPTION],
+                buffers: &[primitive::PathInstance::desc()],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(ColorTargetState {
-                    format: config.format,
-                    blend: None,
-                    write_mask: ColorWrites::default(),
-                })],
+                targets: &[Some(color_target(config.format))],
             }),
             primitive: wgpu::PrimitiveState {
                 topology: PrimitiveTopology::TriangleList,
@@ -243,12 +268,12 @@ impl PathPipeline {
                 depth_compare: CompareFunction::Always,
                 stencil: StencilState {
                     front: StencilFaceState {
-                        // Compare function is not equal.
-                        // later in the pass we define the value to
-                        // compare
-                        compare: CompareFunction::NotEqual,
-                        // we don't care about the stencil buffer after the color
-                        // pass because it is cleared after the pass
+                        // The reference is the clip bit while a clip is active and
+                        // zero otherwise. Fragments pass if they have windings and
+                        // are inside the clip, if there is one.
+                        compare: CompareFunction::Less,
+                        // The stencil buffer is reset while covering, so the
+                        // next path starts with cleared windings
                         fail_op: StencilOperation::Zero,
                         depth_fail_op: StencilOperation::Zero,
                         pass_op: StencilOperation::Zero,
@@ -256,16 +281,14 @@ impl PathPipeline {
                     // For the color pass we have backface culling enabled.
                     // Therefore we don't need a stencil test for the back of a face
                     back: StencilFaceState::IGNORE,
-                    read_mask: !0,
-                    write_mask: !0,
+                    read_mask: STENCIL_WINDING_MASK | STENCIL_CLIP_BIT,
+                    write_mask: STENCIL_WINDING_MASK,
                 },
                 bias: DepthBiasState::default(),
             }),
             multiview: None,
             multisample,
-        });
-
-        pipeline
+        })
     }
 
     fn build_fans_pipeline(
@@ -273,6 +296,7 @@ impl PathPipeline {
         config: &wgpu::SurfaceConfiguration,
         msaa: &MSAA,
         globals_bind_group_layout: &wgpu::BindGroupLayout,
+        fill_rule: FillRule,
     ) -> wgpu::RenderPipeline {
         let multisample = wgpu::MultisampleState {
             count: msaa.clone().into(),
@@ -293,7 +317,7 @@ impl PathPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
@@ -304,7 +328,7 @@ impl PathPipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[],
+                targets: &[Some(stencil_only_target(config.format))],
             }),
             primitive: wgpu::PrimitiveState {
                 topology: PrimitiveTopology::TriangleList,
@@ -319,34 +343,52 @@ impl PathPipeline {
                 format: TextureFormat::Depth24PlusStencil8,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::Always,
-                stencil: StencilState {
-                    front: StencilFaceState {
-                        compare: CompareFunction::Always,
-                        fail_op: StencilOperation::Keep,
-                        // See Kokojima resolution independent rendering paper.
-                        // We simply invert the stencil value for each front and back face
-                        depth_fail_op: StencilOperation::IncrementWrap,
-                        pass_op: StencilOperation::IncrementWrap,
+                stencil: match fill_rule {
+                    FillRule::NonZero => StencilState {
+                        front: StencilFaceState {
+                            compare: CompareFunction::Always,
+                            fail_op: StencilOperation::Keep,
+                            // See Kokojima resolution independent rendering paper.
+                            // We simply invert the stencil value for each front and back face
+                            depth_fail_op: StencilOperation::IncrementWrap,
+                            pass_op: StencilOperation::IncrementWrap,
+                        },
+                        back: StencilFaceState {
+                            compare: CompareFunction::Always,
+                            fail_op: StencilOperation::Keep,
+                            // For each back face we decrement the the stencil buffer at this
+                            // fragment. Later in the color pass we just use fragments that have
+                            // a stencil value other than zero
+                            depth_fail_op: StencilOperation::DecrementWrap,
+                            pass_op: StencilOperation::DecrementWrap,
+                        },
+                        read_mask: STENCIL_WINDING_MASK,
+                        write_mask: STENCIL_WINDING_MASK,
                     },
-                    back: StencilFaceState {
-                        compare: CompareFunction::Always,
-                        fail_op: StencilOperation::Keep,
-                        // For each back face we decrement the the stencil buffer at this
-                        // fragment. Later in the color pass we just use fragments that have
-                        // a stencil value other than zero
-                        depth_fail_op: StencilOperation::DecrementWrap,
-                        pass_op: StencilOperation::DecrementWrap,
-                    },
-                    read_mask: !0,
-                    write_mask: !0,
+                    FillRule::EvenOdd => Self::invert_stencil(),
                 },
                 bias: DepthBiasState::default(),
             }),
             multiview: None,
             multisample,
-        });
+        })
+    }
 
-        pipeline
+    /// Inverts the windings for every face, so that they are non zero where the
+    /// fragment is covered an odd number of times
+    fn invert_stencil() -> StencilState {
+        let face = StencilFaceState {
+            compare: CompareFunction::Always,
+            fail_op: StencilOperation::Keep,
+            depth_fail_op: StencilOperation::Invert,
+            pass_op: StencilOperation::Invert,
+        };
+        StencilState {
+            front: face,
+            back: face,
+            read_mask: STENCIL_WINDING_MASK,
+            write_mask: STENCIL_WINDING_MASK,
+        }
     }
 
     fn build_segments_buffer_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
@@ -371,6 +413,7 @@ impl PathPipeline {
         msaa: &MSAA,
         segments_bind_group_layout: &wgpu::BindGroupLayout,
         globals_bind_group_layout: &wgpu::BindGroupLayout,
+        fill_rule: FillRule,
     ) -> wgpu::RenderPipeline {
         let multisample = wgpu::MultisampleState {
             count: msaa.clone().into(),
@@ -391,7 +434,7 @@ impl PathPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
@@ -402,7 +445,7 @@ impl PathPipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[],
+                targets: &[Some(stencil_only_target(config.format))],
             }),
             primitive: wgpu::PrimitiveState {
                 topology: PrimitiveTopology::TriangleList,
@@ -417,33 +460,35 @@ impl PathPipeline {
                 format: TextureFormat::Depth24PlusStencil8,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::Always,
-                stencil: StencilState {
-                    front: StencilFaceState {
-                        // We check if the current value is equal zero
-                        compare: CompareFunction::Always,
-                        // Else we clear the fragment
-                        fail_op: StencilOperation::Zero,
-                        depth_fail_op: StencilOperation::IncrementWrap,
-                        // When the value is zero we increment the value
-                        pass_op: StencilOperation::IncrementWrap,
+                stencil: match fill_rule {
+                    FillRule::NonZero => StencilState {
+                        front: StencilFaceState {
+                            // We check if the current value is equal zero
+                            compare: CompareFunction::Always,
+                            // Else we clear the fragment
+                            fail_op: StencilOperation::Zero,
+                            depth_fail_op: StencilOperation::IncrementWrap,
+                            // When the value is zero we increment the value
+                            pass_op: StencilOperation::IncrementWrap,
+                        },
+                        // We do the same for the back face
+                        back: StencilFaceState {
+                            compare: CompareFunction::Always,
+                            fail_op: StencilOperation::Zero,
+                            depth_fail_op: StencilOperation::IncrementWrap,
+                            pass_op: StencilOperation::IncrementWrap,
+                        },
+                        read_mask: STENCIL_WINDING_MASK,
+                        write_mask: STENCIL_WINDING_MASK,
                     },
-                    // We do the same for the back face
-                    back: StencilFaceState {
-                        compare: CompareFunction::Always,
-                        fail_op: StencilOperation::Zero,
-                        depth_fail_op: StencilOperation::IncrementWrap,
-                        pass_op: StencilOperation::IncrementWrap,
-                    },
-                    read_mask: !0,
-                    write_mask: !0,
+                    // The regions between the curves and their chords toggle the parity
+                    FillRule::EvenOdd => Self::invert_stencil(),
                 },
                 bias: DepthBiasState::default(),
             }),
             multiview: None,
             multisample,
-        });
-
-        pipeline
+        })
     }
 
     fn build_segments_concave_pipeline(
@@ -452,6 +497,7 @@ impl PathPipeline {
         msaa: &MSAA,
         segments_bind_group_layout: &wgpu::BindGroupLayout,
         globals_bind_group_layout: &wgpu::BindGroupLayout,
+        fill_rule: FillRule,
     ) -> wgpu::RenderPipeline {
         let multisample = wgpu::MultisampleState {
             count: msaa.clone().into(),
@@ -472,7 +518,7 @@ impl PathPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
@@ -483,7 +529,7 @@ impl PathPipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[],
+                targets: &[Some(stencil_only_target(config.format))],
             }),
             primitive: wgpu::PrimitiveState {
                 topology: PrimitiveTopology::TriangleList,
@@ -498,89 +544,84 @@ impl PathPipeline {
                 format: TextureFormat::Depth24PlusStencil8,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::Always,
-                stencil: StencilState {
-                    front: StencilFaceState {
-                        // We check if the current value is equal zero
-                        compare: CompareFunction::Greater,
-                        // Else we clear the fragment
-                        fail_op: StencilOperation::Zero,
-                        depth_fail_op: StencilOperation::DecrementWrap,
-                        // When the value is zero we increment the value
-                        pass_op: StencilOperation::DecrementWrap,
-                    },
-                    // We do the same for the back face
-                    back: StencilFaceState {
-                        compare: CompareFunction::Greater,
-                        fail_op: StencilOperation::Zero,
-                        depth_fail_op: StencilOperation::DecrementWrap,
-                        pass_op: StencilOperation::DecrementWrap,
+                stencil: match fill_rule {
+                    FillRule::NonZero => StencilState {
+                        front: StencilFaceState {
+                            // We check if the current value is equal zero
+                            compare: CompareFunction::Greater,
+                            // Else we clear the fragment
+                            fail_op: StencilOperation::Zero,
+                            depth_fail_op: StencilOperation::DecrementWrap,
+                            // When the value is zero we increment the value
+                            pass_op: StencilOperation::DecrementWrap,
+                        },
+                        // We do the same for the back face
+                        back: StencilFaceState {
+                            compare: CompareFunction::Greater,
+                            fail_op: StencilOperation::Zero,
+                            depth_fail_op: StencilOperation::DecrementWrap,
+                            pass_op: StencilOperation::DecrementWrap,
+                        },
+                        read_mask: STENCIL_WINDING_MASK,
+                        write_mask: STENCIL_WINDING_MASK,
                     },
-                    read_mask: !0,
-                    write_mask: !0,
+                    // The regions between the curves and their chords toggle the parity
+                    FillRule::EvenOdd => Self::invert_stencil(),
                 },
                 bias: DepthBiasState::default(),
             }),
             multiview: None,
             multisample,
-        });
-
-        pipeline
+        })
     }
 
     fn build_triangle_fan_and_curve_triangles(
         path: &PathNode,
         fan_vertices: &mut Vec<vertex::FanVertex>,
-        fan_indices: &mut Vec<u16>,
+        fan_indices: &mut Vec<u32>,
         segment_vertices: &mut Vec<vertex::SegmentVertex>,
-        segment_indices: &mut Vec<u16>,
+        segment_indices: &mut Vec<u32>,
         segments: &mut Vec<primitive::PathSegment1>,
     ) {
-        let n = path.segments.len();
-        // Choose a pivot point for triangle fan
+        // Choose a pivot point for triangle fan. The fan of every subpath shares it.
         let pivot = path.from;
         let pivot_index = fan_vertices.len();
         fan_vertices.push(vertex::FanVertex { pos: pivot });
 
-        // Start point of a segment
+        let mut add_triangle = |from: Point2D, to: Point2D| {
+            // Triangles at the pivot have no area
+            if from == pivot || to == pivot || from == to {
+                return;
+            }
+            let index = fan_vertices.len();
+            fan_indices.push(pivot_index as u32);
+            fan_indices.push(index as u32);
+            fan_indices.push((index + 1) as u32);
+            fan_vertices.push(vertex::FanVertex { pos: from });
+            fan_vertices.push(vertex::FanVertex { pos: to });
+        };
+
+        // Start point of the subpath and of a segment
+        let mut start = pivot;
         let mut p0 = pivot;
-        for i in 0..n {
+        for segment in &path.segments {
             let segment_index = segments.len() as u32;
-            match &path.segments[i] {
-                PathSegment::Linear { .. } => {}
-                PathSegment::Arc { .. } => {}
-                PathSegment::QuadraticBezier { to, param } => {
-                    let index = segment_vertices.len();
-                    segment_indices.push(index as u16);
-                    segment_indices.push((index + 1) as u16);
-                    segment_indices.push((index + 2) as u16);
-                    segment_vertices.push(vertex::SegmentVertex {
-                        pos: p0,
-                        segment_index,
-                    });
-                    segment_vertices.push(vertex::SegmentVertex {
-                        pos: *param,
-                        segment_index,
-                    });
-                    segment_vertices.push(vertex::SegmentVertex {
-                        pos: *to,
-                        segment_index,
-                    });
-                    /*
-                    segments.push(primitive::PathSegment1 {
-                        param0: p0,
-                        param1: *param,
-                        param2: *to,
-                        param3: [0.0, 0.0],
-                    });*/
+            match segment {
+                PathSegment::Move { to } => {
+                    // Subpaths are closed implicitly
+                    add_triangle(p0, start);
+                    start = *to;
+                    p0 = *to;
+                    continue;
                 }
                 PathSegment::CubicBezier { to, params } => {
                     let index = segment_vertices.len();
-                    segment_indices.push(index as u16);
-                    segment_indices.push((index + 1) as u16);
-                    segment_indices.push((index + 2) as u16);
-                    segment_indices.push((index + 1) as u16);
-                    segment_indices.push((index + 2) as u16);
-                    segment_indices.push((index + 3) as u16);
+                    segment_indices.push(index as u32);
+                    segment_indices.push((index + 1) as u32);
+                    segment_indices.push((index + 2) as u32);
+                    segment_indices.push((index + 1) as u32);
+                    segment_indices.push((index + 2) as u32);
+                    segment_indices.push((index + 3) as u32);
                     segment_vertices.push(vertex::SegmentVertex {
                         pos: p0,
                         segment_index,
@@ -605,72 +646,157 @@ impl PathPipeline {
                         param3: *to,
                     });
                 }
-                PathSegment::CatmullRom => {}
-            }
-            let to0 = path.segments[i].to();
-            if i < n - 1 {
-                // Get next control point and the next of the next
-                let to1 = path.segments[i + 1].to();
-
-                // Add the indices for the new triangle
-                let index = fan_vertices.len();
-                fan_indices.push(pivot_index as u16);
-                fan_indices.push(index as u16);
-                fan_indices.push((index + 1) as u16);
-
-                // Add the vertices of new triangle to the vertices
-                fan_vertices.push(vertex::FanVertex { pos: *to0 });
-                fan_vertices.push(vertex::FanVertex { pos: *to1 });
+                // Lines only add to the triangle fan
+                _ => {}
             }
+            let to = *segment.to();
+            add_triangle(p0, to);
 
             // Change p0 to the next control point
-            p0 = *to0;
+            p0 = to;
         }
+        add_triangle(p0, start);
     }
 
-    pub(crate) fn record_stencil_pass<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if let Some(mount) = &self.mount {
-            // Draw the triangle fans into the stencil buffer
-            render_pass.set_pipeline(&self.fans_pipeline);
-            render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
-            render_pass.set_index_buffer(mount.fan_index_buffer.slice(..), IndexFormat::Uint16);
-            render_pass.set_vertex_buffer(0, mount.fan_vertex_buffer.slice(..));
-            render_pass.draw_indexed(0..mount.fan_index_count, 0, 0..1);
-
-            // Change the pipeline so that the curve segments are drawn into the stencil buffer
-            render_pass.set_pipeline(&self.segments_convex_pipeline);
-            // We need to compare the current value with zero.
-            render_pass.set_stencil_reference(0);
-            // if the stencil buffer fragment is zero then we draw it.
-            // Else we delete the current fragment
+    /// Draws the windings of the paths with the indices in `range` into the lower
+    /// bits of the stencil buffer
+    pub(crate) fn record_stencil<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        range: Range<usize>,
+    ) {
+        let mount = match &self.mount {
+            Some(mount) if !range.is_empty() => mount,
+            _ => return,
+        };
+        // Consecutive paths with the same fill rule are drawn at once
+        let mut start = range.start;
+        while start < range.end {
+            let fill_rule = mount.fill_rules[start];
+            let end = (start..range.end)
+                .find(|index| mount.fill_rules[*index] != fill_rule)
+                .unwrap_or(range.end);
+            self.record_windings(render_pass, mount, start..end, fill_rule as usize);
+            start = end;
+        }
+    }
+
+    fn record_windings<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        mount: &'a Mount,
+        range: Range<usize>,
+        fill_rule: usize,
+    ) {
+        let fans = mount.fan_ranges[range.start].start..mount.fan_ranges[range.end - 1].end;
+        let segments =
+            mount.segment_ranges[range.start].start..mount.segment_ranges[range.end - 1].end;
+
+        // Draw the triangle fans into the stencil buffer
+        render_pass.set_pipeline(&self.fans_pipeline[fill_rule]);
+        render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
+        render_pass.set_index_buffer(mount.fan_index_buffer.slice(..), IndexFormat::Uint32);
+        render_pass.set_vertex_buffer(0, mount.fan_vertex_buffer.slice(..));
+        render_pass.draw_indexed(fans, 0, 0..1);
+
+        if !segments.is_empty() {
+            // Change the pipeline so that the curve segments are drawn into the stencil buffer.
+            // The masked stencil reference is zero, regardless of the clip.
+            render_pass.set_pipeline(&self.segments_convex_pipeline[fill_rule]);
             render_pass.set_bind_group(0, &mount.segments_buffer_bind_group, &[]);
             render_pass.set_bind_group(1, &self.globals_bind_group, &[]);
-            render_pass.set_index_buffer(mount.segment_index_buffer.slice(..), IndexFormat::Uint16);
+            render_pass.set_index_buffer(mount.segment_index_buffer.slice(..), IndexFormat::Uint32);
             render_pass.set_vertex_buffer(0, mount.segment_vertex_buffer.slice(..));
-            render_pass.draw_indexed(0..mount.segment_index_count, 0, 0..1);
+            render_pass.draw_indexed(segments.clone(), 0, 0..1);
 
-            render_pass.set_pipeline(&self.segments_concave_pipeline);
-            render_pass.set_stencil_reference(0);
-            render_pass.draw_indexed(0..mount.segment_index_count, 0, 0..1);
+            render_pass.set_pipeline(&self.segments_concave_pipeline[fill_rule]);
+            render_pass.draw_indexed(segments, 0, 0..1);
         }
     }
 
-    pub(crate) fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if let Some(mount) = &self.mount {
+    /// Records the paths with the indices in `range`. Each batch of paths is first
+    /// drawn into the stencil buffer, the color pass then covers the stencilled
+    /// fragments in the bounds of each path with its paint and resets the windings for
+    /// the next batch.
+    ///
+    /// The stencil reference has to be set to the clip of the draw list.
+    pub(crate) fn record<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        range: Range<usize>,
+    ) {
+        let mount = match &self.mount {
+            Some(mount) => mount,
+            None => return,
+        };
+        let mut start = range.start;
+        while start < range.end {
+            let end = mount.batch_ends[start].min(range.end);
+            self.record_stencil(render_pass, start..end);
+
             render_pass.set_pipeline(&self.color_pipeline);
-            render_pass.set_index_buffer(mount.color_index_buffer.slice(..), IndexFormat::Uint16);
-            render_pass.set_vertex_buffer(0, mount.color_vertex_buffer.slice(..));
-            // Stencil reference is important in this pass
-            // We throw away everything from the quad that is zero
-            // or in other words we keep everything that is non zero
-            render_pass.set_stencil_reference(0);
-
-            // Draw the fan triangles
-            render_pass.draw_indexed(0..mount.color_index_count, 0, 0..1);
+            render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
+            render_pass.set_bind_group(1, &mount.stops_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mount.instance_buffer.slice(..));
+            render_pass.draw(0..6, start as u32..end as u32);
+            start = end;
         }
     }
 
-    pub(crate) fn mount(&mut self, device: &wgpu::Device, paths: &Vec<PathNode>) {
+    /// Bounds of the control points, the curves of a path lie inside of them
+    fn bounds(path: &PathNode) -> [f32; 4] {
+        let mut bounds = [path.from[0], path.from[1], path.from[0], path.from[1]];
+        let mut extend = |point: &[f32; 2]| {
+            bounds[0] = bounds[0].min(point[0]);
+            bounds[1] = bounds[1].min(point[1]);
+            bounds[2] = bounds[2].max(point[0]);
+            bounds[3] = bounds[3].max(point[1]);
+        };
+        for segment in &path.segments {
+            match segment {
+                PathSegment::CubicBezier { to, params } => {
+                    extend(&params[0]);
+                    extend(&params[1]);
+                    extend(to);
+                }
+                segment => extend(segment.to()),
+            }
+        }
+        bounds
+    }
+
+    /// Splits the paths into batches of consecutive paths with bounds that don't
+    /// overlap. The paths of a batch are stencilled and covered at once, because the
+    /// windings of one path never reach into the bounds of another one. Returns the
+    /// end of the batch of each path.
+    fn batch_ends(bounds: &[[f32; 4]]) -> Vec<usize> {
+        let overlap = |a: &[f32; 4], b: &[f32; 4]| {
+            a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+        };
+        let mut ends = vec![0; bounds.len()];
+        let mut start = 0;
+        while start < bounds.len() {
+            let mut end = start + 1;
+            while end < bounds.len()
+                && end - start < MAX_BATCH
+                && !bounds[start..end]
+                    .iter()
+                    .any(|other| overlap(other, &bounds[end]))
+            {
+                end += 1;
+            }
+            ends[start..end].fill(end);
+            start = end;
+        }
+        ends
+    }
+
+    pub(crate) fn mount(
+        &mut self,
+        device: &wgpu::Device,
+        paths: &[(PathNode, primitive::Paint)],
+        stops: &[primitive::GradientStop],
+    ) {
         // Build the new data for vertex, index and storage buffers
         // required to render path segments on
         let mut fan_vertices = vec![];
@@ -678,9 +804,15 @@ impl PathPipeline {
         let mut segment_vertices = vec![];
         let mut segment_indices = vec![];
         let mut segments = vec![];
-
-        // Render the paths that are provided
-        for path in paths {
+        let mut fan_ranges = Vec::with_capacity(paths.len());
+        let mut segment_ranges = Vec::with_capacity(paths.len());
+
+        // Render the paths that are provided. The pipeline only draws lines and cubic
+        // bezier curves, all other segments are converted into them.
+        let normalized: Vec<PathNode> = paths.iter().map(|(path, _)| path.normalized()).collect();
+        for path in &normalized {
+            let fan_start = fan_indices.len() as u32;
+            let segment_start = segment_indices.len() as u32;
             Self::build_triangle_fan_and_curve_triangles(
                 path,
                 &mut fan_vertices,
@@ -689,6 +821,8 @@ impl PathPipeline {
                 &mut segment_indices,
                 &mut segments,
             );
+            fan_ranges.push(fan_start..fan_indices.len() as u32);
+            segment_ranges.push(segment_start..segment_indices.len() as u32);
         }
 
         // Create the necessary buffers
@@ -725,44 +859,60 @@ impl PathPipeline {
             }],
             label: Some("Segments storage buffer bind group"),
         });
-        //           +y
-        //           |
-        // -x -------|--------- +x
-        //           |
-        //          -y
-        let vertices = vec![
-            ColorVertex { pos: [-1.0, -1.0] },
-            ColorVertex { pos: [-1.0, 1.0] },
-            ColorVertex { pos: [1.0, -1.0] },
-            ColorVertex { pos: [1.0, 1.0] },
-        ];
-        let color_vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("Color Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: BufferUsages::VERTEX,
-        });
-        let indices = [0u16, 3, 1, 0, 2, 3];
-        let color_index_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("Color Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: BufferUsages::INDEX,
+        let bounds: Vec<[f32; 4]> = normalized.iter().map(Self::bounds).collect();
+        let instances: Vec<primitive::PathInstance> = bounds
+            .iter()
+            .zip(paths)
+            .map(|(bounds, (_, paint))| primitive::PathInstance {
+                bounds: *bounds,
+                color: paint.color,
+                gradient_geometry: paint.geometry,
+                gradient: paint.gradient,
+            })
+            .collect();
+        let instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Path instance Buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
         });
+        let stops_bind_group = stops_bind_group(device, &self.stops_bind_group_layout, stops);
 
         self.mount = Some(Mount {
             fan_vertex_buffer,
             fan_index_buffer,
-            fan_index_count: fan_indices.len() as u32,
             segment_vertex_buffer,
             segment_index_buffer,
-            segment_index_count: segment_indices.len() as u32,
             segments_buffer,
             segments_buffer_bind_group,
-            color_vertex_buffer,
-            color_index_buffer,
-            color_index_count: indices.len() as u32,
+            instance_buffer,
+            stops_bind_group,
+            fan_ranges,
+            segment_ranges,
+            fill_rules: paths.iter().map(|(path, _)| path.fill_rule).collect(),
+            batch_ends: Self::batch_ends(&bounds),
         });
     }
 
+    /// Replaces the paint of the path with the index of the last mount, its bounds
+    /// stay the same
+    pub(crate) fn patch(&self, queue: &wgpu::Queue, index: usize, paint: &primitive::Paint) {
+        if let Some(mount) = &self.mount {
+            // The paint follows the bounds in the instance
+            let offset = (index * mem::size_of::<primitive::PathInstance>()
+                + mem::size_of::<[f32; 4]>()) as u64;
+            queue.write_buffer(
+                &mount.instance_buffer,
+                offset,
+                bytemuck::cast_slice(&[paint.color, paint.geometry]),
+            );
+            queue.write_buffer(
+                &mount.instance_buffer,
+                offset + mem::size_of::<[f32; 8]>() as u64,
+                bytemuck::bytes_of(&paint.gradient),
+            );
+        }
+    }
+
     pub(crate) fn resize(&self, queue: &wgpu::Queue, config: &wgpu::SurfaceConfiguration) {
         let globals = primitive::Globals {
             width_height: util::pack(config.width as u16, config.height as u16),
@@ -1,4 +1,5 @@
 use crate::math::{max, min, rect};
+use crate::renderer::wgpu::pipeline::color_target;
 use crate::renderer::wgpu::primitive;
 use crate::renderer::wgpu::primitive::PathSegment;
 use crate::renderer::MSAA;
@@ -695,7 +696,7 @@ impl PathPipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(config.format.into())],
+                targets: &[Some(color_target(config.format))],
             }),
             primitive: wgpu::PrimitiveState {
                 cull_mode: Some(wgpu::Face::Back),
@@ -1,7 +1,12 @@
+use crate::renderer::wgpu::pipeline::{
+    clip_test, color_target, stops_bind_group, stops_bind_group_layout,
+};
 use crate::renderer::wgpu::primitive;
 use crate::renderer::MSAA;
 use crate::util;
 use std::borrow::Cow;
+use std::mem;
+use std::ops::Range;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu_types::{BufferUsages, MultisampleState};
 
@@ -9,6 +14,8 @@ pub struct RectPipeline {
     pipeline: wgpu::RenderPipeline,
     globals_buffer: wgpu::Buffer,
     globals_bind_group: wgpu::BindGroup,
+    stops_bind_group_layout: wgpu::BindGroupLayout,
+    stops_bind_group: Option<wgpu::BindGroup>,
     instance_count: usize,
     instance_buffer: Option<wgpu::Buffer>,
 }
@@ -51,14 +58,16 @@ impl RectPipeline {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                "../../../../shader/rect.wgsl"
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                include_str!("../../../../shader/paint.wgsl"),
+                include_str!("../../../../shader/rect.wgsl")
             ))),
         });
 
+        let stops_bind_group_layout = stops_bind_group_layout(device);
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[&globals_bind_group_layout],
+            bind_group_layouts: &[&globals_bind_group_layout, &stops_bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -79,13 +88,11 @@ impl RectPipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(config.format.into())],
+                targets: &[Some(color_target(config.format))],
             }),
-            primitive: wgpu::PrimitiveState {
-                cull_mode: Some(wgpu::Face::Back),
-                ..Default::default()
-            },
-            depth_stencil: None,
+            // Transforms may mirror the primitives, therefore nothing is culled
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(clip_test()),
             multisample,
             multiview: None,
         });
@@ -94,6 +101,8 @@ impl RectPipeline {
             pipeline,
             globals_buffer,
             globals_bind_group,
+            stops_bind_group_layout,
+            stops_bind_group: None,
             instance_count: 0,
             instance_buffer: None,
         }
@@ -107,19 +116,38 @@ impl RectPipeline {
         queue.write_buffer(&self.globals_buffer, 0, bytemuck::cast_slice(&[globals]));
     }
 
-    pub fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if self.instance_count > 0 {
+    /// Records the rects with the indices in `range`
+    pub fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, range: Range<usize>) {
+        debug_assert!(range.end <= self.instance_count);
+        if !range.is_empty() {
             // If instance_count > 0 then the instance buffer must exist
             debug_assert!(self.instance_buffer.is_some());
             render_pass.set_pipeline(&self.pipeline);
             render_pass.set_bind_group(0, &self.globals_bind_group, &[]);
+            render_pass.set_bind_group(1, self.stops_bind_group.as_ref().unwrap(), &[]);
             render_pass.set_vertex_buffer(0, self.instance_buffer.as_ref().unwrap().slice(..));
-            render_pass.draw(0..6, 0..self.instance_count as _);
+            render_pass.draw(0..6, range.start as u32..range.end as u32);
+        }
+    }
+
+    /// Replaces the rect with the index of the last mount. Its gradient stops stay
+    /// the same.
+    pub(crate) fn patch(&self, queue: &wgpu::Queue, index: usize, rect: &primitive::Rect) {
+        debug_assert!(index < self.instance_count);
+        if let Some(buffer) = &self.instance_buffer {
+            let offset = (index * mem::size_of::<primitive::Rect>()) as u64;
+            queue.write_buffer(buffer, offset, bytemuck::bytes_of(rect));
         }
     }
 
-    pub(crate) fn mount(&mut self, device: &wgpu::Device, rects: &Vec<primitive::Rect>) {
+    pub(crate) fn mount(
+        &mut self,
+        device: &wgpu::Device,
+        rects: &Vec<primitive::Rect>,
+        stops: &[primitive::GradientStop],
+    ) {
         if rects.len() == 0 {
+            self.instance_count = 0;
             return;
         }
         // Use write buffer instead of recreation in the future.
@@ -129,8 +157,13 @@ impl RectPipeline {
         self.instance_buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(rects),
-            usage: BufferUsages::VERTEX,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
         }));
+        self.stops_bind_group = Some(stops_bind_group(
+            device,
+            &self.stops_bind_group_layout,
+            stops,
+        ));
         self.instance_count = rects.len();
     }
 }
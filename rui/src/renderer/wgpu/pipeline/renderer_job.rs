@@ -1,26 +1,141 @@
+use crate::animation::{AnimatedValues, Property};
+use crate::error::Error;
+use crate::math::Transform;
 use crate::node::base::BaseNode;
+use crate::node::image::Sampling;
 use crate::node::path::PathNode;
-use crate::renderer::wgpu::pipeline::image_pipeline::ImagePipeline;
-use crate::renderer::wgpu::pipeline::new_path_pipeline::PathPipeline;
-use crate::renderer::wgpu::pipeline::rect_pipeline::RectPipeline;
+use crate::node::Clip;
+use crate::renderer::wgpu::pipeline::blur_pipeline::BlurPipeline;
+use crate::renderer::wgpu::pipeline::composite_pipeline::{CompositePipeline, Layer};
+use crate::renderer::wgpu::pipeline::draw_list::{Draw, DrawList, Primitives};
 use crate::renderer::wgpu::primitive;
+use crate::renderer::wgpu::texture_cache::TextureCache;
 use crate::renderer::MSAA;
-use crate::util::{Flags, PathSegment, Rect};
+use crate::util::{Flags, Paint, PathSegment, Rect, Spread};
 use crate::{Backend, Node};
 use async_recursion::async_recursion;
 use rui_util::{be, bs, Extent};
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::mem;
 use wgpu_types::TextureFormat;
 
+/// State that is passed down from a node to its children while flattening
+#[derive(Copy, Clone)]
+struct Inherited {
+    opacity: f32,
+    /// Maps the coordinates of the node to surface coordinates
+    transform: Transform,
+    /// Scissor rect `[x, y, width, height]` of the clips of the ancestors in pixels
+    scissor: [u32; 4],
+}
+impl Inherited {
+    fn root(root: &Rect) -> Self {
+        Inherited {
+            opacity: 1.0,
+            transform: Transform::IDENTITY,
+            scissor: [0, 0, root.extent.width, root.extent.height],
+        }
+    }
+
+    fn child(&self, base: &BaseNode) -> Self {
+        Inherited {
+            opacity: self.opacity * base.opacity,
+            transform: base.world_transform(&self.transform),
+            scissor: self.scissor,
+        }
+    }
+
+    fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity *= opacity;
+        self
+    }
+}
+
+/// How the clip of a node is drawn, the clip has to be ended after the children
+enum ClipScope {
+    Unclipped,
+    /// Nothing inside the clip is visible
+    Empty,
+    /// Restores the scissor rect of the ancestors
+    Scissor([u32; 4]),
+    Stencil,
+}
+impl ClipScope {
+    fn end(self, out: &mut Primitives) {
+        match self {
+            ClipScope::Scissor(previous) => out.push_scissor(previous),
+            ClipScope::Stencil => out.pop_clip(),
+            ClipScope::Unclipped | ClipScope::Empty => {}
+        }
+    }
+}
+
+/// The nodes of a group are rendered into their own layer with their own pipelines
+struct Group {
+    draw_list: DrawList,
+    layer: Layer,
+    opacity: f32,
+    /// Standard deviation of the blur of the layer, zero if it isn't blurred
+    blur: f32,
+}
+
+/// A group while the node tree is flattened
+struct GroupPrimitives {
+    opacity: f32,
+    blur: f32,
+    primitives: Primitives,
+}
+
+/// A node with a key as it was flattened, before its animated values were applied
+#[derive(Clone)]
+pub(crate) struct Keyed {
+    key: u64,
+    base: BaseNode,
+    /// Whether the node is an image that plays once it's mounted
+    autoplay: Option<bool>,
+    /// Where the animated values of the node are patched in, `None` if the node has
+    /// to be flattened again
+    patch: Option<Patch>,
+}
+
+/// The single primitive of a leaf, see [RenderJob::patch_target]
+#[derive(Clone)]
+struct Patch {
+    root: Rect,
+    parent: Rect,
+    /// State of the parent, before the node was applied to it
+    inherited: Inherited,
+    primitive: Patched,
+    /// Index of the first gradient stop of the primitive
+    stops: u32,
+}
+
+/// Index of a primitive in the draw list
+#[derive(Copy, Clone)]
+enum Patched {
+    Rect(usize),
+    Image(usize),
+    Path(usize),
+}
+
 pub struct RenderJob<B>
 where
     B: Backend,
 {
     pub(crate) config: wgpu::SurfaceConfiguration,
     pub(crate) surface: wgpu::Surface,
-    pub(crate) rect_pipeline: RectPipeline,
-    pub(crate) image_pipeline: ImagePipeline,
-    pub(crate) path_pipeline: PathPipeline,
+    draw_list: DrawList,
+    pub(crate) composite_pipeline: CompositePipeline,
+    blur_pipeline: BlurPipeline,
+    /// Groups of the last mount come first, the remaining ones are kept for reuse
+    groups: Vec<Group>,
+    group_count: usize,
+    /// Order to render the groups in, so that each group is rendered before the
+    /// layers that composite it
+    group_order: Vec<usize>,
+    /// Nodes with a key of the last mount, with the group they were flattened into
+    keys: HashMap<u64, Vec<(Option<usize>, Keyed)>>,
     pub(crate) multisampling_framebuffer: Option<wgpu::TextureView>,
     pub(crate) stencil_framebuffer: wgpu::TextureView,
     pub(crate) msaa: MSAA,
@@ -86,9 +201,9 @@ where
         surface: wgpu::Surface,
         msaa: MSAA,
     ) -> Self {
-        let rect_pipeline = RectPipeline::new(device, &config, &msaa);
-        let image_pipeline = ImagePipeline::new(device, &config, &msaa);
-        let path_pipeline = PathPipeline::new(device, &config, &msaa);
+        let draw_list = DrawList::new(device, &config, &msaa);
+        let composite_pipeline = CompositePipeline::new(device, &config, &msaa);
+        let blur_pipeline = BlurPipeline::new(device, &config);
 
         let multisampling_framebuffer = match msaa {
             MSAA::X1 => None,
@@ -102,9 +217,13 @@ where
         RenderJob {
             config,
             surface,
-            rect_pipeline,
-            image_pipeline,
-            path_pipeline,
+            draw_list,
+            composite_pipeline,
+            blur_pipeline,
+            groups: vec![],
+            group_count: 0,
+            group_order: vec![],
+            keys: HashMap::new(),
             multisampling_framebuffer,
             stencil_framebuffer,
             msaa,
@@ -112,29 +231,386 @@ where
         }
     }
 
-    //Calculate rect of primitive element
-    fn rect(parent: &Rect, base: &BaseNode) -> Rect {
-        //debug_assert!(parent.intersect(&base.bounding_rect).is_some());
-        let mut width = base.bounding_rect.extent.width.clamp(
-            0,
-            (parent.extent.width as i32 - base.bounding_rect.offset.x) as u32,
-        );
-        let mut height = base.bounding_rect.extent.height.clamp(
-            0,
-            (parent.extent.height as i32 - base.bounding_rect.offset.y) as u32,
+    /// Paint of the node with the opacity of its ancestors applied. The stops of
+    /// gradients are appended to `stops`.
+    fn paint(
+        base: &BaseNode,
+        inherited: &Inherited,
+        stops: &mut Vec<primitive::GradientStop>,
+    ) -> primitive::Paint {
+        let (kind, geometry, gradient_stops, spread) = match &base.background {
+            Paint::Solid(color) => {
+                let mut color = color.as_raw();
+                color[3] *= inherited.opacity;
+                return primitive::Paint {
+                    color,
+                    ..Default::default()
+                };
+            }
+            Paint::Linear {
+                start,
+                end,
+                stops,
+                spread,
+            } => (
+                primitive::Paint::LINEAR,
+                [start[0], start[1], end[0], end[1]],
+                stops,
+                spread,
+            ),
+            Paint::Radial {
+                center,
+                radius,
+                stops,
+                spread,
+            } => (
+                primitive::Paint::RADIAL,
+                [center[0], center[1], *radius, 0.0],
+                stops,
+                spread,
+            ),
+            Paint::Conic {
+                center,
+                angle,
+                stops,
+                spread,
+            } => (
+                primitive::Paint::CONIC,
+                [center[0], center[1], *angle, 0.0],
+                stops,
+                spread,
+            ),
+        };
+        let spread = match spread {
+            Spread::Pad => primitive::Paint::PAD,
+            Spread::Repeat => primitive::Paint::REPEAT,
+            Spread::Reflect => primitive::Paint::REFLECT,
+        };
+        let first = stops.len() as u32;
+        stops.extend(gradient_stops.iter().map(|stop| primitive::GradientStop {
+            color: stop.color.as_raw(),
+            offset: stop.offset,
+            _padding: [0.0; 3],
+        }));
+        primitive::Paint {
+            color: [1.0, 1.0, 1.0, inherited.opacity],
+            geometry,
+            gradient: [kind, spread, first, gradient_stops.len() as u32],
+        }
+    }
+
+    /// Rect primitive of a rectangle node, see [RenderJob::paint]
+    fn rect(
+        root: &Rect,
+        parent: &Rect,
+        base: &BaseNode,
+        inherited: &Inherited,
+        stops: &mut Vec<primitive::GradientStop>,
+    ) -> primitive::Rect {
+        let paint = Self::paint(base, inherited, stops);
+        primitive::Rect {
+            rect: base.rect(parent).norm(root),
+            color: paint.color,
+            radii: base.border_radii,
+            transform: Self::norm_transform(root, &inherited.transform),
+            gradient_geometry: paint.geometry,
+            gradient: paint.gradient,
+        }
+    }
+
+    /// Instance of an image node that covers the rect
+    fn image_instance(
+        root: &Rect,
+        rect: &Rect,
+        base: &BaseNode,
+        inherited: &Inherited,
+    ) -> primitive::Instance {
+        primitive::Instance {
+            rect: rect.norm(root),
+            // The alpha is the opacity of the image
+            color: [1.0, 1.0, 1.0, inherited.opacity],
+            radii: base.border_radii,
+            transform: Self::norm_transform(root, &inherited.transform),
+            gradient_geometry: [0.0; 4],
+            gradient: [primitive::Paint::SOLID, 0, 0, 0],
+        }
+    }
+
+    /// Converts a transform in surface coordinates to normalized coordinates
+    fn norm_transform(root: &Rect, transform: &Transform) -> [f32; 8] {
+        let size = Transform::scale(root.extent.width as f32, root.extent.height as f32);
+        // Both dimensions are at least one, see `resize`
+        let inverse = size.inverse().unwrap();
+        (inverse * *transform * size).as_raw()
+    }
+
+    /// Maps surface coordinates to the coordinates of paths, which span `-1..1`
+    /// vertically with the y axis pointing up
+    fn surface_to_path(root: &Rect) -> Transform {
+        let width = root.extent.width as f32;
+        let height = root.extent.height as f32;
+        let to_surface = Transform::new(
+            height / 2.0,
+            0.0,
+            0.0,
+            -height / 2.0,
+            width / 2.0,
+            height / 2.0,
         );
-        if base.flags.test(Flags::AUTO_WIDTH) {
-            width = (parent.extent.width as i32 - base.bounding_rect.offset.x).clamp(0, i32::MAX)
-                as u32;
+        to_surface.inverse().unwrap()
+    }
+
+    /// Converts a transform in surface coordinates to the coordinates of paths
+    fn path_transform(root: &Rect, transform: &Transform) -> Transform {
+        let to_path = Self::surface_to_path(root);
+        to_path * *transform * to_path.inverse().unwrap()
+    }
+
+    /// Maps a path of the node to the coordinates of paths. Paths with a view box are
+    /// placed into the bounds of the node, nothing is painted for empty view boxes.
+    /// Strokes are outlined before the transform, which also scales their width.
+    fn place_path(
+        root: &Rect,
+        parent: &Rect,
+        base: &BaseNode,
+        path: &PathNode,
+        transform: &Transform,
+    ) -> Option<PathNode> {
+        let outline = path.outline();
+        match path.view_box() {
+            Some(view_box) => {
+                let to_bounds = view_box.transform(&base.rect(parent))?;
+                Some(outline.transformed(&(Self::surface_to_path(root) * *transform * to_bounds)))
+            }
+            None if transform.is_identity() => Some(outline),
+            None => Some(outline.transformed(&Self::path_transform(root, transform))),
+        }
+    }
+
+    /// Starts the clip of the node. Clips to the bounds that stay axis aligned use
+    /// the scissor rect, all others are drawn into the stencil buffer.
+    fn clip(
+        root: &Rect,
+        parent: &Rect,
+        base: &BaseNode,
+        inherited: &mut Inherited,
+        out: &mut Primitives,
+    ) -> ClipScope {
+        let clip = match &base.clip {
+            Some(clip) => clip,
+            None => return ClipScope::Unclipped,
+        };
+        let rect = base.rect(parent);
+        let bounds = [
+            rect.offset.x as f32,
+            rect.offset.y as f32,
+            rect.extent.width as f32,
+            rect.extent.height as f32,
+        ];
+        let transform = &inherited.transform;
+        let path = match clip {
+            Clip::Bounds if transform.b == 0.0 && transform.c == 0.0 => {
+                let [x0, y0] = transform.apply([bounds[0], bounds[1]]);
+                let [x1, y1] = transform.apply([bounds[0] + bounds[2], bounds[1] + bounds[3]]);
+                let [x, y, width, height] = inherited.scissor;
+                let left = x0.min(x1).floor().clamp(x as f32, (x + width) as f32) as u32;
+                let top = y0.min(y1).floor().clamp(y as f32, (y + height) as f32) as u32;
+                let right = x0.max(x1).ceil().clamp(left as f32, (x + width) as f32) as u32;
+                let bottom = y0.max(y1).ceil().clamp(top as f32, (y + height) as f32) as u32;
+                if right == left || bottom == top {
+                    return ClipScope::Empty;
+                }
+                let previous = inherited.scissor;
+                inherited.scissor = [left, top, right - left, bottom - top];
+                out.push_scissor(inherited.scissor);
+                return ClipScope::Scissor(previous);
+            }
+            Clip::Bounds => PathNode::rounded_rect(bounds, [0.0; 4])
+                .transformed(&(Self::surface_to_path(root) * *transform)),
+            Clip::Rounded(radii) => {
+                // Like border radii the radii are relative to the width of the node
+                let radii = radii.map(|radius| radius * bounds[2]);
+                PathNode::rounded_rect(bounds, radii)
+                    .transformed(&(Self::surface_to_path(root) * *transform))
+            }
+            Clip::Path(path) => match Self::place_path(root, parent, base, path, transform) {
+                Some(path) => path,
+                None => return ClipScope::Empty,
+            },
+        };
+        out.push_clip(path);
+        ClipScope::Stencil
+    }
+
+    /// Bounds of the node as `[x, y, width, height]` in pixels and its border radii
+    /// in pixels. The border radii are relative to the width of the node.
+    fn rounded_bounds(parent: &Rect, base: &BaseNode) -> ([f32; 4], [f32; 4]) {
+        let rect = base.rect(parent);
+        let bounds = [
+            rect.offset.x as f32,
+            rect.offset.y as f32,
+            rect.extent.width as f32,
+            rect.extent.height as f32,
+        ];
+        (bounds, base.border_radii.map(|radius| radius * bounds[2]))
+    }
+
+    /// Pushes the outer or the inset shadows of the node
+    fn shadows(
+        root: &Rect,
+        parent: &Rect,
+        base: &BaseNode,
+        inherited: &Inherited,
+        inset: bool,
+        out: &mut Primitives,
+    ) {
+        if base.shadows.is_empty() {
+            return;
         }
-        if base.flags.test(Flags::AUTO_HEIGHT) {
-            height = (parent.extent.height as i32 - base.bounding_rect.offset.y).clamp(0, i32::MAX)
-                as u32;
+        let ([x, y, width, height], radii) = Self::rounded_bounds(parent, base);
+        let root_width = root.extent.width as f32;
+        let root_height = root.extent.height as f32;
+        for shadow in base.shadows.iter().filter(|shadow| shadow.inset == inset) {
+            // Like the CSS box-shadow the standard deviation is half of the blur radius
+            let sigma = shadow.blur.max(0.0) / 2.0;
+            // The spread grows outer shadows and shrinks the shape of inset shadows
+            let spread = if inset { -shadow.spread } else { shadow.spread };
+            let shape = [
+                x + shadow.offset[0] - spread,
+                y + shadow.offset[1] - spread,
+                (width + 2.0 * spread).max(0.0),
+                (height + 2.0 * spread).max(0.0),
+            ];
+            let shape_radii = radii.map(|radius| {
+                if radius > 0.0 {
+                    (radius + spread).max(0.0)
+                } else {
+                    0.0
+                }
+            });
+            // Inset shadows stay within the bounds, outer ones fade out after three
+            // standard deviations
+            let quad = if inset {
+                [x, y, width, height]
+            } else {
+                let margin = 3.0 * sigma + 1.0;
+                [
+                    shape[0] - margin,
+                    shape[1] - margin,
+                    shape[2] + 2.0 * margin,
+                    shape[3] + 2.0 * margin,
+                ]
+            };
+            let mut color = shadow.color.as_raw();
+            color[3] *= inherited.opacity;
+            // Inset shadows are part of the group and faded with it, outer shadows are
+            // painted outside of it
+            if !inset {
+                color[3] *= base.group_opacity.unwrap_or(1.0);
+            }
+            out.push_shadow(primitive::Shadow {
+                rect: [
+                    quad[0] / root_width,
+                    quad[1] / root_height,
+                    quad[2] / root_width,
+                    quad[3] / root_height,
+                ],
+                color,
+                shape: [shape[0] - quad[0], shape[1] - quad[1], shape[2], shape[3]],
+                shape_radii,
+                bounds: [x - quad[0], y - quad[1], width, height],
+                bounds_radii: radii,
+                params: [sigma, if inset { 1.0 } else { 0.0 }, 0.0, 0.0],
+                transform: Self::norm_transform(root, &inherited.transform),
+            });
+        }
+    }
+
+    /// Renders what was painted so far into a layer that is blurred and composited
+    /// within the bounds of the node
+    fn backdrop(
+        root: &Rect,
+        parent: &Rect,
+        base: &BaseNode,
+        inherited: &Inherited,
+        out: &mut Primitives,
+        groups: &mut Vec<GroupPrimitives>,
+    ) {
+        let index = groups.len();
+        groups.push(GroupPrimitives {
+            opacity: 1.0,
+            blur: base.backdrop_blur,
+            primitives: out.clone(),
+        });
+        let (bounds, radii) = Self::rounded_bounds(parent, base);
+        let shape = PathNode::rounded_rect(bounds, radii)
+            .transformed(&(Self::surface_to_path(root) * inherited.transform));
+        out.push_clip(shape);
+        out.push_group(index);
+        out.pop_clip();
+    }
+
+    /// Records the node if it has a key, before its animated values are applied.
+    /// Returns the index of the record in `out`.
+    fn record_key(node: &mut Node, out: &mut Primitives) -> Option<usize> {
+        let autoplay = match node {
+            Node::Image(_, i) => Some(i.autoplay()),
+            _ => None,
+        };
+        let base = node.base_mut();
+        out.keyed.push(Keyed {
+            key: base.key?,
+            base: base.clone(),
+            autoplay,
+            patch: None,
+        });
+        Some(out.keyed.len() - 1)
+    }
+
+    /// Gives the node its own values back after it was flattened with the animated ones
+    fn restore(node: &mut Node, keyed: Option<usize>, out: &Primitives) {
+        if let Some(index) = keyed {
+            AnimatedValues::restore(node, &out.keyed[index].base);
         }
-        Rect {
-            offset: base.bounding_rect.offset.clone(),
-            extent: Extent { width, height },
+    }
+
+    /// Where the animated values of a leaf that was just flattened are patched in.
+    /// Leaves with shadows, clips or layers and nodes with children are flattened
+    /// again instead. `counts` are the numbers of rects, images and paths before.
+    fn patch_target(
+        root: &Rect,
+        parent: &Rect,
+        inherited: &Inherited,
+        node: &mut Node,
+        counts: (usize, usize, usize),
+        out: &Primitives,
+    ) -> Option<Patch> {
+        let base = node.base_mut();
+        if base.flags.test(Flags::TRANSPARENT)
+            || !base.shadows.is_empty()
+            || base.clip.is_some()
+            || base.backdrop_blur > 0.0
+        {
+            return None;
         }
+        let (rects, images, paths) = counts;
+        let (primitive, stops) = match node {
+            Node::Rectangle(_) if out.rects.len() == rects + 1 => {
+                (Patched::Rect(rects), out.rects[rects].gradient[2])
+            }
+            Node::Image(..) if out.images.len() == images + 1 => (Patched::Image(images), 0),
+            Node::Path(..) if out.paths.len() == paths + 1 => {
+                (Patched::Path(paths), out.paths[paths].1.gradient[2])
+            }
+            _ => return None,
+        };
+        Some(Patch {
+            root: root.clone(),
+            parent: parent.clone(),
+            inherited: *inherited,
+            primitive,
+            stops,
+        })
     }
 
     // For now a simple recursive variant
@@ -147,22 +623,102 @@ where
         root: &Rect,
         parent: &Rect,
         node: &mut Node,
-        rects: &mut Vec<primitive::Rect>,
-        images: &mut Vec<primitive::Image>,
-        paths: &mut Vec<PathNode>,
+        values: &AnimatedValues,
+        inherited: Inherited,
+        out: &mut Primitives,
+        groups: &mut Vec<GroupPrimitives>,
+    ) {
+        // The stored node keeps its own values, the animated ones only apply while
+        // it's flattened
+        let keyed = Self::record_key(node, out);
+        values.apply(node);
+        let parent_inherited = inherited;
+        let mut inherited = inherited.child(node.base_mut());
+        let clip = Self::clip(root, parent, node.base_mut(), &mut inherited, out);
+        if let ClipScope::Empty = clip {
+            Self::restore(node, keyed, out);
+            return;
+        }
+        let base = node.base_mut();
+        Self::shadows(root, parent, base, &inherited, false, out);
+        if base.backdrop_blur > 0.0 {
+            Self::backdrop(root, parent, base, &inherited, out, groups);
+        }
+        if base.group_opacity.is_some() || base.blur > 0.0 {
+            // The group is rendered without its opacity and faded as a whole
+            // when its layer gets composited
+            let opacity = inherited.opacity * base.group_opacity.unwrap_or(1.0);
+            let blur = base.blur;
+            let mut group_inherited = inherited;
+            group_inherited.opacity = 1.0;
+            let index = groups.len();
+            groups.push(GroupPrimitives {
+                opacity,
+                blur,
+                primitives: Primitives::default(),
+            });
+            out.push_group(index);
+            // Nested groups get a higher index and are composited into this layer
+            let mut primitives = Primitives::default();
+            Self::flatten_node(
+                root,
+                parent,
+                node,
+                values,
+                group_inherited,
+                &mut primitives,
+                groups,
+            )
+            .await;
+            groups[index].primitives = primitives;
+        } else {
+            let counts = (out.rects.len(), out.images.len(), out.paths.len());
+            Self::flatten_node(root, parent, node, values, inherited, out, groups).await;
+            if let Some(index) = keyed {
+                out.keyed[index].patch =
+                    Self::patch_target(root, parent, &parent_inherited, node, counts, out);
+            }
+        }
+        clip.end(out);
+        Self::restore(node, keyed, out);
+    }
+
+    #[async_recursion]
+    async fn flatten_node(
+        root: &Rect,
+        parent: &Rect,
+        node: &mut Node,
+        values: &AnimatedValues,
+        inherited: Inherited,
+        out: &mut Primitives,
+        groups: &mut Vec<GroupPrimitives>,
     ) {
+        let leaf = matches!(
+            node,
+            Node::Rectangle(_) | Node::Path(..) | Node::Image(..) | Node::Text(..)
+        );
+        if leaf && node.base_mut().flags.test(Flags::TRANSPARENT) {
+            // Only the children of transparent nodes are painted
+            Self::shadows(root, parent, node.base_mut(), &inherited, true, out);
+            return;
+        }
+        // Inset shadows are painted between the fill of a node and its children
+        if !leaf {
+            Self::shadows(root, parent, node.base_mut(), &inherited, true, out);
+        }
         match node {
-            Node::Rectangle(base) => rects.push(primitive::Rect {
-                rect: Self::rect(parent, base).norm(root),
-                color: base.background.as_raw(),
-                radii: base.border_radii,
-            }),
-            #[allow(unused_variables)]
-            Node::Border(base, b) => {
-                Self::flatten(root, parent, b.node_mut(), rects, images, paths).await;
+            Node::Rectangle(base) => {
+                let rect = Self::rect(root, parent, base, &inherited, &mut out.stops);
+                out.push_rect(rect)
+            }
+            Node::Border(_, b) => {
+                Self::flatten(root, parent, b.node_mut(), values, inherited, out, groups).await;
             }
             Node::Path(base, p) => {
-                paths.push(p.clone());
+                let paint = Self::paint(base, &inherited, &mut out.stops);
+                if let Some(path) = Self::place_path(root, parent, base, p, &inherited.transform) {
+                    out.push_path(path, paint);
+                }
                 /*
                 let mut segments = Vec::with_capacity(p.segments().len());
                 let mut from = p.from();
@@ -215,61 +771,289 @@ where
                 */
             }
             Node::Composition(_, c) => {
-                for node in c.layers_mut() {
-                    Self::flatten(root, parent, node, rects, images, paths).await;
+                for (node, opacity) in c.layers_with_opacity_mut() {
+                    let inherited = inherited.with_opacity(opacity);
+                    Self::flatten(root, parent, node, values, inherited, out, groups).await;
                 }
             }
-            Node::Image(base, i) => images.push(primitive::Image {
-                instance: primitive::Instance {
-                    rect: Self::rect(parent, base).norm(root),
-                    color: base.background.as_raw(),
-                    radii: base.border_radii,
-                },
-                resource: i.resource().clone(),
-            }),
+            Node::Image(base, i) => {
+                let rect = base.rect(parent);
+                let (time, playing) = values.image_time(base.key, i.autoplay());
+                out.push_image(primitive::Image {
+                    instance: Self::image_instance(root, &rect, base, &inherited),
+                    resource: i.resource().clone(),
+                    size: [rect.extent.width as f32, rect.extent.height as f32],
+                    fit: i.fit(),
+                    position: i.position(),
+                    slice: i.slice().copied(),
+                    sampling: i.sampling(),
+                    time,
+                    playing,
+                })
+            }
             #[allow(unused_variables)]
             Node::Text(_, t) => {
                 todo!()
             }
             Node::Component(_, c) => {
                 let mut node = c.node().await;
-                Self::flatten(root, parent, &mut node, rects, images, paths).await;
+                Self::flatten(root, parent, &mut node, values, inherited, out, groups).await;
             }
         }
+        if leaf {
+            Self::shadows(root, parent, node.base_mut(), &inherited, true, out);
+        }
     }
 
     pub(crate) async fn mount(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        textures: &mut TextureCache,
         node: &mut Node,
-    ) {
-        let mut rects = vec![];
-        let mut images = vec![];
-        let mut paths = vec![];
+        values: &AnimatedValues,
+    ) -> Result<(), Error> {
+        let mut primitives = Primitives::default();
+        let mut groups = vec![];
         let root = Rect::new(0, 0, self.config.width, self.config.height);
-        Self::flatten(&root, &root, node, &mut rects, &mut images, &mut paths).await;
-        self.rect_pipeline.mount(device, &rects);
-        self.image_pipeline.mount(device, queue, &images).await;
-        self.path_pipeline.mount(device, &paths);
+        Self::flatten(
+            &root,
+            &root,
+            node,
+            values,
+            Inherited::root(&root),
+            &mut primitives,
+            &mut groups,
+        )
+        .await;
+
+        // Load the images first, so that a failure keeps the last mount intact
+        let images = groups
+            .iter()
+            .flat_map(|group| &group.primitives.images)
+            .chain(&primitives.images);
+        for image in images {
+            let mipmapped = image.sampling == Sampling::Mipmapped;
+            textures
+                .load(device, queue, &image.resource, mipmapped)
+                .await?;
+        }
+
+        // Groups are mounted in order, so a record's group is its index in `groups`
+        let mut keys: HashMap<u64, Vec<(Option<usize>, Keyed)>> = HashMap::new();
+        let keyed = groups
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(index, group)| {
+                mem::take(&mut group.primitives.keyed)
+                    .into_iter()
+                    .map(move |keyed| (Some(index), keyed))
+            })
+            .chain(
+                mem::take(&mut primitives.keyed)
+                    .into_iter()
+                    .map(|keyed| (None, keyed)),
+            );
+        for (group, keyed) in keyed {
+            keys.entry(keyed.key).or_default().push((group, keyed));
+        }
+
+        self.group_order.clear();
+        let mut visited = vec![false; groups.len()];
+        Self::order_groups(
+            &primitives.draws,
+            &groups,
+            &mut visited,
+            &mut self.group_order,
+        );
+        self.draw_list
+            .mount(device, queue, textures, primitives)
+            .await?;
+
+        self.group_count = groups.len();
+        for (index, group) in groups.into_iter().enumerate() {
+            let GroupPrimitives {
+                opacity,
+                blur,
+                primitives,
+            } = group;
+            if index == self.groups.len() {
+                self.groups.push(Group {
+                    draw_list: DrawList::new(device, &self.config, &self.msaa),
+                    layer: self.composite_pipeline.layer(device, &self.config, opacity),
+                    opacity,
+                    blur,
+                });
+            }
+            let group = &mut self.groups[index];
+            if group.opacity != opacity {
+                group.opacity = opacity;
+                self.composite_pipeline
+                    .set_opacity(queue, &group.layer, opacity);
+            }
+            group.blur = blur;
+            group
+                .draw_list
+                .mount(device, queue, textures, primitives)
+                .await?;
+        }
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Patches the animated values into the primitives of the last mount. Returns
+    /// `false` if a node has to be flattened again, e.g. because it has children or
+    /// the animation changes the outline of a path.
+    pub(crate) fn animate(&mut self, queue: &wgpu::Queue, values: &AnimatedValues) -> bool {
+        for (key, animated) in values.animated() {
+            let records = match self.keys.get(&key) {
+                Some(records) => records,
+                None => return false,
+            };
+            for (group, keyed) in records {
+                let patch = match &keyed.patch {
+                    Some(patch) => patch,
+                    None => return false,
+                };
+                let mut base = keyed.base.clone();
+                for value in animated {
+                    value.apply(&mut base);
+                }
+                let inherited = patch.inherited.child(&base);
+                let draw_list = match group {
+                    Some(index) => &mut self.groups[*index].draw_list,
+                    None => &mut self.draw_list,
+                };
+                // Gradients keep their stops of the last mount
+                let mut stops = vec![];
+                match patch.primitive {
+                    Patched::Rect(index) => {
+                        let mut rect =
+                            Self::rect(&patch.root, &patch.parent, &base, &inherited, &mut stops);
+                        rect.gradient[2] = patch.stops;
+                        draw_list.patch_rect(queue, index, &rect);
+                    }
+                    Patched::Image(index) => {
+                        let rect = base.rect(&patch.parent);
+                        let instance = Self::image_instance(&patch.root, &rect, &base, &inherited);
+                        let size = [rect.extent.width as f32, rect.extent.height as f32];
+                        draw_list.patch_image(queue, index, instance, size);
+                    }
+                    Patched::Path(index) => {
+                        if animated
+                            .iter()
+                            .any(|value| matches!(value, Property::BoundingRect(_)))
+                        {
+                            return false;
+                        }
+                        let mut paint = Self::paint(&base, &inherited, &mut stops);
+                        paint.gradient[2] = patch.stops;
+                        draw_list.patch_path(queue, index, &paint);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Reads the property of the node with the key as it was mounted last, without
+    /// its animated value
+    pub(crate) fn read(&self, key: u64, property: &Property) -> Option<Property> {
+        let (_, keyed) = self.keys.get(&key)?.first()?;
+        Some(property.read(&keyed.base))
+    }
+
+    /// Whether the last mount has a node with the key
+    pub(crate) fn is_mounted(&self, key: u64) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    /// Whether the image with the key plays once it's mounted, `None` if the last
+    /// mount has no such image
+    pub(crate) fn autoplay(&self, key: u64) -> Option<bool> {
+        self.keys
+            .get(&key)?
+            .iter()
+            .find_map(|(_, keyed)| keyed.autoplay)
+    }
+
+    /// Orders the groups depth first, so that the groups a draw list composites are
+    /// rendered before it. Backdrops composite groups that come before them.
+    fn order_groups(
+        draws: &[Draw],
+        groups: &[GroupPrimitives],
+        visited: &mut Vec<bool>,
+        order: &mut Vec<usize>,
+    ) {
+        for draw in draws {
+            if let Draw::Group(index) = draw {
+                if !visited[*index] {
+                    visited[*index] = true;
+                    Self::order_groups(&groups[*index].primitives.draws, groups, visited, order);
+                    order.push(*index);
+                }
+            }
+        }
     }
 
-    pub(crate) fn record_prepass<'a>(&'a self, pre_pass: &mut wgpu::RenderPass<'a>) {
-        self.path_pipeline.record_stencil_pass(pre_pass);
+    /// Indices of the groups in the order they have to be rendered in
+    pub(crate) fn group_order(&self) -> &[usize] {
+        &self.group_order
+    }
+
+    /// Whether animated images of the last mount show other frames later
+    pub(crate) fn is_playing(&self) -> bool {
+        self.draw_list.is_playing()
+            || self.groups[..self.group_count]
+                .iter()
+                .any(|group| group.draw_list.is_playing())
+    }
+
+    /// Layers of the groups of the last mount
+    pub(crate) fn layers(&self) -> Vec<&Layer> {
+        self.groups[..self.group_count]
+            .iter()
+            .map(|group| &group.layer)
+            .collect()
+    }
+
+    /// Records the group into its layer, see [RenderJob::group_order]
+    pub(crate) fn record_group<'a>(&'a self, index: usize, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.groups[index]
+            .draw_list
+            .record(render_pass, &self.composite_pipeline, &self.layers());
+    }
+
+    /// Blurs the layer of the group after it was rendered, if the group is blurred
+    pub(crate) fn blur_group(
+        &self,
+        index: usize,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let group = &self.groups[index];
+        if group.blur > 0.0 {
+            self.blur_pipeline
+                .record(device, encoder, &self.config, &group.layer, group.blur);
+        }
     }
 
     pub(crate) fn record<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        //self.rect_pipeline.record(render_pass);
-        //self.image_pipeline.record(render_pass);
-        self.path_pipeline.record(render_pass);
+        self.draw_list
+            .record(render_pass, &self.composite_pipeline, &self.layers());
     }
 
     pub(crate) fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: Extent) {
         self.config.width = size.width.max(1);
         self.config.height = size.height.max(1);
-        self.rect_pipeline.resize(queue, &self.config);
-        self.image_pipeline.resize(queue, &self.config);
-        self.path_pipeline.resize(queue, &self.config);
+        self.draw_list.resize(queue, &self.config);
+        self.blur_pipeline.resize(device, &self.config);
+        for group in &mut self.groups {
+            group.draw_list.resize(queue, &self.config);
+            group.layer = self
+                .composite_pipeline
+                .layer(device, &self.config, group.opacity);
+        }
 
         if self.multisampling_framebuffer.is_some() {
             self.multisampling_framebuffer = Some(Self::create_multisampling_framebuffer(
@@ -1,7 +1,81 @@
+use crate::node::image::{ImageFit, NineSlice, Sampling};
 use crate::renderer::wgpu::primitive;
+use crate::renderer::wgpu::texture_cache::CachedImage;
 use crate::util::Resource;
+use std::time::Duration;
 
+#[derive(Clone)]
 pub struct Image {
     pub(crate) instance: primitive::Instance,
     pub(crate) resource: Resource,
+    /// Width and height of the node in pixels
+    pub(crate) size: [f32; 2],
+    pub(crate) fit: ImageFit,
+    pub(crate) position: [f32; 2],
+    pub(crate) slice: Option<NineSlice>,
+    pub(crate) sampling: Sampling,
+    /// How long an animated image has played
+    pub(crate) time: Duration,
+    /// Whether an animated image is playing or paused
+    pub(crate) playing: bool,
+}
+
+/// Uniform of an image, the instance followed by where the image is drawn on the node
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ImageInstance {
+    pub instance: primitive::Instance,
+    /// Bounds of the image relative to the node after it was fitted
+    pub fit: [f32; 4],
+    /// Left, top, right and bottom slice of the texture in texture coordinates
+    pub slice: [f32; 4],
+    /// Left, top, right and bottom slice on the node relative to its size
+    pub slice_target: [f32; 4],
+    /// Bounds of the image in its texture, which may be an atlas
+    pub atlas: [f32; 4],
+}
+impl Image {
+    /// Uniform for the image after it was uploaded
+    pub fn uniform(&self, image: &CachedImage) -> ImageInstance {
+        let texture = [image.width as f32, image.height as f32];
+        let [node_width, node_height] = self.size;
+        let (fit, slice, slice_target) = match &self.slice {
+            // Nine-slice scaling fills the node, the slices replace the fit
+            Some(slice) if node_width > 0.0 && node_height > 0.0 => {
+                let [top, right, bottom, left] = slice.widths;
+                // Like CSS, the widths are scaled down uniformly if they overlap
+                let scale = (node_width / (left + right))
+                    .min(node_height / (top + bottom))
+                    .min(1.0);
+                let [inset_top, inset_right, inset_bottom, inset_left] = slice.insets;
+                (
+                    [0.0, 0.0, 1.0, 1.0],
+                    [
+                        inset_left / texture[0],
+                        inset_top / texture[1],
+                        inset_right / texture[0],
+                        inset_bottom / texture[1],
+                    ],
+                    [
+                        left * scale / node_width,
+                        top * scale / node_height,
+                        right * scale / node_width,
+                        bottom * scale / node_height,
+                    ],
+                )
+            }
+            _ => (
+                self.fit.rect(self.position, texture, self.size),
+                [0.0; 4],
+                [0.0; 4],
+            ),
+        };
+        ImageInstance {
+            instance: self.instance,
+            fit,
+            slice,
+            slice_target,
+            atlas: image.rect,
+        }
+    }
 }
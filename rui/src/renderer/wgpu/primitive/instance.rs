@@ -4,6 +4,11 @@ pub struct Instance {
     pub rect: [f32; 4],
     pub color: [f32; 4],
     pub radii: [f32; 4],
+    /// Affine transform in normalized surface coordinates, see [crate::math::Transform]
+    pub transform: [f32; 8],
+    /// See [crate::renderer::wgpu::primitive::Paint]
+    pub gradient_geometry: [f32; 4],
+    pub gradient: [u32; 4],
 }
 impl Instance {
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
@@ -27,6 +32,26 @@ impl Instance {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 24]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Uint32x4,
+                },
             ],
         }
     }
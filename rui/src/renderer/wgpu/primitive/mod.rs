@@ -1,13 +1,18 @@
 mod globals;
 mod image;
 mod instance;
+mod paint;
 mod path;
 mod rect;
+mod shadow;
 
-pub(crate) use self::image::Image;
+pub(crate) use self::image::{Image, ImageInstance};
 pub(crate) use globals::Globals;
 pub(crate) use instance::Instance;
+pub(crate) use paint::{GradientStop, Paint};
 pub(crate) use path::Path;
+pub(crate) use path::PathInstance;
 pub(crate) use path::PathSegment;
 pub(crate) use path::PathSegment1;
 pub(crate) use rect::Rect;
+pub(crate) use shadow::Shadow;
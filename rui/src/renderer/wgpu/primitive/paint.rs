@@ -0,0 +1,31 @@
+/// Fill of a primitive as the shaders expect it. The fields are part of the
+/// instances of the primitives.
+#[derive(Copy, Clone, Default)]
+pub struct Paint {
+    /// The solid color, gradients only use the alpha channel as opacity
+    pub color: [f32; 4],
+    /// Points of the gradient relative to the bounds of the primitive
+    pub geometry: [f32; 4],
+    /// Kind and spread of the gradient, the index of its first stop and the number
+    /// of stops
+    pub gradient: [u32; 4],
+}
+impl Paint {
+    pub const SOLID: u32 = 0;
+    pub const LINEAR: u32 = 1;
+    pub const RADIAL: u32 = 2;
+    pub const CONIC: u32 = 3;
+
+    pub const PAD: u32 = 0;
+    pub const REPEAT: u32 = 1;
+    pub const REFLECT: u32 = 2;
+}
+
+/// Stop of a gradient in the storage buffer of the stops
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GradientStop {
+    pub color: [f32; 4],
+    pub offset: f32,
+    pub _padding: [f32; 3],
+}
@@ -26,6 +26,49 @@ impl PathSegment {
     pub const CUBIC_BEZIER: u32 = 3;
     //const CATMULL_ROM: u32 = 4;
 }
+/// Covers the bounds of a path with its paint after it was drawn into the stencil buffer
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PathInstance {
+    /// Bounds of the control points as `[min x, min y, max x, max y]`
+    pub bounds: [f32; 4],
+    /// See [super::Paint]
+    pub color: [f32; 4],
+    pub gradient_geometry: [f32; 4],
+    pub gradient: [u32; 4],
+}
+impl PathInstance {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<PathInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Uint32x4,
+                },
+            ],
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Path {
     pub rect: [f32; 4],
@@ -0,0 +1,40 @@
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Shadow {
+    /// Quad covering the shadow in normalized surface coordinates
+    pub rect: [f32; 4],
+    pub color: [f32; 4],
+    /// Rect `[x, y, width, height]` casting the shadow in pixels relative to the quad
+    pub shape: [f32; 4],
+    /// Radii of the shape in pixels
+    pub shape_radii: [f32; 4],
+    /// Bounds of the node in pixels relative to the quad. Outer shadows are cut out of
+    /// the bounds, inset shadows are clipped to them.
+    pub bounds: [f32; 4],
+    pub bounds_radii: [f32; 4],
+    /// Standard deviation of the blur and whether the shadow is inset
+    pub params: [f32; 4],
+    /// Affine transform in normalized surface coordinates, see [crate::math::Transform]
+    pub transform: [f32; 8],
+}
+impl Shadow {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        const ATTRIBUTES: [wgpu::VertexAttribute; 9] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Shadow>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
@@ -1,12 +1,15 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use wgpu::{LoadOp, Operations, RenderPassDepthStencilAttachment};
+use wgpu::{LoadOp, Operations, RenderPassDepthStencilAttachment, SurfaceError};
 use wgpu_types::{Backends, Color, CompositeAlphaMode};
 
 use rui_io::surface::SurfaceId;
 use rui_util::{be, bs, Extent};
 
+use crate::animation::{AnimatedValues, Property};
 use crate::node::Node;
 use crate::renderer::wgpu::pipeline::renderer_job::RenderJob;
+use crate::renderer::wgpu::texture_cache::{TextureCache, DEFAULT_TEXTURE_BUDGET};
 use crate::renderer::wgpu::RendererError;
 use crate::renderer::MSAA;
 use crate::Backend;
@@ -76,6 +79,8 @@ where
 {
     base: Option<RendererBase>,
     jobs: HashMap<SurfaceId, RenderJob<B>>,
+    /// Textures of the images, which are shared by the surfaces
+    textures: TextureCache,
 }
 impl<B> Renderer<B>
 where
@@ -85,8 +90,22 @@ where
         Renderer {
             base: None,
             jobs: HashMap::new(),
+            textures: TextureCache::new(DEFAULT_TEXTURE_BUDGET),
         }
     }
+
+    /// Limits the memory the textures of images take up in bytes, the default is
+    /// [DEFAULT_TEXTURE_BUDGET]. Images that are still mounted are never evicted,
+    /// so the budget may be exceeded.
+    pub fn with_texture_budget(mut self, budget: usize) -> Self {
+        self.textures.set_budget(budget);
+        self
+    }
+
+    /// Memory the textures of images take up in bytes
+    pub fn texture_bytes(&self) -> usize {
+        self.textures.bytes()
+    }
 }
 impl<B> Default for Renderer<B>
 where
@@ -97,6 +116,36 @@ where
     }
 }
 
+/// Color attachment that renders into the view, through the multisampling framebuffer
+/// if multisampling is enabled
+fn color_attachment<'a, B>(
+    job: &'a RenderJob<B>,
+    view: &'a wgpu::TextureView,
+    clear: Color,
+) -> wgpu::RenderPassColorAttachment<'a>
+where
+    B: Backend,
+{
+    match job.msaa {
+        MSAA::X1 => wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(clear),
+                store: true,
+            },
+        },
+        _ => wgpu::RenderPassColorAttachment {
+            view: job.multisampling_framebuffer.as_ref().unwrap(),
+            resolve_target: Some(view),
+            ops: Operations {
+                load: LoadOp::Clear(clear),
+                store: true,
+            },
+        },
+    }
+}
+
 impl<B> crate::renderer::Renderer<B> for Renderer<B>
 where
     B: Backend,
@@ -108,13 +157,14 @@ where
         &mut self,
         surface: &rui_io::surface::Surface,
         node: &mut Node,
+        values: &AnimatedValues,
     ) -> Result<(), Self::Error> {
         let sid = surface.id();
-        let (job, base) = match self.jobs.get_mut(&sid) {
-            None => {
+        let (job, base) = match self.jobs.entry(sid) {
+            Entry::Vacant(entry) => {
                 // Dynamically creating render base for the first surface that gets mounted
-                let surface_handle = match &mut self.base {
-                    Some(base) => unsafe { base.instance.create_surface(surface) },
+                let (surface_handle, base) = match &mut self.base {
+                    Some(base) => (unsafe { base.instance.create_surface(surface) }, &*base),
                     rb @ None => {
                         //let backend_bits =
                         //    wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all);
@@ -124,16 +174,15 @@ where
                         // Creating a surface first before creating the base, so that
                         // the base can use it to find a suitable adapter (GPU)
                         let first_surface = unsafe { instance.create_surface(surface) };
-                        *rb = Some(pollster::block_on(RendererBase::new(
+                        let base = pollster::block_on(RendererBase::new(
                             backend_bits,
                             instance,
                             &first_surface,
-                        ))?);
-                        first_surface
+                        ))?;
+                        (first_surface, &*rb.insert(base))
                     }
                 };
                 let size = surface.inner_size();
-                let base = self.base.as_ref().unwrap();
                 let swapchain_format = surface_handle.get_supported_formats(&base.adapter)[0];
                 let config = wgpu::SurfaceConfiguration {
                     usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -144,18 +193,25 @@ where
                     alpha_mode: CompositeAlphaMode::Auto,
                 };
                 surface_handle.configure(&base.device, &config);
-                self.jobs.insert(
-                    sid,
-                    RenderJob::new(&base.device, config, surface_handle, MSAA::X4),
-                );
-                (self.jobs.get_mut(&sid).unwrap(), base)
+                let job = RenderJob::new(&base.device, config, surface_handle, MSAA::X4);
+                (entry.insert(job), base)
             }
-            Some(sh) => (sh, self.base.as_ref().unwrap()),
+            Entry::Occupied(entry) => (
+                entry.into_mut(),
+                self.base.as_ref().ok_or(RendererError::NotMounted)?,
+            ),
         };
         //Creation of rendering objects
 
-        pollster::block_on(job.mount(&base.device, &base.queue, node));
-        Ok(())
+        let mounted = pollster::block_on(job.mount(
+            &base.device,
+            &base.queue,
+            &mut self.textures,
+            node,
+            values,
+        ));
+        self.textures.trim();
+        mounted.map_err(RendererError::Mount)
     }
 
     fn resize(
@@ -164,8 +220,11 @@ where
         size: Extent,
     ) -> Result<(), Self::Error> {
         bs!(resize);
-        let job = self.jobs.get_mut(&surface.id()).unwrap();
-        let base = self.base.as_mut().unwrap();
+        let job = self
+            .jobs
+            .get_mut(&surface.id())
+            .ok_or(RendererError::UnknownSurface)?;
+        let base = self.base.as_mut().ok_or(RendererError::NotMounted)?;
         job.resize(&base.device, &base.queue, size);
         be!(resize);
         Ok(())
@@ -173,19 +232,25 @@ where
 
     fn render(&mut self, surface: &rui_io::surface::Surface) -> Result<(), Self::Error> {
         bs!(render_time);
-        let base = self
-            .base
-            .as_ref()
-            .expect("Can't render with no surface mounted!");
-        let job = self.jobs.get(&surface.id()).expect("Invalid surface id!");
+        let base = self.base.as_ref().ok_or(RendererError::NotMounted)?;
+        let job = self
+            .jobs
+            .get(&surface.id())
+            .ok_or(RendererError::UnknownSurface)?;
         let frame = match job.surface.get_current_texture() {
             Ok(frame) => frame,
-            Err(_) => {
+            // The swapchain has to be configured again, e.g. after the surface changed
+            Err(SurfaceError::Lost | SurfaceError::Outdated) => {
                 job.surface.configure(&base.device, &job.config);
-                job.surface
-                    .get_current_texture()
-                    .expect("Failed to acquire next surface texture!")
+                match job.surface.get_current_texture() {
+                    Ok(frame) => frame,
+                    Err(SurfaceError::OutOfMemory) => return Err(RendererError::OutOfMemory),
+                    Err(_) => return Err(RendererError::SurfaceLost),
+                }
             }
+            Err(SurfaceError::OutOfMemory) => return Err(RendererError::OutOfMemory),
+            // The frame is skipped, the next redraw tries again
+            Err(SurfaceError::Timeout) => return Ok(()),
         };
 
         let view = frame
@@ -211,49 +276,39 @@ where
             },
         };
 
-        let render_pass_color_attachment = match job.msaa {
-            MSAA::X1 => wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: Operations {
-                    load: LoadOp::Clear(Color::WHITE),
-                    store: true,
-                },
-            },
-            _ => wgpu::RenderPassColorAttachment {
-                view: job.multisampling_framebuffer.as_ref().unwrap(),
-                resolve_target: Some(&view),
-                ops: Operations {
-                    load: LoadOp::Clear(Color::WHITE),
-                    store: true,
-                },
-            },
-        };
+        let render_pass_color_attachment = color_attachment(job, &view, Color::WHITE);
 
         let mut encoder = base
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        {
-            /*let mut render_pass_compute =
-                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
-            job.record_compute(&mut render_pass_compute);
-             */
-            let mut pre_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Stencil pre pass"),
-                // We don't need color attachments in this pass
-                color_attachments: &[],
+        // Render the groups into their layers, which are composited in paint order.
+        // Nested groups and backdrops are composited into the layer of another group,
+        // therefore they are rendered first.
+        let layers = job.layers();
+        for &index in job.group_order() {
+            let layer = layers[index];
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Group pass"),
+                color_attachments: &[Some(color_attachment(job, &layer.view, Color::TRANSPARENT))],
                 depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                     view: &job.stencil_framebuffer,
                     depth_ops: None,
                     stencil_ops: Some(Operations {
                         load: LoadOp::Clear(0),
-                        store: true,
+                        store: false,
                     }),
                 }),
             });
-            job.record_prepass(&mut pre_pass);
+            job.record_group(index, &mut render_pass);
+            drop(render_pass);
+            job.blur_group(index, &base.device, &mut encoder);
         }
         {
+            /*let mut render_pass_compute =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            job.record_compute(&mut render_pass_compute);
+             */
+            // Paths are stencilled and covered batch by batch within this pass
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(render_pass_color_attachment)],
@@ -261,7 +316,7 @@ where
                     view: &job.stencil_framebuffer,
                     depth_ops: None,
                     stencil_ops: Some(Operations {
-                        load: LoadOp::Load,
+                        load: LoadOp::Clear(0),
                         store: false,
                     }),
                 }),
@@ -275,6 +330,38 @@ where
         Ok(())
     }
 
+    fn animate(&mut self, surface: &rui_io::surface::Surface, values: &AnimatedValues) -> bool {
+        match (self.jobs.get_mut(&surface.id()), &self.base) {
+            (Some(job), Some(base)) => job.animate(&base.queue, values),
+            _ => false,
+        }
+    }
+
+    fn read(
+        &self,
+        surface: &rui_io::surface::Surface,
+        key: u64,
+        property: &Property,
+    ) -> Option<Property> {
+        self.jobs.get(&surface.id())?.read(key, property)
+    }
+
+    fn is_mounted(&self, surface: &rui_io::surface::Surface, key: u64) -> bool {
+        self.jobs
+            .get(&surface.id())
+            .is_some_and(|job| job.is_mounted(key))
+    }
+
+    fn autoplay(&self, surface: &rui_io::surface::Surface, key: u64) -> Option<bool> {
+        self.jobs.get(&surface.id())?.autoplay(key)
+    }
+
+    fn is_playing(&self, surface: &rui_io::surface::Surface) -> bool {
+        self.jobs
+            .get(&surface.id())
+            .is_some_and(|job| job.is_playing())
+    }
+
     fn request_render(&self) -> Result<(), Self::Error> {
         for (_, _job) in &self.jobs {
             //job.surface_adapter.request_redraw();
@@ -0,0 +1,852 @@
+use crate::error::Error;
+use crate::util::{Frame, Resource};
+use image::imageops;
+use image::imageops::FilterType;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Memory the textures of images may take up by default, 256 MiB
+pub const DEFAULT_TEXTURE_BUDGET: usize = 256 << 20;
+/// Width and height of an atlas in texels
+const ATLAS_SIZE: u32 = 2048;
+/// Images up to this width and height are packed into atlases
+const MAX_ATLAS_IMAGE: u32 = 256;
+
+/// Texture of a single image or of an atlas of small images
+pub(crate) struct Texture {
+    #[allow(dead_code)]
+    texture: wgpu::Texture,
+    pub(crate) view: wgpu::TextureView,
+}
+
+/// Image that was uploaded to a texture
+pub(crate) struct CachedImage<T = Texture> {
+    pub(crate) texture: Arc<T>,
+    /// Bounds `[x, y, width, height]` of the image in texture coordinates
+    pub(crate) rect: [f32; 4],
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+struct Entry<T> {
+    /// Frames of the image, still images have one
+    frames: Vec<Arc<CachedImage<T>>>,
+    /// How long each frame is shown
+    delays: Vec<Duration>,
+    /// How often the frames are played, `None` repeats them forever
+    plays: Option<u32>,
+    /// Origin of each frame in its atlas, `None` for frames with a texture of their own
+    origins: Vec<Option<[u32; 2]>>,
+    /// Size of the textures of the frames, frames in an atlas count towards the atlas
+    bytes: usize,
+    /// Mount in which the image was used last
+    last_used: u64,
+}
+impl<T> Entry<T> {
+    /// Index of the frame that is shown after the image played for `time` and
+    /// whether other frames follow it
+    fn frame(&self, time: Duration) -> (usize, bool) {
+        let last = self.frames.len() - 1;
+        let duration: Duration = self.delays.iter().sum();
+        if last == 0 || duration.is_zero() {
+            return (0, false);
+        }
+        let play = time.as_nanos() / duration.as_nanos();
+        if matches!(self.plays, Some(plays) if play >= plays as u128) {
+            return (last, false);
+        }
+        let mut time = Duration::from_nanos((time.as_nanos() % duration.as_nanos()) as u64);
+        for (index, delay) in self.delays.iter().enumerate() {
+            if time < *delay {
+                return (index, true);
+            }
+            time -= *delay;
+        }
+        (last, true)
+    }
+
+    fn is_mounted(&self) -> bool {
+        self.frames.iter().any(|frame| Arc::strong_count(frame) > 1)
+    }
+
+    fn is_packed(&self) -> bool {
+        self.origins.iter().any(Option::is_some)
+    }
+}
+
+/// Row of images in an atlas, which is as high as the first image in it
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Start of the free space to the right of the images
+    x: u32,
+    /// Spans `[x, width]` of evicted images left of the free space, sorted by `x`
+    gaps: Vec<[u32; 2]>,
+}
+impl Shelf {
+    /// Takes the space for an image from the narrowest gap that it fits into
+    fn fill_gap(&mut self, width: u32) -> Option<u32> {
+        let index = (0..self.gaps.len())
+            .filter(|index| self.gaps[*index][1] >= width)
+            .min_by_key(|index| self.gaps[*index][1])?;
+        let [x, gap_width] = self.gaps[index];
+        if gap_width == width {
+            self.gaps.remove(index);
+        } else {
+            self.gaps[index] = [x + width, gap_width - width];
+        }
+        Some(x)
+    }
+
+    /// Returns a span to the shelf and merges it with its neighbours
+    fn free(&mut self, x: u32, width: u32) {
+        let index = self.gaps.partition_point(|gap| gap[0] < x);
+        self.gaps.insert(index, [x, width]);
+        if index + 1 < self.gaps.len() && x + width == self.gaps[index + 1][0] {
+            self.gaps[index][1] += self.gaps.remove(index + 1)[1];
+        }
+        if index > 0 && self.gaps[index - 1][0] + self.gaps[index - 1][1] == x {
+            self.gaps[index - 1][1] += self.gaps.remove(index)[1];
+        }
+        // A gap next to the free space becomes part of it
+        if let Some(&[x, width]) = self.gaps.last() {
+            if x + width == self.x {
+                self.x = x;
+                self.gaps.pop();
+            }
+        }
+    }
+}
+
+struct Atlas<T> {
+    texture: Arc<T>,
+    shelves: Vec<Shelf>,
+    /// Start of the free space below the shelves
+    bottom: u32,
+}
+impl<T> Atlas<T> {
+    /// Finds space for an image, preferring gaps of evicted images and then the lowest
+    /// shelf that it fits into
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && shelf.gaps.iter().any(|gap| gap[1] >= width))
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = shelf {
+            return Some([shelf.fill_gap(width)?, shelf.y]);
+        }
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && shelf.x + width <= ATLAS_SIZE)
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = shelf {
+            shelf.x += width;
+            return Some([shelf.x - width, shelf.y]);
+        }
+        if width > ATLAS_SIZE || self.bottom + height > ATLAS_SIZE {
+            return None;
+        }
+        let y = self.bottom;
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+            gaps: vec![],
+        });
+        self.bottom += height;
+        Some([0, y])
+    }
+
+    /// Returns the space of an evicted image. Empty shelves at the bottom are removed,
+    /// so that images of any height can use their space again.
+    fn free(&mut self, [x, y]: [u32; 2], width: u32) {
+        if let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == y) {
+            shelf.free(x, width);
+        }
+        while let Some(shelf) = self.shelves.last() {
+            if shelf.x > 0 {
+                break;
+            }
+            self.bottom = shelf.y;
+            self.shelves.pop();
+        }
+    }
+}
+
+/// Textures of the images of all surfaces of a renderer, so that an image is only
+/// loaded once while it's in use. Small images are packed into atlases, which lets
+/// them share a bind group.
+///
+/// When the textures exceed the budget after a mount, the images that weren't used
+/// for the longest time and aren't mounted anymore are evicted. Evicting images from
+/// an atlas only frees memory once all of them are evicted, so atlases are evicted
+/// as a whole. Without space for another atlas, new small images take the space of
+/// unused images in the existing atlases instead.
+///
+/// Packing and eviction don't depend on the kind of texture, so they also work
+/// without a GPU.
+pub(crate) struct TextureCache<T = Texture> {
+    /// Images by their resource and whether they have mipmaps
+    entries: HashMap<(Resource, bool), Entry<T>>,
+    atlases: Vec<Atlas<T>>,
+    budget: usize,
+    /// Counts the mounts to find the least recently used images
+    mount: u64,
+}
+impl<T> TextureCache<T> {
+    pub(crate) fn new(budget: usize) -> Self {
+        TextureCache {
+            entries: HashMap::new(),
+            atlases: vec![],
+            budget,
+            mount: 0,
+        }
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+    }
+
+    /// Memory the textures take up in bytes
+    pub(crate) fn bytes(&self) -> usize {
+        let atlas_bytes = 4 * (ATLAS_SIZE * ATLAS_SIZE) as usize;
+        let image_bytes: usize = self.entries.values().map(|entry| entry.bytes).sum();
+        image_bytes + self.atlases.len() * atlas_bytes
+    }
+
+    /// Evicts images until the textures fit into the budget and starts the next mount.
+    /// Images of the current mount and images that are still mounted are kept.
+    pub(crate) fn trim(&mut self) {
+        // Only evictions that free memory are candidates, which are images with textures
+        // of their own and atlases whose images are all unused
+        let mut candidates: Vec<(u64, Vec<(Resource, bool)>)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_packed() && self.is_unused(entry))
+            .map(|(key, entry)| (entry.last_used, vec![key.clone()]))
+            .collect();
+        for atlas in &self.atlases {
+            let images: Vec<_> = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| {
+                    let mut frames = entry.frames.iter();
+                    frames.any(|frame| Arc::ptr_eq(&frame.texture, &atlas.texture))
+                })
+                .collect();
+            if images.iter().all(|(_, entry)| self.is_unused(entry)) {
+                let last_used = images.iter().map(|(_, entry)| entry.last_used).max();
+                let keys = images.into_iter().map(|(key, _)| key.clone()).collect();
+                candidates.push((last_used.unwrap_or(0), keys));
+            }
+        }
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+        for (_, keys) in candidates {
+            if self.bytes() <= self.budget {
+                break;
+            }
+            for key in keys {
+                self.evict(&key);
+            }
+        }
+        self.mount += 1;
+    }
+
+    /// Whether the image wasn't used by the current mount and isn't mounted anymore
+    fn is_unused(&self, entry: &Entry<T>) -> bool {
+        entry.last_used < self.mount && !entry.is_mounted()
+    }
+
+    /// Removes an image, see [TextureCache::release]
+    fn evict(&mut self, key: &(Resource, bool)) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.release(entry);
+        }
+    }
+
+    /// Returns the space of the frames of an image in the atlases and drops the
+    /// atlases that are empty afterwards
+    fn release(&mut self, entry: Entry<T>) {
+        for (frame, origin) in entry.frames.iter().zip(&entry.origins) {
+            let mut atlases = self.atlases.iter_mut();
+            let atlas = atlases.find(|atlas| Arc::ptr_eq(&atlas.texture, &frame.texture));
+            if let (Some(atlas), Some(origin)) = (atlas, origin) {
+                atlas.free(*origin, frame.width);
+            }
+        }
+        drop(entry);
+        // Atlases are only referenced by themselves once their images are gone
+        self.atlases
+            .retain(|atlas| Arc::strong_count(&atlas.texture) > 1);
+    }
+
+    /// Space for a small image in one of the atlases. Unused images of the atlases are
+    /// evicted to make room if another atlas would exceed the budget.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(usize, [u32; 2])> {
+        let allocate = |atlases: &mut Vec<Atlas<T>>| {
+            let mut atlases = atlases.iter_mut().enumerate();
+            atlases.find_map(|(index, atlas)| Some((index, atlas.allocate(width, height)?)))
+        };
+        if let Some(allocation) = allocate(&mut self.atlases) {
+            return Some(allocation);
+        }
+        if self.bytes() + 4 * (ATLAS_SIZE * ATLAS_SIZE) as usize <= self.budget {
+            return None;
+        }
+        let mut candidates: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_packed() && self.is_unused(entry))
+            .map(|(key, entry)| (entry.last_used, key.clone()))
+            .collect();
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+        for (_, key) in candidates {
+            self.evict(&key);
+            if let Some(allocation) = allocate(&mut self.atlases) {
+                return Some(allocation);
+            }
+        }
+        None
+    }
+
+    /// Space for a small image in one of the atlases. Without space in the existing
+    /// atlases another one with the texture is added.
+    fn atlas_space(
+        &mut self,
+        width: u32,
+        height: u32,
+        texture: impl FnOnce() -> T,
+    ) -> Result<(usize, [u32; 2]), Error> {
+        if let Some(allocation) = self.allocate(width, height) {
+            return Ok(allocation);
+        }
+        let mut atlas = Atlas {
+            texture: Arc::new(texture()),
+            shelves: vec![],
+            bottom: 0,
+        };
+        let origin = atlas.allocate(width, height).ok_or_else(|| {
+            Error::Renderer(format!(
+                "a {}x{} image doesn't fit into an atlas",
+                width, height
+            ))
+        })?;
+        self.atlases.push(atlas);
+        Ok((self.atlases.len() - 1, origin))
+    }
+}
+
+impl TextureCache {
+    /// Loads the resource and uploads all of its frames, unless it's cached already.
+    /// Mipmapped images get textures of their own.
+    ///
+    /// Fails if the resource can't be loaded, the image is empty or larger than the
+    /// textures of the device or the GPU runs out of memory.
+    pub(crate) async fn load(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resource: &Resource,
+        mipmapped: bool,
+    ) -> Result<(), Error> {
+        let key = (resource.clone(), mipmapped);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.last_used = self.mount;
+            return Ok(());
+        }
+        let decoded = resource.decode().await?;
+        let max = device.limits().max_texture_dimension_2d;
+        for frame in &decoded.frames {
+            if frame.width == 0 || frame.height == 0 {
+                return Err(Error::DecodeFailed("the image is empty".to_string()));
+            }
+            if frame.width > max || frame.height > max {
+                return Err(Error::DecodeFailed(format!(
+                    "the image is {}x{} pixels, textures can't be larger than {}x{}",
+                    frame.width, frame.height, max, max
+                )));
+            }
+        }
+        // Textures that don't fit into the memory of the GPU or that wgpu rejects fail
+        // the mount instead of the device
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut entry = Entry {
+            frames: Vec::with_capacity(decoded.frames.len()),
+            delays: Vec::with_capacity(decoded.frames.len()),
+            origins: Vec::with_capacity(decoded.frames.len()),
+            plays: decoded.plays,
+            bytes: 0,
+            last_used: self.mount,
+        };
+        for frame in decoded.frames {
+            let (width, height) = (frame.width, frame.height);
+            entry.delays.push(frame.delay);
+            let image = match width <= MAX_ATLAS_IMAGE && height <= MAX_ATLAS_IMAGE {
+                true if !mipmapped => {
+                    match self.pack(device, queue, &frame.pixels, width, height) {
+                        Ok((image, origin)) => {
+                            entry.origins.push(Some(origin));
+                            image
+                        }
+                        Err(err) => {
+                            device.pop_error_scope().await;
+                            device.pop_error_scope().await;
+                            self.release(entry);
+                            return Err(err);
+                        }
+                    }
+                }
+                _ => {
+                    let (image, bytes) = Self::upload(device, queue, frame, mipmapped);
+                    entry.bytes += bytes;
+                    entry.origins.push(None);
+                    image
+                }
+            };
+            entry.frames.push(Arc::new(image));
+        }
+        let invalid = device.pop_error_scope().await;
+        let out_of_memory = device.pop_error_scope().await.is_some();
+        if out_of_memory || invalid.is_some() {
+            // The atlases keep no space for an image that failed
+            self.release(entry);
+            return match invalid {
+                Some(err) if !out_of_memory => Err(Error::Renderer(err.to_string())),
+                _ => Err(Error::OutOfMemory),
+            };
+        }
+        self.entries.insert(key, entry);
+        Ok(())
+    }
+
+    /// The frame of the resource that is shown after it played for `time` and whether
+    /// other frames follow it. The resource is loaded first, see [TextureCache::load].
+    pub(crate) async fn get(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resource: &Resource,
+        mipmapped: bool,
+        time: Duration,
+    ) -> Result<(Arc<CachedImage>, bool), Error> {
+        self.load(device, queue, resource, mipmapped).await?;
+        let entry = self
+            .entries
+            .get_mut(&(resource.clone(), mipmapped))
+            .unwrap();
+        entry.last_used = self.mount;
+        let (index, playing) = entry.frame(time);
+        Ok((entry.frames[index].clone(), playing))
+    }
+
+    /// Copies a small image into the first atlas with enough space, returns it with
+    /// its origin in the atlas
+    fn pack(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<(CachedImage, [u32; 2]), Error> {
+        let (index, [x, y]) = self.atlas_space(width, height, || {
+            Self::texture(device, ATLAS_SIZE, ATLAS_SIZE, 1)
+        })?;
+        let texture = self.atlases[index].texture.clone();
+        Self::write(queue, &texture.texture, 0, [x, y], rgba, width, height);
+        let size = ATLAS_SIZE as f32;
+        let image = CachedImage {
+            texture,
+            rect: [
+                x as f32 / size,
+                y as f32 / size,
+                width as f32 / size,
+                height as f32 / size,
+            ],
+            width,
+            height,
+        };
+        Ok((image, [x, y]))
+    }
+
+    /// Uploads an image to a texture of its own, returns it with the size of the
+    /// texture in bytes
+    fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        frame: Frame<'_>,
+        mipmapped: bool,
+    ) -> (CachedImage, usize) {
+        let (width, height) = (frame.width, frame.height);
+        // Mipmaps go down to a single texel
+        let levels = match mipmapped {
+            true => 32 - width.max(height).max(1).leading_zeros(),
+            false => 1,
+        };
+        let texture = Self::texture(device, width, height, levels);
+        let mut bytes = 0;
+        for (level, (rgba, width, height)) in Self::mipmaps(frame.pixels, width, height, levels)
+            .into_iter()
+            .enumerate()
+        {
+            Self::write(
+                queue,
+                &texture.texture,
+                level as u32,
+                [0, 0],
+                &rgba,
+                width,
+                height,
+            );
+            bytes += rgba.len();
+        }
+        let image = CachedImage {
+            texture: Arc::new(texture),
+            rect: [0.0, 0.0, 1.0, 1.0],
+            width,
+            height,
+        };
+        (image, bytes)
+    }
+
+    fn texture(device: &wgpu::Device, width: u32, height: u32, levels: u32) -> Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // Most images are stored using sRGB so we need to reflect that here.
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            // TEXTURE_BINDING tells wgpu that we want to use this texture in shaders
+            // COPY_DST means that we want to copy data to this texture
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("texture"),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Texture { texture, view }
+    }
+
+    /// Copies the pixels to a region of a mip level of the texture
+    fn write(
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        level: u32,
+        [x, y]: [u32; 2],
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+    ) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: level,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width),
+                rows_per_image: std::num::NonZeroU32::new(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// The image followed by `levels - 1` copies, each half the size of the previous
+    fn mipmaps(
+        rgba: Cow<'_, [u8]>,
+        width: u32,
+        height: u32,
+        levels: u32,
+    ) -> Vec<(Cow<'_, [u8]>, u32, u32)> {
+        let mut mipmaps = Vec::with_capacity(levels as usize);
+        if levels > 1 {
+            let image = image::RgbaImage::from_raw(width, height, rgba.to_vec()).unwrap();
+            for level in 1..levels {
+                let (width, height) = ((width >> level).max(1), (height >> level).max(1));
+                let mipmap = imageops::resize(&image, width, height, FilterType::Triangle);
+                mipmaps.push((Cow::Owned(mipmap.into_raw()), width, height));
+            }
+        }
+        mipmaps.insert(0, (rgba, width, height));
+        mipmaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Atlas, CachedImage, Entry, Shelf, TextureCache, ATLAS_SIZE, MAX_ATLAS_IMAGE};
+    use crate::util::Resource;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const ATLAS_BYTES: usize = 4 * (ATLAS_SIZE * ATLAS_SIZE) as usize;
+
+    fn key(id: u32) -> (Resource, bool) {
+        (Resource::Path(id.to_string().into()), false)
+    }
+
+    fn entry(frames: Vec<Arc<CachedImage<()>>>, origins: Vec<Option<[u32; 2]>>) -> Entry<()> {
+        Entry {
+            delays: vec![Duration::ZERO; frames.len()],
+            frames,
+            plays: None,
+            origins,
+            bytes: 0,
+            last_used: 0,
+        }
+    }
+
+    fn image(texture: Arc<()>, width: u32, height: u32) -> Arc<CachedImage<()>> {
+        Arc::new(CachedImage {
+            texture,
+            rect: [0.0; 4],
+            width,
+            height,
+        })
+    }
+
+    /// Packs an image into the atlases and returns its frame, which keeps it mounted
+    fn packed(
+        cache: &mut TextureCache<()>,
+        id: u32,
+        width: u32,
+        height: u32,
+    ) -> Arc<CachedImage<()>> {
+        let (index, origin) = cache.atlas_space(width, height, || ()).unwrap();
+        let frame = image(cache.atlases[index].texture.clone(), width, height);
+        let mut entry = entry(vec![frame.clone()], vec![Some(origin)]);
+        entry.last_used = cache.mount;
+        cache.entries.insert(key(id), entry);
+        frame
+    }
+
+    /// Adds an image with a texture of its own of the size
+    fn own(cache: &mut TextureCache<()>, id: u32, bytes: usize) -> Arc<CachedImage<()>> {
+        let frame = image(Arc::new(()), 1, 1);
+        let mut entry = entry(vec![frame.clone()], vec![None]);
+        entry.bytes = bytes;
+        entry.last_used = cache.mount;
+        cache.entries.insert(key(id), entry);
+        frame
+    }
+
+    fn atlas() -> Atlas<()> {
+        Atlas {
+            texture: Arc::new(()),
+            shelves: vec![],
+            bottom: 0,
+        }
+    }
+
+    #[test]
+    fn shelves_fill_the_narrowest_gap() {
+        let mut shelf = Shelf {
+            y: 0,
+            height: 10,
+            x: 100,
+            gaps: vec![[0, 30], [40, 10], [60, 20]],
+        };
+        assert_eq!(shelf.fill_gap(10), Some(40));
+        assert_eq!(shelf.gaps, [[0, 30], [60, 20]]);
+        assert_eq!(shelf.fill_gap(15), Some(60));
+        assert_eq!(shelf.gaps, [[0, 30], [75, 5]]);
+        assert_eq!(shelf.fill_gap(31), None);
+    }
+
+    #[test]
+    fn freed_spans_merge_with_their_neighbours() {
+        let mut shelf = Shelf {
+            y: 0,
+            height: 10,
+            x: 100,
+            gaps: vec![],
+        };
+        shelf.free(10, 10);
+        shelf.free(40, 10);
+        assert_eq!(shelf.gaps, [[10, 10], [40, 10]]);
+        // Joins both neighbours
+        shelf.free(20, 20);
+        assert_eq!(shelf.gaps, [[10, 40]]);
+        shelf.free(0, 10);
+        assert_eq!(shelf.gaps, [[0, 50]]);
+        // Joins the free space, which takes the gap along
+        shelf.free(50, 50);
+        assert!(shelf.gaps.is_empty());
+        assert_eq!(shelf.x, 0);
+    }
+
+    #[test]
+    fn atlases_stack_shelves_and_reuse_their_space() {
+        let mut atlas = atlas();
+        assert_eq!(atlas.allocate(100, 50), Some([0, 0]));
+        assert_eq!(atlas.allocate(100, 40), Some([100, 0]));
+        // Too high for the first shelf
+        assert_eq!(atlas.allocate(100, 60), Some([0, 50]));
+        assert_eq!(atlas.bottom, 110);
+        // Prefers the lowest shelf that fits
+        assert_eq!(atlas.allocate(10, 10), Some([200, 0]));
+
+        atlas.free([100, 0], 100);
+        assert_eq!(atlas.allocate(50, 50), Some([100, 0]));
+        assert_eq!(atlas.shelves[0].gaps, [[150, 50]]);
+
+        // Freeing the bottom shelf removes it, so that taller images fit again
+        atlas.free([0, 50], 100);
+        assert_eq!(atlas.shelves.len(), 1);
+        assert_eq!(atlas.bottom, 50);
+        assert_eq!(atlas.allocate(ATLAS_SIZE, ATLAS_SIZE - 50), Some([0, 50]));
+        assert_eq!(atlas.allocate(1, 1), Some([150, 0]));
+        assert_eq!(atlas.allocate(ATLAS_SIZE, 1), None);
+    }
+
+    #[test]
+    fn emptied_shelves_are_removed_from_the_bottom_up() {
+        let mut atlas = atlas();
+        atlas.allocate(10, 10);
+        atlas.allocate(10, 20);
+        atlas.allocate(10, 30);
+        // The middle shelf stays while the one below is in use
+        atlas.free([0, 10], 10);
+        assert_eq!(atlas.shelves.len(), 3);
+        atlas.free([0, 30], 10);
+        assert_eq!(atlas.shelves.len(), 1);
+        assert_eq!(atlas.bottom, 10);
+        atlas.free([0, 0], 10);
+        assert!(atlas.shelves.is_empty());
+        assert_eq!(atlas.bottom, 0);
+    }
+
+    #[test]
+    fn frames_follow_the_delays() {
+        let ms = Duration::from_millis;
+        let frames = (0..3).map(|_| image(Arc::new(()), 1, 1)).collect();
+        let mut animated = entry(frames, vec![None; 3]);
+        animated.delays = vec![ms(100), ms(200), ms(100)];
+        assert_eq!(animated.frame(ms(0)), (0, true));
+        assert_eq!(animated.frame(ms(150)), (1, true));
+        assert_eq!(animated.frame(ms(399)), (2, true));
+        // Repeats forever
+        assert_eq!(animated.frame(ms(4050)), (0, true));
+        // Stops at the last frame after the plays
+        animated.plays = Some(2);
+        assert_eq!(animated.frame(ms(750)), (2, true));
+        assert_eq!(animated.frame(ms(800)), (2, false));
+
+        animated.delays = vec![Duration::ZERO; 3];
+        assert_eq!(animated.frame(ms(100)), (0, false));
+        let still = entry(vec![image(Arc::new(()), 1, 1)], vec![None]);
+        assert_eq!(still.frame(ms(100)), (0, false));
+    }
+
+    #[test]
+    fn evicting_all_images_of_an_atlas_drops_it() {
+        let mut cache = TextureCache::<()>::new(0);
+        let a = packed(&mut cache, 0, 100, 100);
+        let b = packed(&mut cache, 1, 100, 100);
+        assert!(Arc::ptr_eq(&a.texture, &b.texture));
+        assert_eq!(cache.bytes(), ATLAS_BYTES);
+        drop((a, b));
+        cache.evict(&key(0));
+        assert_eq!(cache.atlases.len(), 1);
+        // The space of the evicted image is used again
+        let c = packed(&mut cache, 2, 100, 100);
+        assert_eq!(cache.entries[&key(2)].origins, [Some([0, 0])]);
+        drop(c);
+        cache.evict(&key(1));
+        cache.evict(&key(2));
+        assert!(cache.atlases.is_empty());
+        assert_eq!(cache.bytes(), 0);
+    }
+
+    #[test]
+    fn trim_evicts_the_least_recently_used_images() {
+        let mut cache = TextureCache::<()>::new(250);
+        drop(own(&mut cache, 0, 100));
+        cache.trim();
+        drop(own(&mut cache, 1, 100));
+        let mounted = own(&mut cache, 2, 100);
+        cache.trim();
+        // Images of the current mount are kept even over the budget
+        drop(own(&mut cache, 3, 100));
+        assert_eq!(cache.bytes(), 300);
+        cache.trim();
+        assert!(!cache.entries.contains_key(&key(0)));
+        assert!(!cache.entries.contains_key(&key(1)));
+        assert!(cache.entries.contains_key(&key(2)));
+        assert!(cache.entries.contains_key(&key(3)));
+
+        // Mounted images stay
+        cache.set_budget(0);
+        cache.trim();
+        assert_eq!(cache.entries.len(), 1);
+        drop(mounted);
+        cache.trim();
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn atlases_are_evicted_as_a_whole() {
+        let mut cache = TextureCache::<()>::new(0);
+        drop(packed(&mut cache, 0, 10, 10));
+        let mounted = packed(&mut cache, 1, 10, 10);
+        cache.trim();
+        cache.trim();
+        assert_eq!(cache.entries.len(), 2);
+        drop(mounted);
+        cache.trim();
+        assert!(cache.entries.is_empty());
+        assert!(cache.atlases.is_empty());
+    }
+
+    #[test]
+    fn full_atlases_take_the_space_of_unused_images_within_the_budget() {
+        let mut cache = TextureCache::<()>::new(ATLAS_BYTES);
+        let per_row = ATLAS_SIZE / MAX_ATLAS_IMAGE;
+        let mut frames = vec![];
+        for id in 0..per_row * per_row {
+            frames.push(packed(&mut cache, id, MAX_ATLAS_IMAGE, MAX_ATLAS_IMAGE));
+        }
+        assert_eq!(cache.atlases.len(), 1);
+        cache.trim();
+        cache.trim();
+        // The oldest unused image makes room
+        drop(frames.remove(5));
+        drop(frames.remove(2));
+        cache.entries.get_mut(&key(2)).unwrap().last_used = 0;
+        cache.entries.get_mut(&key(5)).unwrap().last_used = 1;
+        let allocation = cache.allocate(MAX_ATLAS_IMAGE, MAX_ATLAS_IMAGE);
+        assert_eq!(allocation, Some((0, [2 * MAX_ATLAS_IMAGE, 0])));
+        assert!(!cache.entries.contains_key(&key(2)));
+        assert!(cache.entries.contains_key(&key(5)));
+
+        // With room in the budget another atlas is added instead
+        cache.set_budget(2 * ATLAS_BYTES);
+        assert_eq!(cache.allocate(MAX_ATLAS_IMAGE, MAX_ATLAS_IMAGE), None);
+        let (index, _) = cache
+            .atlas_space(MAX_ATLAS_IMAGE, MAX_ATLAS_IMAGE, || ())
+            .unwrap();
+        assert_eq!(index, 1);
+        assert!(cache.entries.contains_key(&key(5)));
+    }
+
+    #[test]
+    fn images_larger_than_an_atlas_fail() {
+        let mut cache = TextureCache::<()>::new(0);
+        assert!(cache.atlas_space(ATLAS_SIZE + 1, 1, || ()).is_err());
+    }
+}
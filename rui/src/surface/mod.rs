@@ -4,10 +4,11 @@ use raw_window_handle::RawWindowHandle;
 
 use crate::error::Error;
 pub use builder::Builder as SurfaceBuilder;
-use rui_io::surface::{SurfaceAttributes, SurfaceId};
+use rui_io::surface::{SurfaceAttributes, SurfaceId, TiledEdges};
 use rui_util::Extent;
 pub(crate) use shared::SharedState as SurfaceSharedState;
 
+use crate::instance::SurfaceRequest;
 use crate::reactor::Reactor;
 use crate::Node;
 
@@ -28,6 +29,8 @@ impl Surface {
         SurfaceBuilder::new()
     }
 
+    /// Shows the node on the surface. If an image can't be loaded or the surface can't
+    /// be rendered the error is returned and the previously mounted node stays.
     pub async fn mount(&self, node: Node) -> Result<(), Error> {
         Reactor::get().shared.mount(self.id(), node).await
     }
@@ -40,8 +43,71 @@ impl Surface {
         self.shared_state.read().unwrap().id
     }
 
+    /// Requests that the surface is rendered again. Multiple requests before the
+    /// next frame are coalesced into a single redraw.
     pub fn request_redraw(&self) {
-        todo!()
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::Redraw);
+    }
+
+    pub fn set_title(&self, title: impl Into<String>) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::SetTitle(title.into()));
+    }
+
+    pub fn set_min_size(&self, size: Extent) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::SetMinSize(size));
+    }
+
+    pub fn set_max_size(&self, size: Extent) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::SetMaxSize(size));
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::SetFullscreen(fullscreen));
+    }
+
+    pub fn set_maximized(&self, maximized: bool) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::SetMaximized(maximized));
+    }
+
+    pub fn minimize(&self) {
+        Reactor::get()
+            .shared
+            .update_surface(self.id(), SurfaceRequest::Minimize);
+    }
+
+    /// Whether the surface has the keyboard focus, e.g. to restyle an unfocused window
+    pub fn is_focused(&self) -> bool {
+        self.shared_state.read().unwrap().focused
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.shared_state.read().unwrap().maximized
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.shared_state.read().unwrap().fullscreen
+    }
+
+    pub fn tiled_edges(&self) -> TiledEdges {
+        self.shared_state.read().unwrap().tiled
+    }
+
+    /// Whether the surface is hidden from the user. Rendering is paused while the
+    /// surface is suspended.
+    pub fn is_suspended(&self) -> bool {
+        self.shared_state.read().unwrap().suspended
     }
 
     pub fn raw_handle(&self) -> RawWindowHandle {
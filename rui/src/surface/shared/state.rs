@@ -1,12 +1,17 @@
 use raw_window_handle::RawWindowHandle;
 
-use rui_io::surface::{SurfaceAttributes, SurfaceId};
+use rui_io::surface::{SurfaceAttributes, SurfaceEvent, SurfaceId, TiledEdges};
 
 #[allow(dead_code)]
 pub struct State {
     pub(crate) id: SurfaceId,
     pub(crate) attr: SurfaceAttributes,
     pub(crate) raw_handle: RawWindowHandle,
+    pub(crate) focused: bool,
+    pub(crate) maximized: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) tiled: TiledEdges,
+    pub(crate) suspended: bool,
 }
 unsafe impl Send for State {}
 unsafe impl Sync for State {}
@@ -16,6 +21,23 @@ impl State {
             id,
             attr,
             raw_handle,
+            focused: false,
+            maximized: false,
+            fullscreen: false,
+            tiled: TiledEdges::default(),
+            suspended: false,
+        }
+    }
+
+    /// Keeps track of the window state changes reported by the event loop
+    pub fn update(&mut self, event: &SurfaceEvent) {
+        match event {
+            SurfaceEvent::Focused(focused) => self.focused = *focused,
+            SurfaceEvent::Maximized(maximized) => self.maximized = *maximized,
+            SurfaceEvent::Fullscreen(fullscreen) => self.fullscreen = *fullscreen,
+            SurfaceEvent::Tiled(tiled) => self.tiled = *tiled,
+            SurfaceEvent::Suspended(suspended) => self.suspended = *suspended,
+            _ => {}
         }
     }
 }
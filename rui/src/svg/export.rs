@@ -0,0 +1,678 @@
+use crate::math::Transform;
+use crate::node::path::{FillRule, PathNode};
+use crate::node::{LineCap, LineJoin, Node};
+use crate::util::{Color, Flags, Paint, PathSegment, Rect, Resource, Spread};
+use async_recursion::async_recursion;
+use image::codecs::png::PngEncoder;
+use image::io::Reader;
+use image::{ColorType, ImageEncoder, ImageFormat};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::io::Cursor;
+
+/// Serializes the node tree as it is laid out into an SVG document, in which one unit
+/// is one pixel of a surface of `width` by `height` pixels. Like the renderer, the
+/// opacities and transforms of the ancestors are applied to each node, so the
+/// document doesn't depend on how a viewer composes groups.
+///
+/// Rectangles, paths, images and compositions are exported with their paint, opacity
+/// and transform, images in memory are embedded. Conic gradients are replaced by the
+/// color of their first stop and nine-slice images are stretched as a whole. Text,
+/// clips, shadows and blurs are left out.
+pub async fn export(node: &mut Node, width: u32, height: u32) -> String {
+    let mut document = Document {
+        root: Rect::new(0, 0, width, height),
+        defs: String::new(),
+        body: String::new(),
+        depth: 1,
+        ids: 0,
+    };
+    document.node(node, 1.0, Transform::IDENTITY).await;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    if !document.defs.is_empty() {
+        svg.push_str("  <defs>\n");
+        svg.push_str(&document.defs);
+        svg.push_str("  </defs>\n");
+    }
+    svg.push_str(&document.body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+struct Document {
+    root: Rect,
+    /// Gradients and clip paths, which are referenced by their id
+    defs: String,
+    body: String,
+    /// Indentation of the next line of the body
+    depth: usize,
+    ids: usize,
+}
+impl Document {
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.body.push_str("  ");
+        }
+        self.body.push_str(line);
+        self.body.push('\n');
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.ids += 1;
+        format!("{}{}", prefix, self.ids)
+    }
+
+    /// Maps the coordinates of paths to pixels
+    fn path_to_surface(&self) -> Transform {
+        let width = self.root.extent.width as f32;
+        let height = self.root.extent.height as f32;
+        Transform::new(
+            height / 2.0,
+            0.0,
+            0.0,
+            -height / 2.0,
+            width / 2.0,
+            height / 2.0,
+        )
+    }
+
+    #[async_recursion]
+    async fn node(&mut self, node: &mut Node, opacity: f32, parent: Transform) {
+        let base = node.base_mut();
+        let opacity = opacity * base.opacity;
+        let transform = base.world_transform(&parent);
+        match base.group_opacity {
+            // The children are composited first and faded as a whole
+            Some(group_opacity) => {
+                self.line(&format!(
+                    "<g opacity=\"{}\">",
+                    number(opacity * group_opacity)
+                ));
+                self.depth += 1;
+                self.content(node, 1.0, transform).await;
+                self.depth -= 1;
+                self.line("</g>");
+            }
+            None => self.content(node, opacity, transform).await,
+        }
+    }
+
+    #[async_recursion]
+    async fn content(&mut self, node: &mut Node, opacity: f32, transform: Transform) {
+        if node.base_mut().flags.test(Flags::TRANSPARENT) {
+            // Only the children of transparent nodes are painted
+            if let Node::Rectangle(_) | Node::Path(..) | Node::Image(..) | Node::Text(..) = node {
+                return;
+            }
+        }
+        match node {
+            Node::Rectangle(base) => {
+                let (bounds, radii) =
+                    self.rounded_bounds(&base.rect(&self.root), &base.border_radii);
+                let [x, y, width, height] = bounds;
+                let units = Transform::new(width, 0.0, 0.0, height, x, y);
+                let paint = self.paint("fill", &base.background, opacity, &units);
+                self.line(&format!(
+                    "{}{}{}/>",
+                    shape(bounds, radii),
+                    paint,
+                    matrix(&transform)
+                ));
+            }
+            Node::Border(_, b) => self.node(b.node_mut(), opacity, transform).await,
+            Node::Path(base, p) => {
+                // Paths with a view box keep their coordinates, so their strokes stay
+                // exact when the view box is stretched, the others are written in pixels
+                let (geometry, element, to_pixels, scale) = match p.view_box() {
+                    Some(view_box) => match view_box.transform(&base.rect(&self.root)) {
+                        Some(to_bounds) => {
+                            let element = transform * to_bounds;
+                            (p.clone(), element, element, 1.0)
+                        }
+                        None => return,
+                    },
+                    None => {
+                        let to_surface = self.path_to_surface();
+                        let scale = self.root.extent.height as f32 / 2.0;
+                        (
+                            p.transformed(&to_surface),
+                            transform,
+                            transform * to_surface,
+                            scale,
+                        )
+                    }
+                };
+                // Gradients span the bounds of what the renderer fills on the surface
+                let inverse = match element.inverse() {
+                    Some(inverse) => inverse,
+                    // The path was collapsed onto a line or point
+                    None => return,
+                };
+                let [left, top, right, bottom] = bounds(&p.outline().transformed(&to_pixels));
+                let units =
+                    inverse * Transform::new(right - left, 0.0, 0.0, bottom - top, left, top);
+
+                let mut attributes = match p.stroke() {
+                    Some(stroke) => {
+                        let mut attributes = String::from(" fill=\"none\"");
+                        attributes.push_str(&self.paint(
+                            "stroke",
+                            &base.background,
+                            opacity,
+                            &units,
+                        ));
+                        write!(
+                            attributes,
+                            " stroke-width=\"{}\"",
+                            number(stroke.width * scale)
+                        )
+                        .unwrap();
+                        match stroke.join {
+                            LineJoin::Miter => {}
+                            LineJoin::Round => attributes.push_str(" stroke-linejoin=\"round\""),
+                            LineJoin::Bevel => attributes.push_str(" stroke-linejoin=\"bevel\""),
+                        }
+                        match stroke.cap {
+                            LineCap::Butt => {}
+                            LineCap::Round => attributes.push_str(" stroke-linecap=\"round\""),
+                            LineCap::Square => attributes.push_str(" stroke-linecap=\"square\""),
+                        }
+                        if stroke.miter_limit != 4.0 {
+                            write!(
+                                attributes,
+                                " stroke-miterlimit=\"{}\"",
+                                number(stroke.miter_limit)
+                            )
+                            .unwrap();
+                        }
+                        // Invalid patterns draw a solid line, like in the renderer
+                        let solid = stroke.dashes.iter().any(|dash| *dash < 0.0)
+                            || stroke.dashes.iter().sum::<f32>() <= 0.0;
+                        if !solid {
+                            let dashes: Vec<String> = stroke
+                                .dashes
+                                .iter()
+                                .map(|dash| number(dash * scale))
+                                .collect();
+                            write!(attributes, " stroke-dasharray=\"{}\"", dashes.join(" "))
+                                .unwrap();
+                            if stroke.dash_offset != 0.0 {
+                                write!(
+                                    attributes,
+                                    " stroke-dashoffset=\"{}\"",
+                                    number(stroke.dash_offset * scale)
+                                )
+                                .unwrap();
+                            }
+                        }
+                        attributes
+                    }
+                    None => {
+                        let mut attributes = self.paint("fill", &base.background, opacity, &units);
+                        if p.fill_rule() == FillRule::EvenOdd {
+                            attributes.push_str(" fill-rule=\"evenodd\"");
+                        }
+                        attributes
+                    }
+                };
+                attributes.push_str(&matrix(&element));
+                self.line(&format!(
+                    "<path d=\"{}\"{}/>",
+                    path_data(&geometry),
+                    attributes
+                ));
+            }
+            Node::Composition(_, c) => {
+                for (node, layer_opacity) in c.layers_with_opacity_mut() {
+                    self.node(node, opacity * layer_opacity, transform).await;
+                }
+            }
+            Node::Image(base, i) => {
+                let (bounds, radii) =
+                    self.rounded_bounds(&base.rect(&self.root), &base.border_radii);
+                let href = match href(i.resource()) {
+                    Some(href) => href,
+                    None => return,
+                };
+                // Nine-slice scaling has no equivalent in SVG, such images are stretched
+                let fit = match (i.slice(), dimensions(i.resource())) {
+                    (None, Some(image)) => {
+                        i.fit().rect(i.position(), image, [bounds[2], bounds[3]])
+                    }
+                    _ => [0.0, 0.0, 1.0, 1.0],
+                };
+                let [x, y, width, height] = [
+                    bounds[0] + fit[0] * bounds[2],
+                    bounds[1] + fit[1] * bounds[3],
+                    fit[2] * bounds[2],
+                    fit[3] * bounds[3],
+                ];
+                let mut attributes = format!(
+                    "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" href=\"{}\" preserveAspectRatio=\"none\"",
+                    number(x),
+                    number(y),
+                    number(width),
+                    number(height),
+                    escape(&href)
+                );
+                let overflows =
+                    fit[0] < 0.0 || fit[1] < 0.0 || fit[0] + fit[2] > 1.0 || fit[1] + fit[3] > 1.0;
+                if overflows || radii.iter().any(|radius| *radius > 0.0) {
+                    let id = self.id("clip");
+                    writeln!(self.defs, "    <clipPath id=\"{}\">", id).unwrap();
+                    writeln!(self.defs, "      {}/>", shape(bounds, radii)).unwrap();
+                    self.defs.push_str("    </clipPath>\n");
+                    write!(attributes, " clip-path=\"url(#{})\"", id).unwrap();
+                }
+                if opacity < 1.0 {
+                    write!(attributes, " opacity=\"{}\"", number(opacity)).unwrap();
+                }
+                attributes.push_str(&matrix(&transform));
+                self.line(&format!("{}/>", attributes));
+            }
+            Node::Text(..) => {}
+            Node::Component(_, c) => {
+                let mut node = c.node().await;
+                self.node(&mut node, opacity, transform).await;
+            }
+        }
+    }
+
+    /// Bounds of the node as `[x, y, width, height]` in pixels and its border radii,
+    /// which are relative to the width of the node, in pixels
+    fn rounded_bounds(&self, rect: &Rect, radii: &[f32; 4]) -> ([f32; 4], [f32; 4]) {
+        let bounds = [
+            rect.offset.x as f32,
+            rect.offset.y as f32,
+            rect.extent.width as f32,
+            rect.extent.height as f32,
+        ];
+        (bounds, radii.map(|radius| radius * bounds[2]))
+    }
+
+    /// Paint attributes of the node. Gradients are added to the definitions, `units`
+    /// maps the unit square to the bounds the gradient is relative to.
+    fn paint(&mut self, attribute: &str, paint: &Paint, opacity: f32, units: &Transform) -> String {
+        let (name, geometry, stops, spread) = match paint {
+            Paint::Linear {
+                start,
+                end,
+                stops,
+                spread,
+            } => (
+                "linearGradient",
+                format!(
+                    "x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"",
+                    number(start[0]),
+                    number(start[1]),
+                    number(end[0]),
+                    number(end[1])
+                ),
+                stops,
+                spread,
+            ),
+            Paint::Radial {
+                center,
+                radius,
+                stops,
+                spread,
+            } => (
+                "radialGradient",
+                format!(
+                    "cx=\"{}\" cy=\"{}\" r=\"{}\"",
+                    number(center[0]),
+                    number(center[1]),
+                    number(*radius)
+                ),
+                stops,
+                spread,
+            ),
+            Paint::Solid(_) | Paint::Conic { .. } => {
+                let (color, alpha) = color(&paint.color());
+                let mut attributes = format!(" {}=\"{}\"", attribute, color);
+                if alpha * opacity < 1.0 {
+                    write!(
+                        attributes,
+                        " {}-opacity=\"{}\"",
+                        attribute,
+                        number(alpha * opacity)
+                    )
+                    .unwrap();
+                }
+                return attributes;
+            }
+        };
+
+        let id = self.id("paint");
+        write!(
+            self.defs,
+            "    <{} id=\"{}\" {} gradientUnits=\"userSpaceOnUse\" gradientTransform=\"{}\"",
+            name,
+            id,
+            geometry,
+            matrix_value(units)
+        )
+        .unwrap();
+        match spread {
+            Spread::Pad => {}
+            Spread::Repeat => self.defs.push_str(" spreadMethod=\"repeat\""),
+            Spread::Reflect => self.defs.push_str(" spreadMethod=\"reflect\""),
+        }
+        self.defs.push_str(">\n");
+        for stop in stops {
+            let (color, alpha) = color(&stop.color);
+            write!(
+                self.defs,
+                "      <stop offset=\"{}\" stop-color=\"{}\"",
+                number(stop.offset),
+                color
+            )
+            .unwrap();
+            if alpha < 1.0 {
+                write!(self.defs, " stop-opacity=\"{}\"", number(alpha)).unwrap();
+            }
+            self.defs.push_str("/>\n");
+        }
+        writeln!(self.defs, "    </{}>", name).unwrap();
+
+        let mut attributes = format!(" {}=\"url(#{})\"", attribute, id);
+        if opacity < 1.0 {
+            write!(attributes, " {}-opacity=\"{}\"", attribute, number(opacity)).unwrap();
+        }
+        attributes
+    }
+}
+
+/// Start of the element of a rectangle with its border radii in pixels. Rectangles
+/// with different radii are written as a path.
+fn shape(bounds: [f32; 4], radii: [f32; 4]) -> String {
+    if radii.iter().any(|radius| *radius != radii[0]) {
+        return format!(
+            "<path d=\"{}\"",
+            path_data(&PathNode::rounded_rect(bounds, radii))
+        );
+    }
+    let [x, y, width, height] = bounds;
+    let mut shape = format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+        number(x),
+        number(y),
+        number(width),
+        number(height)
+    );
+    if radii[0] > 0.0 {
+        write!(shape, " rx=\"{}\"", number(radii[0])).unwrap();
+    }
+    shape
+}
+
+/// Path data of moves, lines and cubic bezier curves. Subpaths that end at their
+/// start are closed, so strokes join there instead of getting caps.
+fn path_data(path: &PathNode) -> String {
+    let path = path.normalized();
+    let mut data = format!("M{}", point(path.from));
+    let mut start = path.from;
+    let mut current = path.from;
+    let mut count = 0;
+    for segment in &path.segments {
+        match segment {
+            PathSegment::Move { to } => {
+                if count > 1 && current == start {
+                    data.push_str(" Z");
+                }
+                write!(data, " M{}", point(*to)).unwrap();
+                start = *to;
+                count = 0;
+            }
+            PathSegment::CubicBezier { to, params } => {
+                write!(
+                    data,
+                    " C{} {} {}",
+                    point(params[0]),
+                    point(params[1]),
+                    point(*to)
+                )
+                .unwrap();
+                count += 1;
+            }
+            segment => {
+                write!(data, " L{}", point(*segment.to())).unwrap();
+                count += 1;
+            }
+        }
+        current = *segment.to();
+    }
+    if count > 1 && current == start {
+        data.push_str(" Z");
+    }
+    data
+}
+
+/// Bounds `[left, top, right, bottom]` of the control points, like the renderer
+/// uses them for gradients
+fn bounds(path: &PathNode) -> [f32; 4] {
+    let path = path.normalized();
+    let mut bounds = [path.from[0], path.from[1], path.from[0], path.from[1]];
+    let mut extend = |point: &[f32; 2]| {
+        bounds[0] = bounds[0].min(point[0]);
+        bounds[1] = bounds[1].min(point[1]);
+        bounds[2] = bounds[2].max(point[0]);
+        bounds[3] = bounds[3].max(point[1]);
+    };
+    for segment in &path.segments {
+        if let PathSegment::CubicBezier { params, .. } = segment {
+            extend(&params[0]);
+            extend(&params[1]);
+        }
+        extend(segment.to());
+    }
+    bounds
+}
+
+/// Reference to the image. Images in memory are embedded as data URLs, decoded
+/// ones are encoded as PNG first.
+fn href(resource: &Resource) -> Option<String> {
+    let (bytes, mime) = match resource {
+        Resource::Path(path) => return Some(path.to_string_lossy().into_owned()),
+        Resource::Bytes(bytes) => (Cow::Borrowed(&bytes[..]), None),
+        Resource::Static(bytes) => (Cow::Borrowed(*bytes), None),
+        Resource::Rgba { extent, pixels } => {
+            let mut png = vec![];
+            PngEncoder::new(&mut png)
+                .write_image(pixels, extent.width, extent.height, ColorType::Rgba8)
+                .ok()?;
+            (Cow::Owned(png), Some("image/png"))
+        }
+    };
+    let mime = mime.unwrap_or_else(|| match image::guess_format(&bytes) {
+        Ok(ImageFormat::Png) => "image/png",
+        Ok(ImageFormat::Jpeg) => "image/jpeg",
+        Ok(ImageFormat::Gif) => "image/gif",
+        Ok(ImageFormat::WebP) => "image/webp",
+        Ok(ImageFormat::Bmp) => "image/bmp",
+        Ok(ImageFormat::Ico) => "image/x-icon",
+        _ => "application/octet-stream",
+    });
+    Some(format!("data:{};base64,{}", mime, base64(&bytes)))
+}
+
+/// Width and height of the image in texels, without decoding it
+fn dimensions(resource: &Resource) -> Option<[f32; 2]> {
+    let (width, height) = match resource {
+        Resource::Path(path) => image::image_dimensions(path).ok()?,
+        Resource::Bytes(bytes) => Reader::new(Cursor::new(&bytes[..]))
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()?,
+        Resource::Static(bytes) => Reader::new(Cursor::new(*bytes))
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()?,
+        Resource::Rgba { extent, .. } => (extent.width, extent.height),
+    };
+    Some([width as f32, height as f32])
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[((group >> (18 - 6 * index)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// The `transform` attribute, which is left out for the identity
+fn matrix(transform: &Transform) -> String {
+    if transform.is_identity() {
+        String::new()
+    } else {
+        format!(" transform=\"{}\"", matrix_value(transform))
+    }
+}
+
+fn matrix_value(transform: &Transform) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        number(transform.a),
+        number(transform.b),
+        number(transform.c),
+        number(transform.d),
+        number(transform.e),
+        number(transform.f)
+    )
+}
+
+/// The color as `rgb()` and its alpha
+fn color(color: &Color) -> (String, f32) {
+    let [r, g, b, a] = color.as_raw();
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (
+        format!("rgb({},{},{})", channel(r), channel(g), channel(b)),
+        a.clamp(0.0, 1.0),
+    )
+}
+
+fn point(point: [f32; 2]) -> String {
+    format!("{} {}", number(point[0]), number(point[1]))
+}
+
+/// Rounds to three decimals, so documents stay stable across platforms
+fn number(value: f32) -> String {
+    // Adding zero turns negative zero into zero
+    format!("{}", (value * 1000.0).round() / 1000.0 + 0.0)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export;
+    use crate::node::{comp, rect, Node};
+    use crate::svg::import;
+    use crate::util::{Color, Flags, Paint, Rect};
+
+    /// Bounds, color with the opacities of the ancestors and stroke width of each path
+    fn paths(node: &mut Node, opacity: f32, out: &mut Vec<([f32; 4], [f32; 4], Option<f32>)>) {
+        let base = node.base_mut();
+        let opacity = opacity * base.opacity * base.group_opacity.unwrap_or(1.0);
+        match node {
+            Node::Composition(_, c) => {
+                for layer in c.layers_mut() {
+                    paths(layer, opacity, out);
+                }
+            }
+            Node::Path(base, p) => {
+                let mut color = match &base.background {
+                    Paint::Solid(color) => color.as_raw(),
+                    _ => panic!("expected a solid paint"),
+                };
+                color[3] *= opacity;
+                out.push((p.bounds(), color, p.stroke().map(|stroke| stroke.width)));
+            }
+            _ => panic!("expected a path or a composition"),
+        }
+    }
+
+    #[test]
+    fn import_export_round_trip() {
+        let source = r#"<svg viewBox="0 0 200 100">
+            <rect x="10" y="10" width="80" height="40" rx="5" fill="red"/>
+            <circle cx="150" cy="50" r="30" fill="blue" stroke="lime" stroke-width="4" opacity="0.5"/>
+            <path d="M10 90H190" fill="none" stroke="black" stroke-width="2" stroke-linecap="round"/>
+        </svg>"#;
+        // The rect covers a surface of 200 by 100 pixels
+        let area = [-2.0, -1.0, 4.0, 2.0];
+        let mut imported = import(source, area).unwrap();
+        let exported = pollster::block_on(export(&mut imported, 200, 100));
+        assert!(exported.contains("stroke-linecap=\"round\""));
+        let mut reimported = import(&exported, area).unwrap();
+
+        let (mut expected, mut actual) = (vec![], vec![]);
+        paths(&mut imported, 1.0, &mut expected);
+        paths(&mut reimported, 1.0, &mut actual);
+        assert_eq!(expected.len(), 4);
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(&actual) {
+            for (a, b) in expected.0.iter().zip(actual.0) {
+                assert!(
+                    (a - b).abs() < 1e-2,
+                    "bounds {:?} != {:?}",
+                    expected.0,
+                    actual.0
+                );
+            }
+            for (a, b) in expected.1.iter().zip(actual.1) {
+                assert!(
+                    (a - b).abs() < 1e-2,
+                    "color {:?} != {:?}",
+                    expected.1,
+                    actual.1
+                );
+            }
+            match (expected.2, actual.2) {
+                (Some(a), Some(b)) => assert!((a - b).abs() < 1e-3),
+                (a, b) => assert_eq!(a, b),
+            }
+        }
+    }
+
+    #[test]
+    fn rects_are_written_in_pixels() {
+        let color = Color::RGBA {
+            r: 0.0,
+            g: 0.0,
+            b: 1.0,
+            a: 0.5,
+        };
+        let mut node = rect(color, [0.1, 0.1, 0.1, 0.1]);
+        let base = node.base_mut();
+        base.bounding_rect = Rect::new(10, 20, 30, 40);
+        base.flags = Flags::NONE;
+        let exported = pollster::block_on(export(&mut comp([node]), 100, 100));
+        assert!(exported.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\""));
+        assert!(exported.contains("<rect x=\"10\" y=\"20\" width=\"30\" height=\"40\" rx=\"3\""));
+        assert!(exported.contains("fill=\"rgb(0,0,255)\" fill-opacity=\"0.5\""));
+    }
+}
@@ -0,0 +1,585 @@
+use crate::error::Error;
+use crate::math::Transform;
+use crate::node::base::BaseNode;
+use crate::node::path::{FillRule, PathNode};
+use crate::node::{comp, LineCap, LineJoin, Node, Stroke};
+use crate::svg::path::{parse_path, Parser};
+use crate::svg::xml::{self, Element};
+use crate::util::{Color, Paint, PathSegment, Point2D};
+
+/// Loads an SVG document into a composition of path nodes. The viewBox of the
+/// document is scaled uniformly into `rect`, which is `[x, y, width, height]` in the
+/// coordinates of path nodes, and centered in it.
+///
+/// Supported are the elements `svg`, `g`, `path`, `rect`, `circle`, `ellipse`, `line`,
+/// `polyline` and `polygon` with their `transform` and the solid color presentation
+/// attributes for fills and strokes. Other elements and paint servers are ignored.
+pub fn import(source: &str, rect: [f32; 4]) -> Result<Node, Error> {
+    let root = xml::parse(source)?;
+    if root.name != "svg" {
+        return Err(Error::InvalidSvg(format!(
+            "root element is `{}` instead of `svg`",
+            root.name
+        )));
+    }
+
+    let [x, y, width, height] = rect;
+    let view_box = view_box(&root)?;
+    let [min_x, min_y, view_width, view_height] = view_box;
+    let scale = (width / view_width).min(height / view_height);
+    // The y axis of SVG points down, the one of paths up
+    let view = Transform::new(
+        scale,
+        0.0,
+        0.0,
+        -scale,
+        x + (width - view_width * scale) / 2.0 - min_x * scale,
+        y + height - (height - view_height * scale) / 2.0 + min_y * scale,
+    );
+
+    let style = Style::from(&root)?;
+    let mut layers = vec![];
+    for child in &root.children {
+        element(child, &view, &style, &mut layers)?;
+    }
+    Ok(comp(layers))
+}
+
+/// `[min_x, min_y, width, height]` of the viewBox, or of the size of the document
+fn view_box(root: &Element) -> Result<[f32; 4], Error> {
+    if let Some(view_box) = root.attribute("viewBox") {
+        let numbers = numbers(view_box)?;
+        if let [min_x, min_y, width, height] = numbers[..] {
+            if width > 0.0 && height > 0.0 {
+                return Ok([min_x, min_y, width, height]);
+            }
+        }
+        return Err(Error::InvalidSvg(format!("invalid viewBox `{}`", view_box)));
+    }
+    // The default size of replaced elements in CSS
+    let width = root.attribute("width").map(length).transpose()?;
+    let height = root.attribute("height").map(length).transpose()?;
+    Ok([0.0, 0.0, width.unwrap_or(300.0), height.unwrap_or(150.0)])
+}
+
+/// Presentation attributes, which are inherited by the children of an element
+#[derive(Clone)]
+struct Style {
+    fill: Option<Color>,
+    fill_rule: FillRule,
+    fill_opacity: f32,
+    stroke: Option<Color>,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    join: LineJoin,
+    cap: LineCap,
+    miter_limit: f32,
+}
+impl Style {
+    fn from(element: &Element) -> Result<Self, Error> {
+        Style {
+            fill: Some(Color::BLACK),
+            fill_rule: FillRule::NonZero,
+            fill_opacity: 1.0,
+            stroke: None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+        .inherit(element)
+    }
+
+    fn inherit(&self, element: &Element) -> Result<Self, Error> {
+        let mut style = self.clone();
+        if let Some(fill) = property(element, "fill") {
+            style.fill = color(&fill)?;
+        }
+        if let Some(rule) = property(element, "fill-rule") {
+            style.fill_rule = match rule.as_str() {
+                "evenodd" => FillRule::EvenOdd,
+                _ => FillRule::NonZero,
+            };
+        }
+        if let Some(opacity) = property(element, "fill-opacity") {
+            style.fill_opacity = length(&opacity)?.clamp(0.0, 1.0);
+        }
+        if let Some(stroke) = property(element, "stroke") {
+            style.stroke = color(&stroke)?;
+        }
+        if let Some(opacity) = property(element, "stroke-opacity") {
+            style.stroke_opacity = length(&opacity)?.clamp(0.0, 1.0);
+        }
+        if let Some(width) = property(element, "stroke-width") {
+            style.stroke_width = length(&width)?;
+        }
+        if let Some(join) = property(element, "stroke-linejoin") {
+            style.join = match join.as_str() {
+                "round" => LineJoin::Round,
+                "bevel" => LineJoin::Bevel,
+                _ => LineJoin::Miter,
+            };
+        }
+        if let Some(cap) = property(element, "stroke-linecap") {
+            style.cap = match cap.as_str() {
+                "round" => LineCap::Round,
+                "square" => LineCap::Square,
+                _ => LineCap::Butt,
+            };
+        }
+        if let Some(limit) = property(element, "stroke-miterlimit") {
+            style.miter_limit = length(&limit)?;
+        }
+        Ok(style)
+    }
+}
+
+fn element(
+    element: &Element,
+    parent: &Transform,
+    style: &Style,
+    out: &mut Vec<Node>,
+) -> Result<(), Error> {
+    if property(element, "display").as_deref() == Some("none") {
+        return Ok(());
+    }
+    let transform = match element.attribute("transform") {
+        Some(transform) => *parent * self::transform(transform)?,
+        None => *parent,
+    };
+    let style = style.inherit(element)?;
+    let opacity = match property(element, "opacity") {
+        Some(opacity) => length(&opacity)?.clamp(0.0, 1.0),
+        None => 1.0,
+    };
+
+    let shape = match element.name.as_str() {
+        "g" | "svg" => {
+            let mut children = vec![];
+            for child in &element.children {
+                self::element(child, &transform, &style, &mut children)?;
+            }
+            let group = comp(children);
+            out.push(if opacity < 1.0 {
+                group.group_opacity(opacity)
+            } else {
+                group
+            });
+            return Ok(());
+        }
+        // Like browsers, paths without data render nothing
+        "path" => match element.attribute("d") {
+            Some(data) if !data.trim().is_empty() => parse_path(data)?,
+            _ => return Ok(()),
+        },
+        "rect" => {
+            let [x, y, width, height] = attributes(element, ["x", "y", "width", "height"])?;
+            let rx = element.attribute("rx").map(length).transpose()?;
+            let ry = element.attribute("ry").map(length).transpose()?;
+            let radii = [
+                rx.or(ry).unwrap_or(0.0).min(width / 2.0),
+                ry.or(rx).unwrap_or(0.0).min(height / 2.0),
+            ];
+            rect([x, y, width, height], radii)
+        }
+        "circle" => {
+            let [cx, cy, r] = attributes(element, ["cx", "cy", "r"])?;
+            ellipse([cx, cy], [r, r])
+        }
+        "ellipse" => {
+            let [cx, cy, rx, ry] = attributes(element, ["cx", "cy", "rx", "ry"])?;
+            ellipse([cx, cy], [rx, ry])
+        }
+        "line" => {
+            let [x1, y1, x2, y2] = attributes(element, ["x1", "y1", "x2", "y2"])?;
+            PathNode::new([x1, y1], vec![PathSegment::Linear { to: [x2, y2] }])
+        }
+        "polyline" | "polygon" => {
+            let numbers = numbers(element.attribute("points").unwrap_or_default())?;
+            let mut points = numbers.chunks_exact(2).map(|point| [point[0], point[1]]);
+            let from = match points.next() {
+                Some(from) => from,
+                None => return Ok(()),
+            };
+            let mut segments: Vec<PathSegment> =
+                points.map(|to| PathSegment::Linear { to }).collect();
+            if element.name == "polygon" {
+                segments.push(PathSegment::Linear { to: from });
+            }
+            PathNode::new(from, segments)
+        }
+        _ => return Ok(()),
+    };
+    let shape = shape.transformed(&transform);
+
+    let mut nodes = vec![];
+    // Lines enclose no area
+    if let (Some(fill), false) = (style.fill, element.name == "line") {
+        let mut path = shape.clone();
+        path.fill_rule = style.fill_rule;
+        nodes.push(node(path, fill, style.fill_opacity));
+    }
+    if let Some(stroke) = style.stroke {
+        // Strokes are outlined in the mapped coordinates
+        let scale = transform.determinant().abs().sqrt();
+        let mut path = shape;
+        path.stroke = Some(
+            Stroke::new(style.stroke_width * scale)
+                .join(style.join)
+                .cap(style.cap)
+                .miter_limit(style.miter_limit),
+        );
+        nodes.push(node(path, stroke, style.stroke_opacity));
+    }
+    // The opacity applies to the element as a whole, so fill and stroke are faded
+    // together where they overlap
+    match (nodes.len(), opacity < 1.0) {
+        (2, true) => out.push(comp(nodes).group_opacity(opacity)),
+        (_, true) => out.extend(nodes.into_iter().map(|node| node.opacity(opacity))),
+        (_, false) => out.extend(nodes),
+    }
+    Ok(())
+}
+
+fn node(path: PathNode, color: Color, opacity: f32) -> Node {
+    let [r, g, b, a] = color.as_raw();
+    let base = BaseNode {
+        background: Paint::Solid(Color::RGBA {
+            r,
+            g,
+            b,
+            a: a * opacity,
+        }),
+        ..BaseNode::default()
+    };
+    Node::Path(base, path)
+}
+
+fn rect(rect: [f32; 4], radii: [f32; 2]) -> PathNode {
+    let [x, y, width, height] = rect;
+    let [rx, ry] = radii;
+    if rx <= 0.0 || ry <= 0.0 {
+        let segments = [
+            [x + width, y],
+            [x + width, y + height],
+            [x, y + height],
+            [x, y],
+        ]
+        .map(|to| PathSegment::Linear { to });
+        return PathNode::new([x, y], segments.to_vec());
+    }
+    let corner = |to: Point2D| PathSegment::Arc {
+        to,
+        radii,
+        rotation: 0.0,
+        large_arc: false,
+        sweep: true,
+    };
+    let (right, bottom) = (x + width, y + height);
+    PathNode::new(
+        [x + rx, y],
+        vec![
+            PathSegment::Linear {
+                to: [right - rx, y],
+            },
+            corner([right, y + ry]),
+            PathSegment::Linear {
+                to: [right, bottom - ry],
+            },
+            corner([right - rx, bottom]),
+            PathSegment::Linear {
+                to: [x + rx, bottom],
+            },
+            corner([x, bottom - ry]),
+            PathSegment::Linear { to: [x, y + ry] },
+            corner([x + rx, y]),
+        ],
+    )
+}
+
+fn ellipse(center: Point2D, radii: [f32; 2]) -> PathNode {
+    let [cx, cy] = center;
+    let half = |to: Point2D| PathSegment::Arc {
+        to,
+        radii,
+        rotation: 0.0,
+        large_arc: false,
+        sweep: true,
+    };
+    PathNode::new(
+        [cx + radii[0], cy],
+        vec![half([cx - radii[0], cy]), half([cx + radii[0], cy])],
+    )
+}
+
+/// Value of a presentation attribute, declarations in the `style` attribute take
+/// precedence
+fn property(element: &Element, name: &str) -> Option<String> {
+    let declared = element.attribute("style").and_then(|style| {
+        style.split(';').find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    });
+    declared.or_else(|| {
+        element
+            .attribute(name)
+            .map(|value| value.trim().to_string())
+    })
+}
+
+/// Lengths of the attributes, missing attributes are zero
+fn attributes<const N: usize>(element: &Element, names: [&str; N]) -> Result<[f32; N], Error> {
+    let mut values = [0.0; N];
+    for (value, name) in values.iter_mut().zip(names) {
+        if let Some(attribute) = element.attribute(name) {
+            *value = length(attribute)?;
+        }
+    }
+    Ok(values)
+}
+
+/// Number with an optional unit, which is ignored
+fn length(value: &str) -> Result<f32, Error> {
+    Parser::new(value).number()
+}
+
+fn numbers(value: &str) -> Result<Vec<f32>, Error> {
+    let mut parser = Parser::new(value);
+    let mut numbers = vec![];
+    while parser.has_number() {
+        numbers.push(parser.number()?);
+    }
+    if !parser.at_end() {
+        return Err(parser.error("expected a number"));
+    }
+    Ok(numbers)
+}
+
+/// Parses a transform list like `translate(10 20) rotate(45)`
+fn transform(value: &str) -> Result<Transform, Error> {
+    let mut transform = Transform::IDENTITY;
+    for function in value.split(')') {
+        let function = function.trim_matches(|c: char| c.is_whitespace() || c == ',');
+        if function.is_empty() {
+            continue;
+        }
+        let (name, arguments) = function
+            .split_once('(')
+            .ok_or_else(|| Error::InvalidSvg(format!("invalid transform `{}`", value)))?;
+        let arguments = numbers(arguments)?;
+        let argument = |index: usize| arguments.get(index).copied();
+        let next = match (name.trim(), arguments.len()) {
+            ("matrix", 6) => Transform::new(
+                arguments[0],
+                arguments[1],
+                arguments[2],
+                arguments[3],
+                arguments[4],
+                arguments[5],
+            ),
+            ("translate", 1 | 2) => Transform::translate(arguments[0], argument(1).unwrap_or(0.0)),
+            ("scale", 1 | 2) => Transform::scale(arguments[0], argument(1).unwrap_or(arguments[0])),
+            ("rotate", 1) => Transform::rotate(arguments[0].to_radians()),
+            ("rotate", 3) => {
+                Transform::rotate(arguments[0].to_radians()).around([arguments[1], arguments[2]])
+            }
+            ("skewX", 1) => Transform::skew(arguments[0].to_radians(), 0.0),
+            ("skewY", 1) => Transform::skew(0.0, arguments[0].to_radians()),
+            _ => return Err(Error::InvalidSvg(format!("invalid transform `{}`", value))),
+        };
+        transform = transform * next;
+    }
+    Ok(transform)
+}
+
+/// Solid color of a paint, `None` for `none` and paint servers
+fn color(value: &str) -> Result<Option<Color>, Error> {
+    let invalid = || Error::InvalidSvg(format!("invalid color `{}`", value));
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |index: usize| {
+            hex.get(index..index + 1)
+                .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+                .ok_or_else(invalid)
+        };
+        let rgb = match hex.len() {
+            3 => [digit(0)? * 17, digit(1)? * 17, digit(2)? * 17],
+            6 => [
+                digit(0)? * 16 + digit(1)?,
+                digit(2)? * 16 + digit(3)?,
+                digit(4)? * 16 + digit(5)?,
+            ],
+            _ => return Err(invalid()),
+        };
+        return Ok(Some(rgb.into()));
+    }
+    if let Some(arguments) = value
+        .strip_prefix("rgb(")
+        .and_then(|value| value.strip_suffix(')'))
+    {
+        let channels: Vec<f32> = arguments
+            .split(',')
+            .map(|channel| {
+                let channel = channel.trim();
+                match channel.strip_suffix('%') {
+                    Some(percent) => length(percent).map(|percent| percent / 100.0),
+                    None => length(channel).map(|channel| channel / 255.0),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        return match channels[..] {
+            [r, g, b] => Ok(Some([r, g, b].map(|c| c.clamp(0.0, 1.0)).into())),
+            _ => Err(invalid()),
+        };
+    }
+    let rgb: [u8; 3] = match value {
+        "none" | "transparent" => return Ok(None),
+        _ if value.starts_with("url(") => return Ok(None),
+        "black" | "currentColor" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "lime" => [0, 255, 0],
+        "green" => [0, 128, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "cyan" | "aqua" => [0, 255, 255],
+        "magenta" | "fuchsia" => [255, 0, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "silver" => [192, 192, 192],
+        "maroon" => [128, 0, 0],
+        "olive" => [128, 128, 0],
+        "purple" => [128, 0, 128],
+        "teal" => [0, 128, 128],
+        "navy" => [0, 0, 128],
+        "orange" => [255, 165, 0],
+        _ => return Err(invalid()),
+    };
+    Ok(Some(rgb.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import;
+    use crate::error::Error;
+    use crate::node::Node;
+    use crate::util::Paint;
+
+    fn layers(node: &Node) -> &Vec<Node> {
+        match node {
+            Node::Composition(_, c) => c.layers(),
+            _ => panic!("expected a composition"),
+        }
+    }
+
+    fn color(node: &Node) -> [f32; 4] {
+        match node {
+            Node::Path(base, _) => match &base.background {
+                Paint::Solid(color) => color.as_raw(),
+                _ => panic!("expected a solid paint"),
+            },
+            _ => panic!("expected a path"),
+        }
+    }
+
+    fn assert_close(a: [f32; 4], b: [f32; 4]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn view_box_is_centered_in_the_rect() {
+        let source = r##"<svg viewBox="0 0 100 50">
+            <rect x="0" y="0" width="100" height="50" fill="#ff0000"/>
+            <rect x="0" y="0" width="50" height="25"/>
+        </svg>"##;
+        let node = import(source, [-2.0, -2.0, 4.0, 4.0]).unwrap();
+        let layers = layers(&node);
+        assert_eq!(layers.len(), 2);
+        // The width fits, the height is centered and the y axis points up
+        match &layers[0] {
+            Node::Path(_, p) => assert_close(p.bounds(), [-2.0, -1.0, 2.0, 1.0]),
+            _ => panic!("expected a path"),
+        }
+        match &layers[1] {
+            Node::Path(_, p) => assert_close(p.bounds(), [-2.0, 0.0, 0.0, 1.0]),
+            _ => panic!("expected a path"),
+        }
+        assert_close(color(&layers[0]), [1.0, 0.0, 0.0, 1.0]);
+        // The default fill is black
+        assert_close(color(&layers[1]), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn paths_without_data_are_skipped() {
+        let source = r#"<svg viewBox="0 0 10 10">
+            <path d=""/>
+            <path d="  "/>
+            <path/>
+            <path d="M0 0H10V10Z"/>
+        </svg>"#;
+        let node = import(source, [0.0, 0.0, 1.0, 1.0]).unwrap();
+        assert_eq!(layers(&node).len(), 1);
+    }
+
+    #[test]
+    fn opacity_fades_fill_and_stroke_together() {
+        let source = r#"<svg viewBox="0 0 10 10">
+            <rect width="10" height="10" fill="red" stroke="blue" opacity="0.5"/>
+            <rect width="10" height="10" fill="red" fill-opacity="0.5" opacity="0.5"/>
+        </svg>"#;
+        let node = import(source, [0.0, 0.0, 1.0, 1.0]).unwrap();
+        let layers = layers(&node);
+        assert_eq!(layers.len(), 2);
+        match &layers[0] {
+            Node::Composition(base, c) => {
+                assert_eq!(base.group_opacity, Some(0.5));
+                assert_eq!(c.layers().len(), 2);
+                assert!(matches!(&c.layers()[1], Node::Path(_, p) if p.stroke().is_some()));
+            }
+            _ => panic!("expected the fill and the stroke in a group"),
+        }
+        // A single node is faded by itself, the fill opacity is part of its color
+        match &layers[1] {
+            Node::Path(base, _) => assert_eq!(base.opacity, 0.5),
+            _ => panic!("expected a path"),
+        }
+        assert_close(color(&layers[1]), [1.0, 0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn styles_are_inherited_and_declarations_take_precedence() {
+        let source = r#"<svg viewBox="0 0 10 10">
+            <g fill="blue" style="fill: red">
+                <circle cx="5" cy="5" r="5"/>
+                <circle cx="5" cy="5" r="5" fill="lime"/>
+                <circle cx="5" cy="5" r="5" style="display: none"/>
+                <line x1="0" y1="0" x2="10" y2="10"/>
+            </g>
+        </svg>"#;
+        let node = import(source, [0.0, 0.0, 1.0, 1.0]).unwrap();
+        let group = &layers(&node)[0];
+        // Lines enclose no area and aren't stroked by default
+        let layers = layers(group);
+        assert_eq!(layers.len(), 2);
+        assert_close(color(&layers[0]), [1.0, 0.0, 0.0, 1.0]);
+        assert_close(color(&layers[1]), [0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(matches!(
+            import("<html></html>", [0.0, 0.0, 1.0, 1.0]),
+            Err(Error::InvalidSvg(_))
+        ));
+        assert!(matches!(
+            import(r#"<svg viewBox="0 0 0 10"/>"#, [0.0, 0.0, 1.0, 1.0]),
+            Err(Error::InvalidSvg(_))
+        ));
+        assert!(matches!(
+            import(r#"<svg><path d="M0 0L"/></svg>"#, [0.0, 0.0, 1.0, 1.0]),
+            Err(Error::InvalidSvg(_))
+        ));
+    }
+}
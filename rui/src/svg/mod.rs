@@ -0,0 +1,10 @@
+//! Conversion between SVG and nodes
+
+mod export;
+mod import;
+mod path;
+mod xml;
+
+pub use export::export;
+pub use import::import;
+pub use path::parse_path;
@@ -0,0 +1,392 @@
+use crate::error::Error;
+use crate::node::path::PathNode;
+use crate::util::{PathSegment, Point2D};
+
+/// Parses SVG path data, the `d` attribute of a `path` element. The coordinates are
+/// taken as they are, relative commands are resolved against the current point.
+pub fn parse_path(data: &str) -> Result<PathNode, Error> {
+    let mut parser = Parser::new(data);
+    let mut from = None;
+    let mut segments = vec![];
+    // Start of the current subpath and the end of the last segment
+    let mut start = [0.0, 0.0];
+    let mut current = [0.0, 0.0];
+    // Second control point of the last curve, which smooth curves reflect
+    let mut last_control: Option<(u8, Point2D)> = None;
+    // A subpath that is drawn on after it was closed starts anew at its start
+    let mut closed = false;
+
+    let mut command = match parser.command() {
+        Some(command @ (b'M' | b'm')) => command,
+        _ => return Err(parser.error("path data has to start with a move")),
+    };
+    loop {
+        let relative = command.is_ascii_lowercase();
+        let offset = |point: Point2D| {
+            if relative {
+                [current[0] + point[0], current[1] + point[1]]
+            } else {
+                point
+            }
+        };
+        if closed && !matches!(command, b'M' | b'm' | b'Z' | b'z') {
+            segments.push(PathSegment::Move { to: start });
+            closed = false;
+        }
+
+        let mut control = None;
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let to = offset(parser.point()?);
+                if from.is_none() {
+                    from = Some(to);
+                } else {
+                    segments.push(PathSegment::Move { to });
+                }
+                start = to;
+                current = to;
+                closed = false;
+                // Following pairs are implicit lines
+                command = if relative { b'l' } else { b'L' };
+                while parser.has_number() {
+                    current = if relative {
+                        let point = parser.point()?;
+                        [current[0] + point[0], current[1] + point[1]]
+                    } else {
+                        parser.point()?
+                    };
+                    segments.push(PathSegment::Linear { to: current });
+                }
+            }
+            b'L' => {
+                current = offset(parser.point()?);
+                segments.push(PathSegment::Linear { to: current });
+            }
+            b'H' => {
+                let x = parser.number()?;
+                current = [if relative { current[0] + x } else { x }, current[1]];
+                segments.push(PathSegment::Linear { to: current });
+            }
+            b'V' => {
+                let y = parser.number()?;
+                current = [current[0], if relative { current[1] + y } else { y }];
+                segments.push(PathSegment::Linear { to: current });
+            }
+            b'C' | b'S' => {
+                let a = if command.eq_ignore_ascii_case(&b'C') {
+                    offset(parser.point()?)
+                } else {
+                    reflect(last_control, b'C', current)
+                };
+                let b = offset(parser.point()?);
+                let to = offset(parser.point()?);
+                segments.push(PathSegment::CubicBezier { to, params: [a, b] });
+                control = Some((b'C', b));
+                current = to;
+            }
+            b'Q' | b'T' => {
+                let param = if command.eq_ignore_ascii_case(&b'Q') {
+                    offset(parser.point()?)
+                } else {
+                    reflect(last_control, b'Q', current)
+                };
+                let to = offset(parser.point()?);
+                segments.push(PathSegment::QuadraticBezier { to, param });
+                control = Some((b'Q', param));
+                current = to;
+            }
+            b'A' => {
+                let radii = [parser.number()?, parser.number()?];
+                let rotation = parser.number()?.to_radians();
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let to = offset(parser.point()?);
+                segments.push(PathSegment::Arc {
+                    to,
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                });
+                current = to;
+            }
+            b'Z' => {
+                if current != start {
+                    segments.push(PathSegment::Linear { to: start });
+                }
+                current = start;
+                closed = true;
+            }
+            _ => return Err(parser.error("unknown command")),
+        }
+        last_control = control;
+
+        // Commands repeat as long as numbers follow
+        if matches!(command, b'Z' | b'z') || !parser.has_number() {
+            match parser.command() {
+                Some(next) => command = next,
+                None if parser.at_end() => break,
+                None => return Err(parser.error("expected a command")),
+            }
+        }
+    }
+
+    Ok(PathNode::new(from.unwrap_or(start), segments))
+}
+
+/// Reflects the control point of the last curve of the same kind at the current
+/// point, otherwise the control point is the current point
+fn reflect(last: Option<(u8, Point2D)>, kind: u8, current: Point2D) -> Point2D {
+    match last {
+        Some((last, point)) if last == kind => {
+            [2.0 * current[0] - point[0], 2.0 * current[1] - point[1]]
+        }
+        _ => current,
+    }
+}
+
+/// Reads the numbers and lists of SVG attributes, which are separated by whitespace
+/// and optionally a comma
+pub(crate) struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+impl<'a> Parser<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        Parser {
+            source: source.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.source.get(self.position) {
+            if c.is_ascii_whitespace() || *c == b',' {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub(crate) fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.position >= self.source.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.source.get(self.position)?;
+        if c.is_ascii_alphabetic() && !matches!(c, b'e' | b'E') {
+            self.position += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(
+            self.source.get(self.position),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.')
+        )
+    }
+
+    pub(crate) fn number(&mut self) -> Result<f32, Error> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            while let Some(b'0'..=b'9') = parser.source.get(parser.position) {
+                parser.position += 1;
+            }
+        };
+        if let Some(b'-' | b'+') = self.source.get(self.position) {
+            self.position += 1;
+        }
+        digits(self);
+        // A second dot starts the next number, e.g. `0.5.5`
+        if let Some(b'.') = self.source.get(self.position) {
+            self.position += 1;
+            digits(self);
+        }
+        if let Some(b'e' | b'E') = self.source.get(self.position) {
+            let mantissa = self.position;
+            self.position += 1;
+            if let Some(b'-' | b'+') = self.source.get(self.position) {
+                self.position += 1;
+            }
+            if let Some(b'0'..=b'9') = self.source.get(self.position) {
+                digits(self);
+            } else {
+                self.position = mantissa;
+            }
+        }
+        std::str::from_utf8(&self.source[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| {
+                self.position = start;
+                self.error("expected a number")
+            })
+    }
+
+    fn point(&mut self) -> Result<Point2D, Error> {
+        Ok([self.number()?, self.number()?])
+    }
+
+    /// Arc flags are a single digit and need no separator, e.g. `a1 1 0 011 1`
+    fn flag(&mut self) -> Result<bool, Error> {
+        self.skip_separators();
+        let flag = match self.source.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+
+    pub(crate) fn error(&self, message: &str) -> Error {
+        Error::InvalidSvg(format!("{} at {}", message, self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_path;
+    use crate::error::Error;
+    use crate::node::path::PathNode;
+    use crate::util::{PathSegment, Point2D};
+
+    /// Start of the path followed by the end points of its segments
+    fn points(path: &PathNode) -> Vec<Point2D> {
+        let mut points = vec![path.from];
+        points.extend(path.segments.iter().map(|segment| *segment.to()));
+        points
+    }
+
+    #[test]
+    fn implicit_commands() {
+        // Pairs after a move are lines, other commands repeat
+        let path = parse_path("M0 0 10 0 10 10 L20 20 30 30").unwrap();
+        assert_eq!(
+            points(&path),
+            [
+                [0.0, 0.0],
+                [10.0, 0.0],
+                [10.0, 10.0],
+                [20.0, 20.0],
+                [30.0, 30.0]
+            ]
+        );
+        assert!(path
+            .segments
+            .iter()
+            .all(|segment| matches!(segment, PathSegment::Linear { .. })));
+
+        let path = parse_path("m1 1 2 0 0 2h-2v-1z").unwrap();
+        assert_eq!(
+            points(&path),
+            [
+                [1.0, 1.0],
+                [3.0, 1.0],
+                [3.0, 3.0],
+                [1.0, 3.0],
+                [1.0, 2.0],
+                [1.0, 1.0]
+            ]
+        );
+
+        let path = parse_path("M0 0c1 1 2 1 3 0 1-1 2-1 3 0").unwrap();
+        assert_eq!(points(&path), [[0.0, 0.0], [3.0, 0.0], [6.0, 0.0]]);
+        match &path.segments[1] {
+            PathSegment::CubicBezier { params, .. } => {
+                assert_eq!(*params, [[4.0, -1.0], [5.0, -1.0]])
+            }
+            _ => panic!("expected a cubic bezier"),
+        }
+    }
+
+    #[test]
+    fn smooth_curves_reflect_the_last_control_point() {
+        let path = parse_path("M0 0Q1 1 2 0T4 0").unwrap();
+        match &path.segments[1] {
+            PathSegment::QuadraticBezier { param, .. } => assert_eq!(*param, [3.0, -1.0]),
+            _ => panic!("expected a quadratic bezier"),
+        }
+        // Without a preceding curve of the same kind the control point is the current point
+        let path = parse_path("M0 0L2 0S3 1 4 0").unwrap();
+        match &path.segments[1] {
+            PathSegment::CubicBezier { params, .. } => assert_eq!(params[0], [2.0, 0.0]),
+            _ => panic!("expected a cubic bezier"),
+        }
+    }
+
+    #[test]
+    fn number_separators() {
+        let path = parse_path("M1-2.5.5.5L1e1,2 , 3E-1 +4").unwrap();
+        assert_eq!(
+            points(&path),
+            [[1.0, -2.5], [0.5, 0.5], [10.0, 2.0], [0.3, 4.0]]
+        );
+        // An `e` without exponent digits isn't part of the number
+        assert!(matches!(parse_path("M1e 2"), Err(Error::InvalidSvg(_))));
+    }
+
+    #[test]
+    fn arc_flags() {
+        // Flags are single digits and need no separators
+        let path = parse_path("M0 0a1 2 30 1010 0A1,1,0,0,1,5,5").unwrap();
+        match &path.segments[..] {
+            [PathSegment::Arc {
+                to,
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+            }, PathSegment::Arc {
+                to: to_2,
+                large_arc: large_arc_2,
+                sweep: sweep_2,
+                ..
+            }] => {
+                assert_eq!(*to, [10.0, 0.0]);
+                assert_eq!(*radii, [1.0, 2.0]);
+                assert!((rotation - 30f32.to_radians()).abs() < 1e-6);
+                assert!(*large_arc && !*sweep);
+                assert_eq!(*to_2, [5.0, 5.0]);
+                assert!(!*large_arc_2 && *sweep_2);
+            }
+            _ => panic!("expected two arcs"),
+        }
+        assert!(matches!(
+            parse_path("M0 0a1 1 0 2 0 1 1"),
+            Err(Error::InvalidSvg(_))
+        ));
+    }
+
+    #[test]
+    fn closed_subpaths_start_anew() {
+        let path = parse_path("M0 0L1 0L1 1ZL2 2").unwrap();
+        assert_eq!(
+            points(&path),
+            [
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [1.0, 1.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [2.0, 2.0]
+            ]
+        );
+        assert!(matches!(path.segments[3], PathSegment::Move { .. }));
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(matches!(parse_path("L1 1"), Err(Error::InvalidSvg(_))));
+        assert!(matches!(parse_path("M0 0L1"), Err(Error::InvalidSvg(_))));
+        assert!(matches!(parse_path("M0 0X1 1"), Err(Error::InvalidSvg(_))));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::error::Error;
+
+/// Element of an XML document. Text content is not kept, SVG shapes are described
+/// by their attributes only.
+pub(crate) struct Element {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<Element>,
+}
+impl Element {
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses the root element of a document. Declarations, comments, CDATA sections
+/// and processing instructions are skipped.
+pub(crate) fn parse(source: &str) -> Result<Element, Error> {
+    let mut reader = Reader {
+        source,
+        position: 0,
+    };
+    reader.skip_misc();
+    let root = reader.element()?;
+    reader.skip_misc();
+    Ok(root)
+}
+
+struct Reader<'a> {
+    source: &'a str,
+    position: usize,
+}
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidSvg(format!("{} at {}", message, self.position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips everything up to and including `end`
+    fn skip_past(&mut self, end: &str) -> Result<(), Error> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.position += index + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("missing `{}`", end))),
+        }
+    }
+
+    /// Skips whitespace, comments, declarations and processing instructions
+    fn skip_misc(&mut self) {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let end = if rest.starts_with("<!--") {
+                "-->"
+            } else if rest.starts_with("<![CDATA[") {
+                "]]>"
+            } else if rest.starts_with("<?") {
+                "?>"
+            } else if rest.starts_with("<!") {
+                ">"
+            } else if !rest.is_empty() && !rest.starts_with('<') {
+                // Text content
+                "<"
+            } else {
+                return;
+            };
+            if self.skip_past(end).is_err() {
+                self.position = self.source.len();
+                return;
+            }
+            if end == "<" {
+                self.position -= 1;
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Error> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += length;
+        Ok(rest[..length].to_string())
+    }
+
+    fn element(&mut self) -> Result<Element, Error> {
+        if !self.rest().starts_with('<') {
+            return Err(self.error("expected an element"));
+        }
+        self.position += 1;
+        let name = self.name()?;
+        let mut attributes = vec![];
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.position += 2;
+                return Ok(Element {
+                    name,
+                    attributes,
+                    children: vec![],
+                });
+            }
+            if rest.starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error("expected `=`"));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error("expected a quoted value")),
+            };
+            self.position += 1;
+            let length = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| self.error("unterminated value"))?;
+            let value = unescape(&self.rest()[..length]);
+            self.position += length + 1;
+            attributes.push((key, value));
+        }
+
+        let mut children = vec![];
+        loop {
+            self.skip_misc();
+            if self.rest().starts_with("</") {
+                self.position += 2;
+                let end = self.name()?;
+                if end != name {
+                    return Err(self.error(&format!("expected `</{}>`", name)));
+                }
+                self.skip_past(">")?;
+                return Ok(Element {
+                    name,
+                    attributes,
+                    children,
+                });
+            }
+            if self.rest().is_empty() {
+                return Err(self.error(&format!("missing `</{}>`", name)));
+            }
+            children.push(self.element()?);
+        }
+    }
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -2,6 +2,7 @@ mod color;
 mod flags;
 mod handler;
 mod id;
+mod paint;
 pub mod path;
 mod point;
 mod rect;
@@ -12,9 +13,11 @@ pub use color::Color;
 pub use flags::Flags;
 pub use handler::Handler;
 pub use id::id;
+pub use paint::{GradientStop, Paint, Spread};
 pub use path::PathSegment;
 pub use point::Point2D;
 pub use rect::Rect;
+pub(crate) use resource::Frame;
 pub use resource::Resource;
 
 pub fn pack(x: u16, y: u16) -> u32 {
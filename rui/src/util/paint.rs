@@ -0,0 +1,164 @@
+use crate::util::{Color, Point2D};
+
+/// How a gradient continues before its first and after its last stop
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Spread {
+    /// Extends the colors of the first and last stop
+    #[default]
+    Pad,
+    /// Starts over at the first stop
+    Repeat,
+    /// Runs back and forth between the first and the last stop
+    Reflect,
+}
+
+/// A color at a position in `0..=1` along a gradient
+#[derive(Debug, Copy, Clone)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color,
+}
+impl GradientStop {
+    pub fn new(offset: f32, color: impl Into<Color>) -> Self {
+        GradientStop {
+            offset,
+            color: color.into(),
+        }
+    }
+}
+
+/// The fill of a node. The points of gradients are relative to the bounds of the
+/// node, e.g. `[0.5, 0.5]` is the center of the node. The bounds of paths are the
+/// bounds of their control points.
+#[derive(Debug, Clone)]
+pub enum Paint {
+    Solid(Color),
+    /// Varies the color along the line from `start` to `end`
+    Linear {
+        start: Point2D,
+        end: Point2D,
+        stops: Vec<GradientStop>,
+        spread: Spread,
+    },
+    /// Varies the color from the center outwards. The radius is relative to the
+    /// bounds as well, therefore the gradient is an ellipse on non-square nodes.
+    Radial {
+        center: Point2D,
+        radius: f32,
+        stops: Vec<GradientStop>,
+        spread: Spread,
+    },
+    /// Varies the color clockwise around the center, starting at `angle` radians
+    /// from the positive x axis
+    Conic {
+        center: Point2D,
+        angle: f32,
+        stops: Vec<GradientStop>,
+        spread: Spread,
+    },
+}
+
+impl Paint {
+    /// Linear gradient with stops evenly distributed between `start` and `end`
+    pub fn linear(
+        start: impl Into<Point2D>,
+        end: impl Into<Point2D>,
+        colors: impl IntoIterator<Item = impl Into<Color>>,
+    ) -> Self {
+        Paint::Linear {
+            start: start.into(),
+            end: end.into(),
+            stops: Self::even_stops(colors),
+            spread: Spread::Pad,
+        }
+    }
+
+    /// Radial gradient with stops evenly distributed from the center to the radius
+    pub fn radial(
+        center: impl Into<Point2D>,
+        radius: f32,
+        colors: impl IntoIterator<Item = impl Into<Color>>,
+    ) -> Self {
+        Paint::Radial {
+            center: center.into(),
+            radius,
+            stops: Self::even_stops(colors),
+            spread: Spread::Pad,
+        }
+    }
+
+    /// Conic gradient with stops evenly distributed around the center
+    pub fn conic(
+        center: impl Into<Point2D>,
+        angle: f32,
+        colors: impl IntoIterator<Item = impl Into<Color>>,
+    ) -> Self {
+        Paint::Conic {
+            center: center.into(),
+            angle,
+            stops: Self::even_stops(colors),
+            spread: Spread::Pad,
+        }
+    }
+
+    fn even_stops(colors: impl IntoIterator<Item = impl Into<Color>>) -> Vec<GradientStop> {
+        let colors: Vec<Color> = colors.into_iter().map(Into::into).collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        colors
+            .into_iter()
+            .enumerate()
+            .map(|(index, color)| GradientStop::new(index as f32 / last, color))
+            .collect()
+    }
+
+    /// Replaces the stops of a gradient. The stops are sorted by their offset.
+    pub fn stops(mut self, new: impl Into<Vec<GradientStop>>) -> Self {
+        if let Paint::Linear { stops, .. }
+        | Paint::Radial { stops, .. }
+        | Paint::Conic { stops, .. } = &mut self
+        {
+            *stops = new.into();
+            stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        }
+        self
+    }
+
+    /// Sets the spread mode of a gradient
+    pub fn spread(mut self, mode: Spread) -> Self {
+        if let Paint::Linear { spread, .. }
+        | Paint::Radial { spread, .. }
+        | Paint::Conic { spread, .. } = &mut self
+        {
+            *spread = mode;
+        }
+        self
+    }
+
+    /// The solid color, or the color of the first stop of a gradient
+    pub fn color(&self) -> Color {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Linear { stops, .. }
+            | Paint::Radial { stops, .. }
+            | Paint::Conic { stops, .. } => {
+                stops.first().map(|stop| stop.color).unwrap_or(Color::BLACK)
+            }
+        }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Solid(color)
+    }
+}
+impl From<[u8; 3]> for Paint {
+    fn from(c: [u8; 3]) -> Self {
+        Paint::Solid(c.into())
+    }
+}
+impl From<[f32; 3]> for Paint {
+    fn from(c: [f32; 3]) -> Self {
+        Paint::Solid(c.into())
+    }
+}
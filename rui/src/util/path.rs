@@ -1,23 +1,185 @@
 use crate::util::point::Point2D;
+use std::f32::consts::PI;
 
 #[derive(Clone)]
 pub enum PathSegment {
-    Linear { to: Point2D },
-    Arc { to: Point2D, radii: [f32; 2] },
-    QuadraticBezier { to: Point2D, param: Point2D },
-    CubicBezier { to: Point2D, params: [Point2D; 2] },
-    CatmullRom,
+    /// Starts a new subpath at `to`
+    Move {
+        to: Point2D,
+    },
+    Linear {
+        to: Point2D,
+    },
+    /// Elliptical arc like the SVG arc command. The ellipse is rotated by `rotation`
+    /// radians, `large_arc` selects the arc that spans more than 180 degrees and
+    /// `sweep` the arc that is drawn in the direction of increasing angles. Radii
+    /// that are too small to reach `to` are scaled up.
+    Arc {
+        to: Point2D,
+        radii: [f32; 2],
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+    },
+    QuadraticBezier {
+        to: Point2D,
+        param: Point2D,
+    },
+    CubicBezier {
+        to: Point2D,
+        params: [Point2D; 2],
+    },
+    /// Catmull-Rom spline that passes through all points of `through` and ends at `to`
+    CatmullRom {
+        to: Point2D,
+        through: Vec<Point2D>,
+    },
 }
 impl PathSegment {
     pub fn to(&self) -> &Point2D {
         match self {
+            PathSegment::Move { to } => to,
             PathSegment::Linear { to } => to,
             PathSegment::Arc { to, .. } => to,
             PathSegment::QuadraticBezier { to, .. } => to,
             PathSegment::CubicBezier { to, .. } => to,
-            PathSegment::CatmullRom => {
-                unreachable!()
+            PathSegment::CatmullRom { to, .. } => to,
+        }
+    }
+
+    /// Appends the segment starting at `from` as moves, lines and cubic bezier
+    /// curves, which are the only segments that the renderer draws
+    pub(crate) fn to_cubics(&self, from: Point2D, out: &mut Vec<PathSegment>) {
+        match self {
+            PathSegment::Move { .. }
+            | PathSegment::Linear { .. }
+            | PathSegment::CubicBezier { .. } => out.push(self.clone()),
+            // Degree elevation is exact
+            PathSegment::QuadraticBezier { to, param } => out.push(PathSegment::CubicBezier {
+                to: *to,
+                params: [lerp(from, *param, 2.0 / 3.0), lerp(*to, *param, 2.0 / 3.0)],
+            }),
+            PathSegment::Arc {
+                to,
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+            } => arc_to_cubics(from, *to, *radii, *rotation, *large_arc, *sweep, out),
+            PathSegment::CatmullRom { to, through } => {
+                let points: Vec<Point2D> = std::iter::once(from)
+                    .chain(through.iter().copied())
+                    .chain(std::iter::once(*to))
+                    .collect();
+                // The tangents at the ends point to their neighbours
+                let last = points.len() - 1;
+                for i in 0..last {
+                    let previous = points[i.saturating_sub(1)];
+                    let next = points[(i + 2).min(last)];
+                    out.push(PathSegment::CubicBezier {
+                        to: points[i + 1],
+                        params: [
+                            tangent(points[i], previous, points[i + 1]),
+                            tangent(points[i + 1], next, points[i]),
+                        ],
+                    });
+                }
             }
         }
     }
 }
+
+/// Converts the endpoint parameterization of an elliptical arc into its center
+/// parameterization, see the implementation notes of the SVG specification, and
+/// approximates it with a cubic bezier curve per quarter of the ellipse
+fn arc_to_cubics(
+    from: Point2D,
+    to: Point2D,
+    radii: [f32; 2],
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    out: &mut Vec<PathSegment>,
+) {
+    let [mut rx, mut ry] = radii.map(f32::abs);
+    if from == to {
+        return;
+    }
+    if rx == 0.0 || ry == 0.0 {
+        out.push(PathSegment::Linear { to });
+        return;
+    }
+
+    let (sin, cos) = rotation.sin_cos();
+    let (dx, dy) = ((from[0] - to[0]) / 2.0, (from[1] - to[1]) / 2.0);
+    let (x, y) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    let lambda = (x * x) / (rx * rx) + (y * y) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y * y - ry * ry * x * x;
+    let denominator = rx * rx * y * y + ry * ry * x * x;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let (cx, cy) = (coefficient * rx * y / ry, -coefficient * ry * x / rx);
+    let center = [
+        cos * cx - sin * cy + (from[0] + to[0]) / 2.0,
+        sin * cx + cos * cy + (from[1] + to[1]) / 2.0,
+    ];
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let start = angle(1.0, 0.0, (x - cx) / rx, (y - cy) / ry);
+    let mut delta = angle((x - cx) / rx, (y - cy) / ry, (-x - cx) / rx, (-y - cy) / ry);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+
+    let point = |t: f32| {
+        let (sin_t, cos_t) = t.sin_cos();
+        [
+            center[0] + cos * rx * cos_t - sin * ry * sin_t,
+            center[1] + sin * rx * cos_t + cos * ry * sin_t,
+        ]
+    };
+    let derivative = |t: f32| {
+        let (sin_t, cos_t) = t.sin_cos();
+        [
+            -cos * rx * sin_t - sin * ry * cos_t,
+            -sin * rx * sin_t + cos * ry * cos_t,
+        ]
+    };
+
+    let n = (delta.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let step = delta / n as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    for i in 0..n {
+        let (t0, t1) = (start + step * i as f32, start + step * (i + 1) as f32);
+        let (p0, p1) = (point(t0), point(t1));
+        let (d0, d1) = (derivative(t0), derivative(t1));
+        out.push(PathSegment::CubicBezier {
+            // The last point is exactly the end point
+            to: if i == n - 1 { to } else { p1 },
+            params: [
+                [p0[0] + k * d0[0], p0[1] + k * d0[1]],
+                [p1[0] - k * d1[0], p1[1] - k * d1[1]],
+            ],
+        });
+    }
+}
+
+/// Control point of a Catmull-Rom spline at `point`, which is a sixth of the
+/// distance between its neighbours away from it
+fn tangent(point: Point2D, from: Point2D, to: Point2D) -> Point2D {
+    [
+        point[0] + (to[0] - from[0]) / 6.0,
+        point[1] + (to[1] - from[1]) / 6.0,
+    ]
+}
+
+fn lerp(a: Point2D, b: Point2D, t: f32) -> Point2D {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
@@ -1,7 +1,7 @@
 use rui_util::{Extent, Offset};
 use std::cmp::{max, min};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Rect {
     pub offset: Offset,
     pub extent: Extent,
@@ -84,6 +84,13 @@ impl Rect {
         ]
     }
 
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.offset.x as f32
+            && y >= self.offset.y as f32
+            && x < self.offset.x as f32 + self.extent.width as f32
+            && y < self.offset.y as f32 + self.extent.height as f32
+    }
+
     pub fn as_raw(&self) -> [f32; 4] {
         [
             self.offset.x as f32,
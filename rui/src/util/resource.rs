@@ -1,4 +1,382 @@
+use crate::error::Error;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, ImageFormat, ImageResult};
+use rui_util::Extent;
+use std::borrow::Cow;
+use std::future::poll_fn;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+use std::{fs, thread};
+
+/// Source of an image. Encoded images may be PNG, JPEG, GIF, WebP, BMP or ICO files,
+/// animated GIF, PNG and WebP files are played.
+///
+/// Resources are compared by their identity, so that comparing them doesn't read
+/// the images. Files are equal if their paths are, images in memory if they share
+/// the same allocation, e.g. clones of one another.
 #[derive(Clone)]
 pub enum Resource {
-    Path(std::path::PathBuf),
+    /// Encoded image file that is read when the node is mounted
+    Path(PathBuf),
+    /// Encoded image in memory, e.g. a PNG that was downloaded
+    Bytes(Arc<[u8]>),
+    /// Encoded image that is embedded into the binary with `include_bytes!`
+    Static(&'static [u8]),
+    /// Decoded image with four bytes per pixel in rows from top to bottom, e.g. a
+    /// generated one. The pixels are in sRGB with straight alpha.
+    Rgba { extent: Extent, pixels: Arc<[u8]> },
+}
+impl Resource {
+    /// Decoded image, see [Resource::Rgba]
+    ///
+    /// # Panics
+    /// If the number of bytes doesn't match the extent
+    pub fn rgba(extent: Extent, pixels: impl Into<Arc<[u8]>>) -> Self {
+        let pixels = pixels.into();
+        assert_eq!(
+            pixels.len(),
+            4 * extent.width as usize * extent.height as usize,
+            "expected four bytes per pixel"
+        );
+        Resource::Rgba { extent, pixels }
+    }
+}
+
+impl From<PathBuf> for Resource {
+    fn from(path: PathBuf) -> Self {
+        Resource::Path(path)
+    }
+}
+
+impl From<&std::path::Path> for Resource {
+    fn from(path: &std::path::Path) -> Self {
+        Resource::Path(path.to_path_buf())
+    }
+}
+
+impl From<&'static [u8]> for Resource {
+    fn from(bytes: &'static [u8]) -> Self {
+        Resource::Static(bytes)
+    }
+}
+
+impl<const N: usize> From<&'static [u8; N]> for Resource {
+    fn from(bytes: &'static [u8; N]) -> Self {
+        Resource::Static(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Resource {
+    fn from(bytes: Vec<u8>) -> Self {
+        Resource::Bytes(bytes.into())
+    }
+}
+
+impl Resource {
+    /// Address and length of the bytes of an image in memory, decoded images also
+    /// need the same width to be equal
+    fn allocation(&self) -> Option<(*const u8, usize, u32)> {
+        match self {
+            Resource::Path(_) => None,
+            Resource::Bytes(bytes) => Some((bytes.as_ptr(), bytes.len(), 0)),
+            Resource::Static(bytes) => Some((bytes.as_ptr(), bytes.len(), 0)),
+            Resource::Rgba { extent, pixels } => {
+                Some((pixels.as_ptr(), pixels.len(), extent.width))
+            }
+        }
+    }
+}
+
+impl PartialEq for Resource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Resource::Path(a), Resource::Path(b)) => a == b,
+            (Resource::Bytes(_), Resource::Bytes(_))
+            | (Resource::Static(_), Resource::Static(_))
+            | (Resource::Rgba { .. }, Resource::Rgba { .. }) => {
+                self.allocation() == other.allocation()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Resource {}
+
+impl Hash for Resource {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Resource::Path(path) => path.hash(state),
+            _ => self.allocation().hash(state),
+        }
+    }
+}
+
+/// Frame of a decoded image with four bytes per pixel
+pub(crate) struct Frame<'a> {
+    pub(crate) pixels: Cow<'a, [u8]>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// How long the frame is shown, zero for still images
+    pub(crate) delay: Duration,
+}
+
+/// Frames of a decoded image, still images have a single one
+pub(crate) struct Decoded<'a> {
+    pub(crate) frames: Vec<Frame<'a>>,
+    /// How often the frames are played, `None` repeats them forever
+    pub(crate) plays: Option<u32>,
+}
+
+impl Resource {
+    /// Reads and decodes the image. All frames of animated images are decoded.
+    ///
+    /// Files are read on another thread. Fails with [Error::MissingResource] if the file
+    /// can't be read and with [Error::DecodeFailed] if the image is malformed or its
+    /// format isn't supported.
+    pub(crate) async fn decode(&self) -> Result<Decoded<'_>, Error> {
+        let decoded = match self {
+            Resource::Path(path) => {
+                let contents = read(path.clone())
+                    .await
+                    .map_err(|err| Error::MissingResource(path.clone(), err.kind()))?;
+                decode(&contents)
+            }
+            Resource::Bytes(bytes) => decode(bytes),
+            Resource::Static(bytes) => decode(bytes),
+            // The fields are public, so the length isn't necessarily checked by
+            // [Resource::rgba]
+            Resource::Rgba { extent, pixels }
+                if pixels.len() != 4 * extent.width as usize * extent.height as usize =>
+            {
+                return Err(Error::DecodeFailed(format!(
+                    "expected {} bytes for {}x{} pixels, got {}",
+                    4 * extent.width as usize * extent.height as usize,
+                    extent.width,
+                    extent.height,
+                    pixels.len()
+                )));
+            }
+            Resource::Rgba { extent, pixels } => Ok(Decoded {
+                frames: vec![Frame {
+                    pixels: Cow::Borrowed(&pixels[..]),
+                    width: extent.width,
+                    height: extent.height,
+                    delay: Duration::ZERO,
+                }],
+                plays: Some(1),
+            }),
+        };
+        decoded.map_err(|err| Error::DecodeFailed(err.to_string()))
+    }
+}
+
+/// File that is read on another thread and the task that waits for it
+#[derive(Default)]
+struct Reading {
+    contents: Option<io::Result<Vec<u8>>>,
+    waker: Option<Waker>,
+}
+
+/// Reads the file on a thread of its own, so that the task doesn't block the executor
+async fn read(path: PathBuf) -> io::Result<Vec<u8>> {
+    let reading = Arc::new(Mutex::new(Reading::default()));
+    let reader = reading.clone();
+    thread::spawn(move || {
+        let contents = fs::read(path);
+        let mut reading = reader.lock().unwrap();
+        reading.contents = Some(contents);
+        if let Some(waker) = reading.waker.take() {
+            waker.wake();
+        }
+    });
+    poll_fn(|cx| {
+        let mut reading = reading.lock().unwrap();
+        match reading.contents.take() {
+            Some(contents) => Poll::Ready(contents),
+            None => {
+                reading.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    })
+    .await
+}
+
+fn decode(bytes: &[u8]) -> ImageResult<Decoded<'static>> {
+    let frames = match image::guess_format(bytes)? {
+        ImageFormat::Gif => Some(GifDecoder::new(Cursor::new(bytes))?.into_frames()),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            match decoder.is_apng() {
+                true => Some(decoder.apng().into_frames()),
+                false => None,
+            }
+        }
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            match decoder.has_animation() {
+                true => Some(decoder.into_frames()),
+                false => None,
+            }
+        }
+        _ => None,
+    };
+    let frames = match frames {
+        Some(frames) => frames
+            .collect_frames()?
+            .into_iter()
+            .map(|frame| {
+                let delay = Duration::from(frame.delay());
+                let rgba = frame.into_buffer();
+                Frame {
+                    width: rgba.width(),
+                    height: rgba.height(),
+                    pixels: Cow::Owned(rgba.into_raw()),
+                    // Like browsers, frames without a meaningful delay are slowed down
+                    delay: match delay < Duration::from_millis(10) {
+                        true => Duration::from_millis(100),
+                        false => delay,
+                    },
+                }
+            })
+            .collect(),
+        None => {
+            let rgba = image::load_from_memory(bytes)?.to_rgba8();
+            vec![Frame {
+                width: rgba.width(),
+                height: rgba.height(),
+                pixels: Cow::Owned(rgba.into_raw()),
+                delay: Duration::ZERO,
+            }]
+        }
+    };
+    Ok(Decoded {
+        plays: match frames.len() {
+            1 => Some(1),
+            _ => plays(bytes),
+        },
+        frames,
+    })
+}
+
+/// How often an animation is played according to its file, `None` repeats it
+/// forever. The decoders don't report it, therefore the chunk is looked up.
+fn plays(bytes: &[u8]) -> Option<u32> {
+    let find = |tag: &[u8]| {
+        bytes
+            .windows(tag.len())
+            .position(|window| window == tag)
+            .map(|position| &bytes[position + tag.len()..])
+    };
+    let count = match image::guess_format(bytes) {
+        // The NETSCAPE2.0 application extension counts the repetitions after the
+        // first play like browsers do, files without it are played once
+        Ok(ImageFormat::Gif) => match find(b"NETSCAPE2.0") {
+            Some([_, 1, low, high, ..]) => match u16::from_le_bytes([*low, *high]) {
+                0 => 0,
+                repetitions => repetitions as u32 + 1,
+            },
+            _ => return Some(1),
+        },
+        // The acTL chunk counts the frames and then the plays
+        Ok(ImageFormat::Png) => match find(b"acTL") {
+            Some([_, _, _, _, plays @ ..]) if plays.len() >= 4 => {
+                u32::from_be_bytes([plays[0], plays[1], plays[2], plays[3]])
+            }
+            _ => 0,
+        },
+        // The ANIM chunk has its size and the background color before the loop count
+        Ok(ImageFormat::WebP) => match find(b"ANIM") {
+            Some(chunk) if chunk.len() >= 10 => u16::from_le_bytes([chunk[8], chunk[9]]) as u32,
+            _ => 0,
+        },
+        _ => 0,
+    };
+    // Zero repeats forever in all formats
+    match count {
+        0 => None,
+        count => Some(count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resource;
+    use crate::error::Error;
+    use image::{ImageOutputFormat, RgbaImage};
+    use rui_util::Extent;
+    use std::io::{Cursor, ErrorKind};
+    use std::time::Duration;
+
+    const PIXELS: [u8; 8] = [255, 0, 0, 255, 0, 0, 255, 128];
+
+    fn png() -> Vec<u8> {
+        let image = RgbaImage::from_raw(2, 1, PIXELS.to_vec()).unwrap();
+        let mut png = Cursor::new(vec![]);
+        image.write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    fn assert_decodes(resource: &Resource) {
+        let decoded = pollster::block_on(resource.decode()).unwrap();
+        assert_eq!(decoded.plays, Some(1));
+        assert_eq!(decoded.frames.len(), 1);
+        let frame = &decoded.frames[0];
+        assert_eq!([frame.width, frame.height], [2, 1]);
+        assert_eq!(&frame.pixels[..], &PIXELS);
+        assert_eq!(frame.delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn encoded_images_decode() {
+        assert_decodes(&Resource::from(png()));
+        assert_decodes(&Resource::Static(png().leak()));
+
+        let path = std::env::temp_dir().join(format!("rui-resource-{}.png", std::process::id()));
+        std::fs::write(&path, png()).unwrap();
+        assert_decodes(&Resource::from(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn decoded_pixels_must_match_the_extent() {
+        let extent = Extent {
+            width: 2,
+            height: 1,
+        };
+        assert_decodes(&Resource::rgba(extent, PIXELS.to_vec()));
+        let short = Resource::Rgba {
+            extent,
+            pixels: PIXELS[..4].into(),
+        };
+        match pollster::block_on(short.decode()) {
+            Err(Error::DecodeFailed(message)) => {
+                assert_eq!(message, "expected 8 bytes for 2x1 pixels, got 4")
+            }
+            _ => panic!("expected a decode error"),
+        }
+    }
+
+    #[test]
+    fn failures_tell_the_reason() {
+        let path = std::env::temp_dir().join("rui-resource-missing.png");
+        match pollster::block_on(Resource::from(path.clone()).decode()) {
+            Err(Error::MissingResource(missing, ErrorKind::NotFound)) => assert_eq!(missing, path),
+            _ => panic!("expected a missing resource"),
+        }
+        let garbage = Resource::from(vec![1, 2, 3, 4]);
+        assert!(matches!(
+            pollster::block_on(garbage.decode()),
+            Err(Error::DecodeFailed(_))
+        ));
+    }
 }